@@ -6,6 +6,8 @@ use type_info::TypeInfo;
 use std::boxed::Box;
 use std::collections::HashMap;
 use include::read_file_into_ast;
+use io_context;
+use debugger;
 
 use s_expression::SExpression;
 use std::rc::Rc;
@@ -31,12 +33,23 @@ pub enum Ast {
     Literal(Datatype), // consider making the Literal another enum with supported default datatypes.
     Type(TypeInfo), // value in the datatype is useless, just use this to determine parameter type.
     ValueIdentifier(String), // gets the value mapped to a hashmap
+    Located {
+        line: usize, // The line of the source the statement starts on, counting from 1.
+        ast: Box<Ast>, // The statement, which is evaluated as if it wasn't located.
+    },
 }
 
 
 
 impl Ast {
 
+    /// The statement without the line it was parsed from, for code that only cares what kind of statement it is.
+    pub fn unlocated(&self) -> &Ast {
+        match *self {
+            Ast::Located { ref ast, .. } => ast.unlocated(),
+            ref other => other,
+        }
+    }
 
     /// Moves functions and structs to the top of the Ast's top level ExpressionList.
     /// This is done because regardless of where a function is declared, a datatype representing it
@@ -54,7 +67,7 @@ impl Ast {
 
                 for ast in expressions {
 
-                    match *ast {
+                    match *ast.unlocated() {
                         Ast::SExpr(ref sexpr) => {
                             match *sexpr {
                                 SExpression::CreateStruct { .. } => {
@@ -91,13 +104,31 @@ impl Ast {
         }
     }
 
+    /// Gets the name and the optional type annotation out of the identifier used in a declaration.
+    /// This will either be a plain identifier, as in `let a := 5`,
+    /// or a TypeAssignment if the declaration is annotated, as in `let a: Number := 5`.
+    pub fn declaration_identifier(&self) -> Option<(&String, Option<&TypeInfo>)> {
+        match *self {
+            Ast::ValueIdentifier(ref ident) => Some((ident, None)),
+            Ast::SExpr(SExpression::TypeAssignment { ref identifier, ref type_info }) => {
+                if let Ast::ValueIdentifier(ref ident) = **identifier {
+                    if let Ast::Type(ref type_info) = **type_info {
+                        return Some((ident, Some(type_info)))
+                    }
+                }
+                None
+            }
+            _ => None
+        }
+    }
+
     /// Determines if a main function exists.
     /// This should be used to determine if calling the main function after evaluating the AST is necessary.
     pub fn main_fn_exists(&self) -> bool {
         match *self {
             Ast::ExpressionList(ref expressions) => {
                 for ast in expressions {
-                    match *ast {
+                    match *ast.unlocated() {
                         Ast::SExpr(ref sexpr) => {
                             if let SExpression::DeclareFunction {
                                 ref identifier,
@@ -122,10 +153,25 @@ impl Ast {
 
     /// Calls the main function.
     /// This should be used if a main function has been determined to exist.
-    pub fn execute_main(&self, map: &mut VariableStore) -> LangResult {
+    ///
+    /// main can either take no parameters, or a single `[String]` parameter that will hold the supplied arguments.
+    pub fn execute_main(&self, map: &mut VariableStore, arguments: &[String]) -> LangResult {
+        let string_array: TypeInfo = TypeInfo::Array(Box::new(TypeInfo::String));
+        let main_type: Option<TypeInfo> = map.get(MAIN_FUNCTION_NAME).map(|main| TypeInfo::from(main.as_ref().clone()));
+        let parameters: Vec<Ast> = match main_type {
+            Some(TypeInfo::Function { ref parameters, .. }) if parameters.is_empty() => vec![],
+            Some(TypeInfo::Function { ref parameters, .. }) if *parameters == vec![string_array] => {
+                vec![Ast::Literal(Datatype::Array {
+                    value: arguments.iter().map(|argument| Rc::new(Datatype::String(argument.clone()))).collect(),
+                    type_: TypeInfo::String,
+                })]
+            }
+            Some(other_type) => return Err(LangError::InvalidMainSignature(other_type)),
+            None => vec![], // Let the function call report that main doesn't exist.
+        };
         let executing_ast = Ast::SExpr(SExpression::ExecuteFn {
             identifier: Box::new(Ast::ValueIdentifier(MAIN_FUNCTION_NAME.to_string())),
-            parameters: Box::new(Ast::ExpressionList(vec![])),
+            parameters: Box::new(Ast::ExpressionList(parameters)),
         });
 
         executing_ast.evaluate(map)
@@ -153,15 +199,33 @@ impl Ast {
                     SExpression::Modulo(ref lhs, ref rhs) => {
                         lhs.evaluate(map)?.as_ref().clone() % rhs.evaluate(map)?.as_ref().clone()
                     }
+                    SExpression::Power(ref lhs, ref rhs) => {
+                        lhs.evaluate(map)?.as_ref().clone().pow(rhs.evaluate(map)?.as_ref().clone())
+                    }
+                    SExpression::BitwiseAnd(ref lhs, ref rhs) => {
+                        lhs.evaluate(map)?.as_ref().clone() & rhs.evaluate(map)?.as_ref().clone()
+                    }
+                    SExpression::BitwiseOr(ref lhs, ref rhs) => {
+                        lhs.evaluate(map)?.as_ref().clone() | rhs.evaluate(map)?.as_ref().clone()
+                    }
+                    SExpression::BitwiseXor(ref lhs, ref rhs) => {
+                        lhs.evaluate(map)?.as_ref().clone() ^ rhs.evaluate(map)?.as_ref().clone()
+                    }
+                    SExpression::ShiftLeft(ref lhs, ref rhs) => {
+                        lhs.evaluate(map)?.as_ref().clone() << rhs.evaluate(map)?.as_ref().clone()
+                    }
+                    SExpression::ShiftRight(ref lhs, ref rhs) => {
+                        lhs.evaluate(map)?.as_ref().clone() >> rhs.evaluate(map)?.as_ref().clone()
+                    }
                     SExpression::Equals(ref lhs, ref rhs) => {
-                        if lhs.evaluate(map)? == rhs.evaluate(map)? {
+                        if lhs.evaluate(map)?.lang_eq(&*rhs.evaluate(map)?) {
                             return Ok(Rc::new(Datatype::Bool(true)));
                         } else {
                             return Ok(Rc::new(Datatype::Bool(false)));
                         }
                     }
                     SExpression::NotEquals(ref lhs, ref rhs) => {
-                        if lhs.evaluate(map)? != rhs.evaluate(map)? {
+                        if !lhs.evaluate(map)?.lang_eq(&*rhs.evaluate(map)?) {
                             return Ok(Rc::new(Datatype::Bool(true)));
                         } else {
                             return Ok(Rc::new(Datatype::Bool(false)));
@@ -254,7 +318,7 @@ impl Ast {
                     SExpression::ConstDeclaration {
                        identifier: ref lhs,
                         ast: ref rhs,
-                    } |
+                    } => return declare_variable(lhs, rhs, map),
                     SExpression::Assignment {
                         identifier: ref lhs,
                         ast: ref rhs,
@@ -305,6 +369,30 @@ impl Ast {
                         }
                         return Ok(evaluated_loop); // leave block
                     }
+                    SExpression::TryCatch {
+                        ref body,
+                        ref error_identifier,
+                        ref handler,
+                    } => {
+                        let error: LangError = match body.evaluate(map) {
+                            Ok(value) => return Ok(value),
+                            Err(LangError::StoppedByDebugger) => return Err(LangError::StoppedByDebugger),
+                            Err(error) => error,
+                        };
+                        if let Ast::ValueIdentifier(ref ident) = **error_identifier {
+                            // Variables the body assigned before the error keep their new values.
+                            // The error is only visible to the handler, so whatever it replaced is restored afterwards.
+                            let replaced: Option<Rc<Datatype>> = map.insert(ident.clone(), Rc::new(Datatype::String(error.to_string())));
+                            let handled: LangResult = handler.evaluate(map);
+                            match replaced {
+                                Some(replaced) => map.insert(ident.clone(), replaced),
+                                None => map.remove(ident),
+                            };
+                            handled
+                        } else {
+                            Err(LangError::IdentifierDoesntExist)
+                        }
+                    }
                     SExpression::AccessArray {
                         ref identifier,
                         ref index,
@@ -312,19 +400,16 @@ impl Ast {
                         let datatype: &Datatype = &*identifier.evaluate(map)?;
                         match *datatype {
                             Datatype::Array { ref value, .. } => {
-                                let possible_index: Rc<Datatype> = index.evaluate(map)?;
-                                match *possible_index {
-                                    Datatype::Number(resolved_index) => {
-                                        if resolved_index >= 0 {
-                                            match value.get(resolved_index as usize) {
-                                                Some(indexed_rc_to_value) => Ok(indexed_rc_to_value.clone()),
-                                                None => Err(LangError::OutOfBoundsArrayAccess),
-                                            }
-                                        } else {
-                                            Err(LangError::NegativeIndex(resolved_index))
-                                        }
-                                    }
-                                    _ => Err(LangError::InvalidIndexType(possible_index.as_ref().clone())),
+                                match value.get(evaluate_index(index, map)?) {
+                                    Some(indexed_rc_to_value) => Ok(indexed_rc_to_value.clone()),
+                                    None => Err(LangError::OutOfBoundsArrayAccess),
+                                }
+                            }
+                            // Strings are indexed by character, producing a String holding only that character.
+                            Datatype::String(ref string) => {
+                                match string.chars().nth(evaluate_index(index, map)?) {
+                                    Some(character) => Ok(Rc::new(Datatype::String(character.to_string()))),
+                                    None => Err(LangError::OutOfBoundsArrayAccess),
                                 }
                             }
                             _ => {
@@ -345,6 +430,7 @@ impl Ast {
                             _ => Err(LangError::TriedToGetLengthOfNonArray)
                         }
                     }
+                    SExpression::CreateArray(ref elements) => return create_array(elements, map),
                     SExpression::Range { ref start, ref end} => {
                         let start_val: i32 = match *start.evaluate(map)? {
                             Datatype::Number(num) => num,
@@ -378,17 +464,9 @@ impl Ast {
                         ref parameters,
                     } => return execute_function(identifier, parameters, map),
                     SExpression::Print(ref expr) => {
-                        let datatype_to_print = expr.evaluate(map)?;
-                        if let Ast::ValueIdentifier(ref identifier) = **expr {
-                            if let Datatype::Struct { .. } = *datatype_to_print {
-                                print!("{}{}", identifier, datatype_to_print)
-                            } else {
-                                print!("{}", datatype_to_print);
-                            }
-                        } else {
-                            print!("{}", datatype_to_print);
-                        }
-                        Ok(datatype_to_print)
+                        let text: String = format!("{}", expr.evaluate(map)?);
+                        io_context::write_stdout(&text)?;
+                        Ok(Rc::new(Datatype::String(text)))
                     }
                     SExpression::Include(ref expr) => {
                         match *expr.evaluate(map)? {
@@ -404,11 +482,19 @@ impl Ast {
                     }
                     SExpression::Negate(ref expr) => {
                         match *expr.evaluate(map)? {
-                            Datatype::Number(num) => Ok(Rc::new(Datatype::Number(-num))),
+                            Datatype::Number(num) => {
+                                match num.checked_neg() {
+                                    Some(negated) => Ok(Rc::new(Datatype::Number(negated))),
+                                    None => Err(LangError::IntegerOverflow),
+                                }
+                            }
                             Datatype::Float(float) => Ok(Rc::new(Datatype::Float(-float))),
                             _ => Err(LangError::NegateNotNumber)
                         }
                     }
+                    SExpression::BitwiseNot(ref expr) => {
+                        !expr.evaluate(map)?.as_ref().clone()
+                    }
                     SExpression::Invert(ref expr) => {
                         match *expr.evaluate(map)? {
                             Datatype::Bool(bool) => Ok(Rc::new(Datatype::Bool(!bool))),
@@ -417,13 +503,23 @@ impl Ast {
                     }
                     SExpression::Increment(ref expr) => {
                         match *expr.evaluate(map)? {
-                            Datatype::Number(number) => Ok(Rc::new(Datatype::Number(number + 1))),
+                            Datatype::Number(number) => {
+                                match number.checked_add(1) {
+                                    Some(incremented) => Ok(Rc::new(Datatype::Number(incremented))),
+                                    None => Err(LangError::IntegerOverflow),
+                                }
+                            }
                             _ => Err(LangError::IncrementNonNumber),
                         }
                     }
                     SExpression::Decrement(ref expr) => {
                         match *expr.evaluate(map)? {
-                            Datatype::Number(number) => Ok(Rc::new(Datatype::Number(number - 1))),
+                            Datatype::Number(number) => {
+                                match number.checked_sub(1) {
+                                    Some(decremented) => Ok(Rc::new(Datatype::Number(decremented))),
+                                    None => Err(LangError::IntegerOverflow),
+                                }
+                            }
                             _ => Err(LangError::DecrementNonNumber),
                         }
                     }
@@ -434,6 +530,7 @@ impl Ast {
             Ast::ExpressionList(ref expressions) => {
                 let mut val: Rc<Datatype> = Rc::new(Datatype::None); // TODO, consider making this return an error if the expressions vector is empty
                 for e in expressions {
+                    debugger::before_statement(e, map)?;
                     val = e.evaluate(map)?;
                 }
                 Ok(val) // return the last evaluated expression;
@@ -468,8 +565,105 @@ impl Ast {
                     )),
                 }
             }
+            Ast::Located { ref ast, .. } => ast.evaluate(map),
+        }
+    }
+}
+
+/// Evaluate the index of an array or string access, which must be a non-negative Number.
+fn evaluate_index(index: &Ast, map: &mut VariableStore) -> Result<usize, LangError> {
+    let possible_index: Rc<Datatype> = index.evaluate(map)?;
+    match *possible_index {
+        Datatype::Number(resolved_index) if resolved_index >= 0 => Ok(resolved_index as usize),
+        Datatype::Number(resolved_index) => Err(LangError::NegativeIndex(resolved_index)),
+        _ => Err(LangError::InvalidIndexType(possible_index.as_ref().clone())),
+    }
+}
+
+/// Evaluate the right hand side of a declaration and store it under the declared identifier.
+/// If the declaration has a type annotation, the value must match the annotated type.
+/// Arrays without a known element type, such as `[]`, take on the element type specified by the annotation.
+fn declare_variable(identifier: &Ast, ast: &Ast, map: &mut VariableStore) -> LangResult {
+    let (ident, annotation) = match identifier.declaration_identifier() {
+        Some(declared) => declared,
+        None => return Err(LangError::IdentifierDoesntExist),
+    };
+    let mut cloned_map = map.clone(); // since this is a clone, the required righthand expressions will be evaluated in their own 'stack', this modified hashmap will be cleaned up post assignment.
+    let mut value: Rc<Datatype> = ast.evaluate(&mut cloned_map)?;
+
+    if let Some(annotated_type) = annotation {
+        let value_type: TypeInfo = TypeInfo::from(value.as_ref().clone());
+        match resolve_struct_types(annotated_type, map)?.unify(&value_type) {
+            Some(TypeInfo::Array(element_type)) => {
+                if let Datatype::Array { value: ref elements, .. } = *value.clone() {
+                    value = Rc::new(Datatype::Array {
+                        value: elements.clone(),
+                        type_: *element_type,
+                    });
+                }
+            }
+            Some(_) => {}
+            None => {
+                return Err(LangError::TypeError {
+                    expected: annotated_type.clone(),
+                    found: value_type,
+                })
+            }
         }
     }
+
+    map.insert(ident.clone(), value.clone());
+    Ok(value)
+}
+
+/// The types of struct instances are described by their fields,
+/// so any struct names in the type need to be replaced with the fields of the struct before it can be compared to a value.
+fn resolve_struct_types(type_info: &TypeInfo, map: &VariableStore) -> Result<TypeInfo, LangError> {
+    match *type_info {
+        TypeInfo::StructType { ref identifier } => {
+            match map.get(identifier) {
+                Some(datatype) => {
+                    match **datatype {
+                        Datatype::StructType { ref type_information, .. } => Ok(type_information.clone()),
+                        _ => Err(LangError::ExpectedIdentifierToBeStructType { found: identifier.clone() }),
+                    }
+                }
+                None => Err(LangError::IdentifierDoesntExist),
+            }
+        }
+        TypeInfo::Array(ref element_type) => Ok(TypeInfo::Array(Box::new(resolve_struct_types(element_type, map)?))),
+        _ => Ok(type_info.clone())
+    }
+}
+
+/// Evaluate each of the expressions that make up the array's elements.
+/// Every element must have the same type, which becomes the type of the array.
+/// An array without elements can hold Any type.
+fn create_array(elements: &Ast, map: &mut VariableStore) -> LangResult {
+    if let Ast::ExpressionList(ref expressions) = *elements {
+        let mut values: Vec<Rc<Datatype>> = vec![];
+        let mut array_type: TypeInfo = TypeInfo::Any;
+        for expression in expressions {
+            let value: Rc<Datatype> = expression.evaluate(map)?;
+            let value_type: TypeInfo = TypeInfo::from(value.as_ref().clone());
+            array_type = match array_type.unify(&value_type) {
+                Some(unified_type) => unified_type,
+                None => {
+                    return Err(LangError::MismatchedArrayElementTypes {
+                        expected: array_type,
+                        found: value_type,
+                    })
+                }
+            };
+            values.push(value);
+        }
+        Ok(Rc::new(Datatype::Array {
+            value: values,
+            type_: array_type,
+        }))
+    } else {
+        Err(LangError::ParserShouldHaveRejected)
+    }
 }
 
 /// Resolve the first expression to a struct.
@@ -482,7 +676,7 @@ fn access_struct_field(
     map: &mut VariableStore,
 ) -> LangResult {
     // if struct_identifier produces a struct when evaluated
-    if let Datatype::Struct { map: ref struct_map } = *struct_identifier.evaluate(map)? {
+    if let Datatype::Struct { map: ref struct_map, .. } = *struct_identifier.evaluate(map)? {
         if let Ast::ValueIdentifier(ref field_identifier) = *field_identifier {
             match struct_map.get(field_identifier) {
                 Some(struct_field_datatype) => return Ok(Rc::new(struct_field_datatype.clone())),
@@ -510,6 +704,7 @@ fn declare_struct(
         if let Ast::ExpressionList(ref expressions) = *struct_type_assignments {
 
             let mut struct_map: HashMap<String, TypeInfo> = HashMap::new();
+            let mut field_order: Vec<String> = vec![];
 
             for assignment_expr in expressions {
                 if let &Ast::SExpr(ref sexpr) = assignment_expr {
@@ -521,6 +716,7 @@ fn declare_struct(
                         if let Ast::ValueIdentifier(ref field_id) = **field_identifier_expr {
                             if let Ast::Type(ref field_type) = **field_type_expr {
                                 struct_map.insert(field_id.clone(), field_type.clone());
+                                field_order.push(field_id.clone());
                             } else {
                                 return Err(LangError::FieldTypeNotSupplied);
                             }
@@ -535,7 +731,7 @@ fn declare_struct(
                 }
             }
             let new_struct_type = TypeInfo::Struct { map: struct_map };
-            let retval = Rc::new(Datatype::StructType{ identifier: struct_type_identifier.clone(), type_information: new_struct_type});
+            let retval = Rc::new(Datatype::StructType{ identifier: struct_type_identifier.clone(), type_information: new_struct_type, field_order });
             map.insert(struct_type_identifier.clone(), retval.clone());
             return Ok(retval);
         } else {
@@ -556,7 +752,7 @@ fn declare_struct(
 fn create_struct(expr1: &Ast, expr2: &Ast, map: &mut VariableStore) -> LangResult {
     // This expects the expr1 to be an Identifier that resolves to be a struct definition, or the struct definition itself.
     match *expr1.evaluate(map)? {
-        Datatype::StructType { ref identifier, ref type_information } => {
+        Datatype::StructType { ref identifier, ref type_information, ref field_order } => {
             match *type_information {
                 TypeInfo::Struct { map: ref struct_type_map } => {
                     if let Ast::ExpressionList(ref assignment_expressions) = *expr2 {
@@ -602,7 +798,16 @@ fn create_struct(expr1: &Ast, expr2: &Ast, map: &mut VariableStore) -> LangResul
                                 return Err(LangError::NonAssignmentInStructInit);
                             }
                         }
-                        return Ok(Rc::new(Datatype::Struct { map: new_struct_map })); // Return the new struct.
+                        // Only the fields that were assigned are kept, in the order they were declared.
+                        let field_order: Vec<String> = field_order.iter()
+                            .filter(|field| new_struct_map.contains_key(*field))
+                            .cloned()
+                            .collect();
+                        return Ok(Rc::new(Datatype::Struct {
+                            identifier: Some(identifier.clone()),
+                            field_order,
+                            map: new_struct_map,
+                        })); // Return the new struct.
                     } else {
                         return Err(LangError::StructBodyNotSupplied); // not entirely accurate
                     }
@@ -659,7 +864,19 @@ fn execute_function(
 
 
     // Take an existing function by (by grabbing the function using an identifier, which should resolve to a function)
-    match *identifier.evaluate(&mut cloned_map)? {
+    let function: Rc<Datatype> = identifier.evaluate(&mut cloned_map)?;
+    debugger::calling(identifier, || call_function(&function, evaluated_parameters, &mut cloned_map))
+}
+
+/// Call a function value with arguments that have already been evaluated.
+/// The function's parameters are assigned into the provided stack, so callers should pass a copy of their own stack.
+/// This allows builtins to call functions that are passed to them as arguments.
+pub fn call_function(
+    function: &Datatype,
+    evaluated_parameters: Vec<Rc<Datatype>>,
+    cloned_map: &mut VariableStore,
+) -> LangResult {
+    match *function {
         Datatype::Function {
             ref parameters,
             ref body,
@@ -736,21 +953,21 @@ fn execute_function(
                         // they can be associated with the identifiers, and the identifiers can be used in the function body later.
                         for rhs in rhs_replaced_with_evaluated_parameters_results {
                             let rhs = rhs?; // return the error if present
-                            rhs.evaluate(&mut cloned_map)?; // create the assignment
+                            rhs.evaluate(cloned_map)?; // create the assignment
                         }
                     } else {
                         return Err(LangError::ParameterLengthMismatch);
                     }
 
                     // Evaluate the body of the function
-                    let output: Rc<Datatype> = body.evaluate(&mut cloned_map)?;
+                    let output: Rc<Datatype> = body.evaluate(cloned_map)?;
 //                    let expected_return_type: TypeInfo = return_type.clone();
                     let expected_return_type: TypeInfo = match *return_type {
                         TypeInfo::StructType { ref identifier } => {
-                            match map.get(identifier) {
+                            match cloned_map.get(identifier) {
                                 Some(datatype) => {
                                     let datatype: &Datatype = &**datatype;
-                                    if let &Datatype::StructType{ ref identifier, ref type_information } = datatype {
+                                    if let Datatype::StructType{ ref identifier, ref type_information, .. } = *datatype {
                                         type_information.clone()
                                     } else {
                                         return Err(LangError::ExpectedIdentifierToBeStructType {
@@ -792,6 +1009,8 @@ fn execute_function(
                 _ => return Err(LangError::ParserShouldHaveRejected), // The parser should have put the parameters in the form ExpressionList(expression_with_assignment, expression_with_assignment, ...)
             }
         }
+        Datatype::BuiltinFunction(ref builtin) => builtin.call(evaluated_parameters, cloned_map),
+        Datatype::NativeFunction(ref native) => native.call(&evaluated_parameters),
         _ => Err(LangError::ExecuteNonFunction),
     }
 }
@@ -1052,6 +1271,48 @@ mod test {
         assert_eq!(Datatype::Number(2), *ast.evaluate(&mut map).unwrap())
     }
 
+    fn try_catch(body: Ast, handler: Ast) -> Ast {
+        Ast::SExpr(SExpression::TryCatch {
+            body: Box::new(body),
+            error_identifier: Box::new(Ast::ValueIdentifier("e".to_string())),
+            handler: Box::new(handler),
+        })
+    }
+
+    #[test]
+    fn try_catch_without_error_test() {
+        let mut map: VariableStore = VariableStore::new();
+        let ast = try_catch(Ast::Literal(Datatype::Number(7)), Ast::Literal(Datatype::Number(2)));
+        assert_eq!(Datatype::Number(7), *ast.evaluate(&mut map).unwrap())
+    }
+
+    #[test]
+    fn try_catch_error_message_test() {
+        let mut map: VariableStore = VariableStore::new();
+        let ast = try_catch(
+            Ast::SExpr(SExpression::Divide(
+                Box::new(Ast::Literal(Datatype::Number(1))),
+                Box::new(Ast::Literal(Datatype::Number(0))),
+            )),
+            Ast::ValueIdentifier("e".to_string()),
+        );
+        assert_eq!(Datatype::String("Division by zero".to_string()), *ast.evaluate(&mut map).unwrap());
+        assert_eq!(None, map.get("e")); // The error is only visible inside the handler.
+    }
+
+    #[test]
+    fn try_catch_handler_error_test() {
+        let mut map: VariableStore = VariableStore::new();
+        let ast = try_catch(
+            Ast::ValueIdentifier("missing".to_string()),
+            Ast::SExpr(SExpression::Divide(
+                Box::new(Ast::Literal(Datatype::Number(1))),
+                Box::new(Ast::Literal(Datatype::Number(0))),
+            )),
+        );
+        assert_eq!(LangError::DivideByZero, ast.evaluate(&mut map).unwrap_err())
+    }
+
     #[test]
     fn basic_function_test() {
         let mut map: VariableStore = VariableStore::new();
@@ -1163,6 +1424,16 @@ mod test {
         assert_eq!(Datatype::Number(12), *ast.evaluate(&mut map).unwrap())
     }
 
+    #[test]
+    fn string_access_test() {
+        let mut map: VariableStore = VariableStore::new();
+        let ast: Ast = Ast::SExpr(SExpression::AccessArray {
+            identifier: Box::new(Ast::Literal(Datatype::String("héllo".to_string()))),
+            index: Box::new(Ast::Literal(Datatype::Number(1))), // indexed by character, not byte
+        });
+        assert_eq!(Datatype::String("é".to_string()), *ast.evaluate(&mut map).unwrap())
+    }
+
     #[test]
     fn array_incorrect_access_test() {
         let mut map: VariableStore = VariableStore::new();
@@ -1202,7 +1473,11 @@ mod test {
         inner_struct_hash_map.insert("Field1".to_string(), TypeInfo::Number);
         expected_map.insert(
             "MyStruct".to_string(),
-            Rc::new(Datatype::StructType{ identifier: String::from("MyStruct"), type_information: TypeInfo::Struct { map: inner_struct_hash_map }} ),
+            Rc::new(Datatype::StructType{
+                identifier: String::from("MyStruct"),
+                type_information: TypeInfo::Struct { map: inner_struct_hash_map },
+                field_order: vec!["Field1".to_string()],
+            }),
         );
         assert_eq!(expected_map, map)
     }
@@ -1239,7 +1514,11 @@ mod test {
         inner_struct_hash_map.insert("Field1".to_string(), Datatype::Number(8));
 
         assert_eq!(
-            &Datatype::Struct { map: inner_struct_hash_map },
+            &Datatype::Struct {
+                identifier: Some("MyStruct".to_string()),
+                field_order: vec!["Field1".to_string()],
+                map: inner_struct_hash_map,
+            },
             struct_instance
         )
     }
@@ -3,13 +3,21 @@ use std::ops::Add;
 use std::ops::Mul;
 use std::ops::Div;
 use std::ops::Rem;
+use std::ops::BitAnd;
+use std::ops::BitOr;
+use std::ops::BitXor;
+use std::ops::Shl;
+use std::ops::Shr;
+use std::ops::Not;
 
 use std::cmp::PartialOrd;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 
 use lang_result::*;
 use ast::abstract_syntax_tree::Ast;
 use ast::type_info::TypeInfo;
+use std_functions::{Builtin, NativeFunction};
 
 use std::collections::HashMap;
 use std::fmt;
@@ -34,11 +42,24 @@ pub enum Datatype {
         body: Box<Ast>,
         return_type: TypeInfo
     },
-    Struct { map: HashMap<String, Datatype> }, // Actualized struct that holds real data.
-    StructType{ identifier: String, type_information: TypeInfo}, // type_information will point to a TypeInfo that is a Struct{map: HashMap<String, TypeInfo> } that encodes the types used in the sturct
+    BuiltinFunction(Builtin), // A function implemented in Rust.
+    NativeFunction(NativeFunction), // A function supplied by the program embedding the interpreter.
+    /// Actualized struct that holds real data.
+    /// The identifier names the struct's type, or is None for the structs returned by builtins like `zip`,
+    /// and field_order lists the fields in the order they were declared, so they can be printed in that order.
+    Struct { identifier: Option<String>, field_order: Vec<String>, map: HashMap<String, Datatype> },
+    StructType{ identifier: String, type_information: TypeInfo, field_order: Vec<String> }, // type_information will point to a TypeInfo that is a Struct{map: HashMap<String, TypeInfo> } that encodes the types used in the sturct
 }
 
 
+/// Values that would be wider than this on one line are split over several lines.
+const MAX_LINE_WIDTH: usize = 80;
+/// The indentation used for each level of nesting when a value is split over several lines.
+const INDENT: &str = "    ";
+
+/// Shows values the way they are written in programs, like `[1, 2, 3]` or `Point { x: 1, y: 2 }`.
+/// A String on its own is shown as it is, so `print` writes its contents, but Strings inside arrays and structs are quoted.
+/// Arrays and structs that don't fit on one line are split over several, with their contents indented.
 impl fmt::Display for Datatype {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -46,60 +67,116 @@ impl fmt::Display for Datatype {
             Datatype::Float(ref value) => write!(f, "{}", value),
             Datatype::String(ref value) => write!(f, "{}", value),
             Datatype::Bool(ref value) => write!(f, "{}", value),
-            Datatype::Array {
-                ref value,
-                ref type_,
-            } => write!(f, "[{:?}]{:?}", value, type_),
+            Datatype::Array { .. } | Datatype::Struct { .. } => write!(f, "{}", self.representation()),
             Datatype::None => write!(f, "NONE"),
-            Datatype::Function {
-                ref parameters,
-                body: ref _body,
-                ref return_type,
-            } => write!(f, "{:?} -> {:?}", parameters, return_type),
-            Datatype::Struct { ref map } => write!(f, "{{{:?}}}", map),
-            Datatype::StructType{ ref identifier, ref type_information}  => write!(f, "{:?}: {:?}", identifier, type_information),
+            Datatype::Function { .. } => write!(f, "{}", TypeInfo::from(self.clone())),
+            Datatype::BuiltinFunction(ref builtin) => write!(f, "{}", builtin.name()),
+            Datatype::NativeFunction(ref native) => write!(f, "{}", native.name),
+            Datatype::StructType { ref identifier, ref type_information, ref field_order } => {
+                match *type_information {
+                    TypeInfo::Struct { ref map } if !field_order.is_empty() => {
+                        let fields: Vec<String> = field_order.iter()
+                            .map(|field| format!("{}: {}", field, map[field]))
+                            .collect();
+                        write!(f, "struct {} {{ {} }}", identifier, fields.join(", "))
+                    }
+                    _ => write!(f, "struct {} {{}}", identifier),
+                }
+            }
+        }
+    }
+}
+
+impl Datatype {
+    /// Shows the value like `Display` does, except that Strings are always quoted and Floats always have a decimal point,
+    /// so the REPL can show what type of value was produced.
+    pub fn representation(&self) -> String {
+        representation(self, 0)
+    }
+}
+
+/// Shows the value on one line if it fits, given how deeply it is nested, or otherwise splits its elements or fields over several lines.
+fn representation(value: &Datatype, depth: usize) -> String {
+    let one_line: String = one_line_representation(value);
+    if INDENT.len() * depth + one_line.len() <= MAX_LINE_WIDTH {
+        return one_line;
+    }
+    let indentation: String = INDENT.repeat(depth + 1);
+    match *value {
+        Datatype::Array { ref value, .. } if !value.is_empty() => {
+            let elements: Vec<String> = value.iter()
+                .map(|element| format!("{}{}", indentation, representation(element, depth + 1)))
+                .collect();
+            format!("[\n{}\n{}]", elements.join(",\n"), INDENT.repeat(depth))
+        }
+        Datatype::Struct { ref identifier, ref field_order, ref map } if !map.is_empty() => {
+            let fields: Vec<String> = ordered_fields(field_order, map).into_iter()
+                .map(|(field, value)| format!("{}{}: {}", indentation, field, representation(value, depth + 1)))
+                .collect();
+            format!("{}{{\n{}\n{}}}", struct_prefix(identifier), fields.join(",\n"), INDENT.repeat(depth))
         }
+        _ => one_line,
     }
 }
 
+fn one_line_representation(value: &Datatype) -> String {
+    match *value {
+        Datatype::String(ref value) => format!("{:?}", value),
+        Datatype::Float(ref value) => format!("{:?}", value),
+        Datatype::Array { ref value, .. } => {
+            let elements: Vec<String> = value.iter().map(|element| one_line_representation(element)).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        Datatype::Struct { ref identifier, ref field_order, ref map } => {
+            if map.is_empty() {
+                return format!("{}{{}}", struct_prefix(identifier));
+            }
+            let fields: Vec<String> = ordered_fields(field_order, map).into_iter()
+                .map(|(field, value)| format!("{}: {}", field, one_line_representation(value)))
+                .collect();
+            format!("{}{{ {} }}", struct_prefix(identifier), fields.join(", "))
+        }
+        ref other => other.to_string(),
+    }
+}
+
+/// Structs are shown with the name of their type, unless they were made by a builtin.
+fn struct_prefix(identifier: &Option<String>) -> String {
+    match *identifier {
+        Some(ref identifier) => format!("{} ", identifier),
+        None => String::new(),
+    }
+}
+
+/// The fields of a struct in the order they were declared in.
+/// Any fields missing from the order come last, in alphabetical order.
+fn ordered_fields<'a>(field_order: &'a [String], map: &'a HashMap<String, Datatype>) -> Vec<(&'a String, &'a Datatype)> {
+    let mut fields: Vec<(&String, &Datatype)> = field_order.iter()
+        .filter_map(|field| map.get_key_value(field))
+        .collect();
+    let mut unordered: Vec<(&String, &Datatype)> = map.iter()
+        .filter(|&(field, _)| !field_order.contains(field))
+        .collect();
+    unordered.sort_by(|a, b| a.0.cmp(b.0));
+    fields.extend(unordered);
+    fields
+}
+
 
 impl PartialOrd for Datatype {
     fn partial_cmp(&self, rhs: &Datatype) -> Option<Ordering> {
         match *self {
             Datatype::Number(lhs) => {
-                if let Datatype::Number(rhs_number) = *rhs {
-                    if lhs < rhs_number {
-                        Some(Ordering::Less)
-                    } else if lhs > rhs_number {
-                        Some(Ordering::Greater)
-                    } else {
-                        Some(Ordering::Equal)
-                    }
-                } else {
-                    None
+                match *rhs {
+                    Datatype::Number(rhs) => Some(lhs.cmp(&rhs)),
+                    Datatype::Float(rhs) => (lhs as f64).partial_cmp(&rhs),
+                    _ => None
                 }
             }
             Datatype::Float(lhs) => {
                 match *rhs {
-                    Datatype::Float(rhs) => {
-                        if lhs < rhs {
-                            Some(Ordering::Less)
-                        } else if lhs > rhs {
-                            Some(Ordering::Greater)
-                        } else {
-                            Some(Ordering::Equal)
-                        }
-                    }
-                    Datatype::Number(rhs) => {
-                        let rhs = rhs as f64;
-                        if lhs < rhs {
-                            Some(Ordering::Less)
-                        } else if lhs > rhs {
-                            Some(Ordering::Greater)
-                        } else {
-                            Some(Ordering::Equal)
-                        }
-                    }
+                    Datatype::Float(rhs) => lhs.partial_cmp(&rhs),
+                    Datatype::Number(rhs) => lhs.partial_cmp(&(rhs as f64)),
                     _ => None
                 }
             }
@@ -157,8 +234,8 @@ impl PartialOrd for Datatype {
                 }
             }
             //Datatype::Function
-            Datatype::Struct { map: ref lhs_map } => {
-                if let &Datatype::Struct { map: ref rhs_map } = rhs {
+            Datatype::Struct { map: ref lhs_map, .. } => {
+                if let Datatype::Struct { map: ref rhs_map, .. } = *rhs {
                     for (lhs_key, lhs_value) in lhs_map.into_iter() {
                         // clone the rhs value out of the rhs_map so it can be compared.
                         if rhs_map.get(lhs_key) == Some(lhs_value) {
@@ -178,51 +255,214 @@ impl PartialOrd for Datatype {
 }
 
 
+// Conversions that allow Rust programs embedding the interpreter to exchange values with it.
 
-impl Sub for Datatype {
-    type Output = LangResult;
-    fn sub(self, other: Datatype) -> LangResult {
+impl From<i32> for Datatype {
+    fn from(value: i32) -> Datatype {
+        Datatype::Number(value)
+    }
+}
+
+impl From<f64> for Datatype {
+    fn from(value: f64) -> Datatype {
+        Datatype::Float(value)
+    }
+}
+
+impl From<bool> for Datatype {
+    fn from(value: bool) -> Datatype {
+        Datatype::Bool(value)
+    }
+}
+
+impl From<String> for Datatype {
+    fn from(value: String) -> Datatype {
+        Datatype::String(value)
+    }
+}
+
+impl<'a> From<&'a str> for Datatype {
+    fn from(value: &'a str) -> Datatype {
+        Datatype::String(value.to_string())
+    }
+}
+
+/// The type of the array is taken from its first element, an empty Vec produces an array of `Any`.
+impl<T: Into<Datatype>> From<Vec<T>> for Datatype {
+    fn from(values: Vec<T>) -> Datatype {
+        let value: Vec<RcDatatype> = values.into_iter().map(|value| Rc::new(value.into())).collect();
+        let type_: TypeInfo = match value.first() {
+            Some(first) => TypeInfo::from(first.as_ref().clone()),
+            None => TypeInfo::Any,
+        };
+        Datatype::Array { value, type_ }
+    }
+}
+
+impl<'a> TryFrom<&'a Datatype> for i32 {
+    type Error = LangError;
+    fn try_from(datatype: &'a Datatype) -> Result<i32, LangError> {
+        match *datatype {
+            Datatype::Number(value) => Ok(value),
+            ref other => Err(conversion_error(TypeInfo::Number, other)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Datatype> for f64 {
+    type Error = LangError;
+    fn try_from(datatype: &'a Datatype) -> Result<f64, LangError> {
+        match *datatype {
+            Datatype::Float(value) => Ok(value),
+            ref other => Err(conversion_error(TypeInfo::Float, other)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Datatype> for bool {
+    type Error = LangError;
+    fn try_from(datatype: &'a Datatype) -> Result<bool, LangError> {
+        match *datatype {
+            Datatype::Bool(value) => Ok(value),
+            ref other => Err(conversion_error(TypeInfo::Bool, other)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Datatype> for String {
+    type Error = LangError;
+    fn try_from(datatype: &'a Datatype) -> Result<String, LangError> {
+        match *datatype {
+            Datatype::String(ref value) => Ok(value.clone()),
+            ref other => Err(conversion_error(TypeInfo::String, other)),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a Datatype> for Vec<T> where T: TryFrom<&'a Datatype, Error = LangError> {
+    type Error = LangError;
+    fn try_from(datatype: &'a Datatype) -> Result<Vec<T>, LangError> {
+        match *datatype {
+            Datatype::Array { ref value, .. } => value.iter().map(|element| T::try_from(element.as_ref())).collect(),
+            ref other => Err(conversion_error(TypeInfo::Array(Box::new(TypeInfo::Any)), other)),
+        }
+    }
+}
+
+fn conversion_error(expected: TypeInfo, found: &Datatype) -> LangError {
+    LangError::TypeError {
+        expected,
+        found: TypeInfo::from(found.clone()),
+    }
+}
+
+
+/// Two numeric operands, widened to a common type.
+///
+/// This implements the language's numeric tower:
+/// Numbers (i32) and Floats (f64) can be freely mixed in arithmetic and comparisons.
+/// If either operand is a Float, the Number is widened and the result is a Float,
+/// otherwise the operation stays in the integer domain.
+///
+/// Integer operations that would overflow produce an `IntegerOverflow` error, and integer division
+/// or modulo by zero produce a `DivideByZero` error.
+/// Operations involving a Float follow IEEE 754, so dividing by 0.0 yields infinity or NaN instead of an error.
+enum NumericPair {
+    Numbers(i32, i32),
+    Floats(f64, f64),
+}
+
+impl NumericPair {
+    fn new(lhs: &Datatype, rhs: &Datatype) -> Option<NumericPair> {
+        match (lhs, rhs) {
+            (&Datatype::Number(lhs), &Datatype::Number(rhs)) => Some(NumericPair::Numbers(lhs, rhs)),
+            (&Datatype::Number(lhs), &Datatype::Float(rhs)) => Some(NumericPair::Floats(lhs as f64, rhs)),
+            (&Datatype::Float(lhs), &Datatype::Number(rhs)) => Some(NumericPair::Floats(lhs, rhs as f64)),
+            (&Datatype::Float(lhs), &Datatype::Float(rhs)) => Some(NumericPair::Floats(lhs, rhs)),
+            _ => None,
+        }
+    }
+
+    /// Applies the integer operation if both operands are Numbers, otherwise applies the float operation.
+    /// A None from the integer operation indicates that it overflowed.
+    fn apply<I, F>(self, integer_op: I, float_op: F) -> LangResult
+        where I: Fn(i32, i32) -> Option<i32>,
+              F: Fn(f64, f64) -> f64
+    {
         match self {
-            Datatype::Number(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Number(lhs - rhs))),
-                    Datatype::Float(rhs) => return Ok(Rc::new(Datatype::Float(lhs as f64 - rhs))),
-                    _ => Err(LangError::UnsupportedArithimaticOperation),
-                }
-            }
-            Datatype::Float(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Float(lhs - rhs as f64))),
-                    Datatype::Float(rhs) => return Ok(Rc::new(Datatype::Float(lhs - rhs))),
-                    _ => Err(LangError::UnsupportedArithimaticOperation),
+            NumericPair::Numbers(lhs, rhs) => {
+                match integer_op(lhs, rhs) {
+                    Some(value) => Ok(Rc::new(Datatype::Number(value))),
+                    None => Err(LangError::IntegerOverflow),
                 }
             }
+            NumericPair::Floats(lhs, rhs) => Ok(Rc::new(Datatype::Float(float_op(lhs, rhs)))),
+        }
+    }
+}
+
+impl Datatype {
+    /// Equality as used by the `==` and `!=` operators.
+    /// Unlike the PartialEq implementation, Numbers and Floats are compared by value, so `1 == 1.0`.
+    pub fn lang_eq(&self, other: &Datatype) -> bool {
+        match NumericPair::new(self, other) {
+            Some(NumericPair::Numbers(lhs, rhs)) => lhs == rhs,
+            Some(NumericPair::Floats(lhs, rhs)) => lhs == rhs,
+            None => self == other,
+        }
+    }
+
+    /// Raises the Datatype to the power of another.
+    /// Numbers raised to Numbers stay Numbers, so the exponent must not be negative.
+    pub fn pow(self, other: Datatype) -> LangResult {
+        match NumericPair::new(&self, &other) {
+            Some(NumericPair::Numbers(_, exponent)) if exponent < 0 => Err(LangError::NegativeExponent(exponent)),
+            Some(pair) => pair.apply(|base, exponent| base.checked_pow(exponent as u32), f64::powf),
+            None => Err(LangError::UnsupportedArithimaticOperation),
+        }
+    }
+
+    /// Applies an operation that is only defined for two Numbers.
+    fn integer_operation<F>(self, other: Datatype, operation: F) -> LangResult
+        where F: Fn(i32, i32) -> LangResult
+    {
+        match (self, other) {
+            (Datatype::Number(lhs), Datatype::Number(rhs)) => operation(lhs, rhs),
             _ => Err(LangError::UnsupportedArithimaticOperation),
         }
     }
 }
 
+impl Sub for Datatype {
+    type Output = LangResult;
+    fn sub(self, other: Datatype) -> LangResult {
+        match NumericPair::new(&self, &other) {
+            Some(pair) => pair.apply(i32::checked_sub, |lhs, rhs| lhs - rhs),
+            None => Err(LangError::UnsupportedArithimaticOperation),
+        }
+    }
+}
+
 impl Add for Datatype {
     type Output = LangResult;
     fn add(self, other: Datatype) -> LangResult {
+        if let Some(pair) = NumericPair::new(&self, &other) {
+            return pair.apply(i32::checked_add, |lhs, rhs| lhs + rhs);
+        }
         match self {
             Datatype::Number(lhs) => {
                 match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Number(lhs + rhs))),
                     Datatype::String(rhs) => {
                         return Ok(Rc::new(Datatype::String(format!("{}{}", lhs, rhs)))); // add the string to the number.
                     },
-                    Datatype::Float(rhs) => return Ok(Rc::new(Datatype::Float(lhs as f64 + rhs))),
                     _ => return Err(LangError::UnsupportedArithimaticOperation),
                 }
             }
             Datatype::Float(lhs) => {
                 match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Float(lhs + rhs as f64))),
                     Datatype::String(rhs) => {
                         return Ok(Rc::new(Datatype::String(format!("{}{}", lhs, rhs)))); // add the string to the number.
                     }
-                    Datatype::Float(rhs) => return Ok(Rc::new(Datatype::Float(lhs + rhs))),
                     _ => return Err(LangError::UnsupportedArithimaticOperation),
                 }
             }
@@ -248,22 +488,9 @@ impl Add for Datatype {
 impl Mul for Datatype {
     type Output = LangResult;
     fn mul(self, other: Datatype) -> LangResult {
-        match self {
-            Datatype::Number(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Number(lhs * rhs))),
-                    Datatype::Float(rhs) => return Ok(Rc::new(Datatype::Float(lhs as f64 * rhs))),
-                    _ => return Err(LangError::UnsupportedArithimaticOperation),
-                }
-            }
-            Datatype::Float(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Float(lhs * rhs as f64))),
-                    Datatype::Float(rhs) => return Ok(Rc::new(Datatype::Float(lhs * rhs))),
-                    _ => return Err(LangError::UnsupportedArithimaticOperation),
-                }
-            }
-            _ => return Err(LangError::UnsupportedArithimaticOperation),
+        match NumericPair::new(&self, &other) {
+            Some(pair) => pair.apply(i32::checked_mul, |lhs, rhs| lhs * rhs),
+            None => Err(LangError::UnsupportedArithimaticOperation),
         }
     }
 }
@@ -271,44 +498,10 @@ impl Mul for Datatype {
 impl Div for Datatype {
     type Output = LangResult;
     fn div(self, other: Datatype) -> LangResult {
-        match self {
-            Datatype::Number(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => {
-                        if rhs == 0 {
-                            return Err(LangError::DivideByZero);
-                        }
-                        return Ok(Rc::new(Datatype::Number(lhs / rhs)));
-                    }
-                    Datatype::Float(rhs) => {
-                        let lhs = lhs as f64;
-                        if rhs == 0.0 {
-                            return Err(LangError::DivideByZero);
-                        }
-                        return Ok(Rc::new(Datatype::Float(lhs / rhs)));
-                    }
-                    _ => return Err(LangError::UnsupportedArithimaticOperation),
-                }
-            }
-            Datatype::Float(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => {
-                        let rhs = rhs as f64;
-                        if rhs == 0.0 {
-                            return Err(LangError::DivideByZero);
-                        }
-                        return Ok(Rc::new(Datatype::Float(lhs / rhs)));
-                    }
-                    Datatype::Float(rhs) => {
-                        if rhs == 0.0 {
-                            return Err(LangError::DivideByZero);
-                        }
-                        return Ok(Rc::new(Datatype::Float(lhs / rhs)));
-                    }
-                    _ => return Err(LangError::UnsupportedArithimaticOperation),
-                }
-            }
-            _ => return Err(LangError::UnsupportedArithimaticOperation),
+        match NumericPair::new(&self, &other) {
+            Some(NumericPair::Numbers(_, 0)) => Err(LangError::DivideByZero),
+            Some(pair) => pair.apply(i32::checked_div, |lhs, rhs| lhs / rhs),
+            None => Err(LangError::UnsupportedArithimaticOperation),
         }
     }
 }
@@ -317,18 +510,85 @@ impl Div for Datatype {
 impl Rem for Datatype {
     type Output = LangResult;
     fn rem(self, other: Datatype) -> LangResult {
-        match self {
-            Datatype::Number(lhs) => {
-                match other {
-                    Datatype::Number(rhs) => return Ok(Rc::new(Datatype::Number(lhs % rhs))),
-                    _ => return Err(LangError::UnsupportedArithimaticOperation),
-                }
+        match NumericPair::new(&self, &other) {
+            Some(NumericPair::Numbers(_, 0)) => Err(LangError::DivideByZero),
+            Some(pair) => pair.apply(i32::checked_rem, |lhs, rhs| lhs % rhs),
+            None => Err(LangError::UnsupportedArithimaticOperation),
+        }
+    }
+}
+
+
+impl BitAnd for Datatype {
+    type Output = LangResult;
+    fn bitand(self, other: Datatype) -> LangResult {
+        self.integer_operation(other, |lhs, rhs| Ok(Rc::new(Datatype::Number(lhs & rhs))))
+    }
+}
+
+impl BitOr for Datatype {
+    type Output = LangResult;
+    fn bitor(self, other: Datatype) -> LangResult {
+        self.integer_operation(other, |lhs, rhs| Ok(Rc::new(Datatype::Number(lhs | rhs))))
+    }
+}
+
+impl BitXor for Datatype {
+    type Output = LangResult;
+    fn bitxor(self, other: Datatype) -> LangResult {
+        self.integer_operation(other, |lhs, rhs| Ok(Rc::new(Datatype::Number(lhs ^ rhs))))
+    }
+}
+
+/// Shifting by a negative amount, or by more bits than a Number has, is an error.
+impl Shl for Datatype {
+    type Output = LangResult;
+    fn shl(self, other: Datatype) -> LangResult {
+        self.integer_operation(other, |lhs, rhs| {
+            if rhs < 0 { return Err(LangError::ShiftOutOfRange(rhs)) }
+            match lhs.checked_shl(rhs as u32) {
+                Some(value) => Ok(Rc::new(Datatype::Number(value))),
+                None => Err(LangError::ShiftOutOfRange(rhs)),
             }
-            _ => return Err(LangError::UnsupportedArithimaticOperation),
+        })
+    }
+}
+
+/// Right shifts are arithmetic, so the sign of the Number is preserved.
+impl Shr for Datatype {
+    type Output = LangResult;
+    fn shr(self, other: Datatype) -> LangResult {
+        self.integer_operation(other, |lhs, rhs| {
+            if rhs < 0 { return Err(LangError::ShiftOutOfRange(rhs)) }
+            match lhs.checked_shr(rhs as u32) {
+                Some(value) => Ok(Rc::new(Datatype::Number(value))),
+                None => Err(LangError::ShiftOutOfRange(rhs)),
+            }
+        })
+    }
+}
+
+impl Not for Datatype {
+    type Output = LangResult;
+    fn not(self) -> LangResult {
+        match self {
+            Datatype::Number(number) => Ok(Rc::new(Datatype::Number(!number))),
+            _ => Err(LangError::BitwiseNotNonNumber),
         }
     }
 }
 
+#[test]
+fn rust_value_conversion_tests() {
+    assert_eq!(Datatype::Number(3), Datatype::from(3));
+    assert_eq!(Datatype::String("hi".to_string()), Datatype::from("hi"));
+    let array = Datatype::from(vec![1.5, 2.5]);
+    assert_eq!(Ok(vec![1.5, 2.5]), Vec::<f64>::try_from(&array));
+    assert_eq!(
+        Err(LangError::TypeError { expected: TypeInfo::Number, found: TypeInfo::Bool }),
+        i32::try_from(&Datatype::from(true))
+    );
+}
 
 /// I reimplemented PartialEq for Datatype to accommodate the HashMap in Struct (which doesn't implement it)
 /// This test checks that I didn't break the re-implementation.
@@ -381,21 +641,108 @@ fn datatype_equality_tests() {
         }
     );
     assert_eq!(
-        Datatype::Struct { map: HashMap::new() },
-        Datatype::Struct { map: HashMap::new() }
+        Datatype::Struct { identifier: None, field_order: vec![], map: HashMap::new() },
+        Datatype::Struct { identifier: None, field_order: vec![], map: HashMap::new() }
     );
 
     let mut map: HashMap<String, Datatype> = HashMap::new();
     map.insert("field".to_string(), Datatype::Bool(true));
     assert_ne!(
-        Datatype::Struct { map: map.clone() },
-        Datatype::Struct { map: HashMap::new() }
+        Datatype::Struct { identifier: None, field_order: vec!["field".to_string()], map: map.clone() },
+        Datatype::Struct { identifier: None, field_order: vec![], map: HashMap::new() }
     );
 
     let mut other_map: HashMap<String, Datatype> = HashMap::new();
     other_map.insert("field".to_string(), Datatype::Bool(true));
     assert_eq!(
-        Datatype::Struct { map: map },
-        Datatype::Struct { map: other_map }
+        Datatype::Struct { identifier: None, field_order: vec!["field".to_string()], map: map.clone() },
+        Datatype::Struct { identifier: None, field_order: vec!["field".to_string()], map: other_map.clone() }
+    );
+    // Structs of different types aren't equal, even if they have the same fields.
+    assert_ne!(
+        Datatype::Struct { identifier: Some("A".to_string()), field_order: vec!["field".to_string()], map },
+        Datatype::Struct { identifier: Some("B".to_string()), field_order: vec!["field".to_string()], map: other_map }
     );
 }
+
+#[test]
+fn numeric_tower_tests() {
+    // Mixed arithmetic is symmetric, widening to Float.
+    assert_eq!(*(Datatype::Number(1) + Datatype::Float(0.5)).unwrap(), Datatype::Float(1.5));
+    assert_eq!(*(Datatype::Float(0.5) + Datatype::Number(1)).unwrap(), Datatype::Float(1.5));
+    assert_eq!(*(Datatype::Number(3) - Datatype::Float(0.5)).unwrap(), Datatype::Float(2.5));
+    assert_eq!(*(Datatype::Number(7) / Datatype::Number(2)).unwrap(), Datatype::Number(3));
+
+    // Mixed comparisons work in both directions.
+    assert!(Datatype::Number(1) < Datatype::Float(1.5));
+    assert!(Datatype::Float(1.5) > Datatype::Number(1));
+    assert!(Datatype::Number(1).lang_eq(&Datatype::Float(1.0)));
+    assert!(Datatype::Float(f64::NAN).partial_cmp(&Datatype::Number(1)).is_none());
+
+    // Integer division by zero is an error, float division follows IEEE 754.
+    assert_eq!(Datatype::Number(1) / Datatype::Number(0), Err(LangError::DivideByZero));
+    assert_eq!(Datatype::Number(1) % Datatype::Number(0), Err(LangError::DivideByZero));
+    assert_eq!(*(Datatype::Float(1.0) / Datatype::Number(0)).unwrap(), Datatype::Float(f64::INFINITY));
+    match *(Datatype::Float(0.0) / Datatype::Float(0.0)).unwrap() {
+        Datatype::Float(f) => assert!(f.is_nan()),
+        _ => panic!("Expected a Float"),
+    }
+
+    assert_eq!(Datatype::Number(i32::MAX) + Datatype::Number(1), Err(LangError::IntegerOverflow));
+}
+
+#[test]
+fn bitwise_and_power_tests() {
+    assert_eq!(*(Datatype::Number(6) & Datatype::Number(3)).unwrap(), Datatype::Number(2));
+    assert_eq!(*(Datatype::Number(6) | Datatype::Number(3)).unwrap(), Datatype::Number(7));
+    assert_eq!(*(Datatype::Number(6) ^ Datatype::Number(3)).unwrap(), Datatype::Number(5));
+    assert_eq!(*(Datatype::Number(1) << Datatype::Number(4)).unwrap(), Datatype::Number(16));
+    assert_eq!(*(Datatype::Number(-16) >> Datatype::Number(2)).unwrap(), Datatype::Number(-4));
+    assert_eq!(*(!Datatype::Number(0)).unwrap(), Datatype::Number(-1));
+    assert_eq!(Datatype::Number(1) << Datatype::Number(32), Err(LangError::ShiftOutOfRange(32)));
+    assert_eq!(Datatype::Number(1) >> Datatype::Number(-1), Err(LangError::ShiftOutOfRange(-1)));
+    assert_eq!(Datatype::Float(1.0) & Datatype::Number(1), Err(LangError::UnsupportedArithimaticOperation));
+
+    assert_eq!(*Datatype::Number(2).pow(Datatype::Number(10)).unwrap(), Datatype::Number(1024));
+    assert_eq!(*Datatype::Number(2).pow(Datatype::Float(0.5)).unwrap(), Datatype::Float(2f64.sqrt()));
+    assert_eq!(Datatype::Number(2).pow(Datatype::Number(-1)), Err(LangError::NegativeExponent(-1)));
+    assert_eq!(Datatype::Number(2).pow(Datatype::Number(31)), Err(LangError::IntegerOverflow));
+}
+
+#[test]
+fn display_and_representation_tests() {
+    assert_eq!(Datatype::from(vec![1, 2, 3]).to_string(), "[1, 2, 3]");
+    assert_eq!(Datatype::from("text").to_string(), "text");
+    assert_eq!(Datatype::from("text").representation(), "\"text\"");
+    assert_eq!(Datatype::Float(2.0).representation(), "2.0");
+    assert_eq!(Datatype::from(vec!["a", "b"]).to_string(), "[\"a\", \"b\"]");
+
+    let mut map: HashMap<String, Datatype> = HashMap::new();
+    map.insert("x".to_string(), Datatype::Number(1));
+    map.insert("y".to_string(), Datatype::Number(2));
+    // Fields are shown in the order they were declared in, rather than alphabetically.
+    let point = Datatype::Struct {
+        identifier: Some("Point".to_string()),
+        field_order: vec!["y".to_string(), "x".to_string()],
+        map: map.clone(),
+    };
+    assert_eq!(point.to_string(), "Point { y: 2, x: 1 }");
+    let pair = Datatype::Struct { identifier: None, field_order: vec!["x".to_string(), "y".to_string()], map };
+    assert_eq!(pair.to_string(), "{ x: 1, y: 2 }");
+    let empty = Datatype::Struct { identifier: Some("Empty".to_string()), field_order: vec![], map: HashMap::new() };
+    assert_eq!(empty.to_string(), "Empty {}");
+}
+
+#[test]
+fn long_values_are_split_over_several_lines_test() {
+    let mut map: HashMap<String, Datatype> = HashMap::new();
+    map.insert("rows".to_string(), Datatype::from(vec![(0..20).collect::<Vec<i32>>(), vec![1]]));
+    let grid = Datatype::Struct { identifier: Some("Grid".to_string()), field_order: vec!["rows".to_string()], map };
+    let expected: &str = "Grid {
+    rows: [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19],
+        [1]
+    ]
+}";
+    assert_eq!(grid.to_string(), expected);
+}
@@ -1,17 +1,24 @@
 
 use ast::{Datatype, TypeInfo, RcDatatype, TypeError};
+use ast::mutability::MutabilityError;
+use std::fmt;
 
 pub type LangResult = Result<RcDatatype, LangError>;
 
 #[derive(PartialEq, Debug)]
 pub enum LangError {
     DivideByZero,
+    IntegerOverflow,
+    FloatNotRepresentableAsNumber(f64),
+    NegativeExponent(i32),
+    ShiftOutOfRange(i32),
     IdentifierDoesntExist,
     ParserShouldHaveRejected, // should never happen
     UnsupportedArithimaticOperation,
     ConditionOnNonBoolean,
     InvertNonBoolean,
     NegateNotNumber,
+    BitwiseNotNonNumber,
     DecrementNonNumber,
     IncrementNonNumber,
     ExecuteNonFunction,
@@ -21,7 +28,7 @@ pub enum LangError {
     InvalidFunctionPrototypeFormatting,
     TypeError { expected: TypeInfo, found: TypeInfo },
     ExpectedDataTypeInfo,
-    InvalidSyntax,
+    InvalidSyntax(String),
     InvalidSyntaxFailedToParse,
     VariableDoesntExist(String),
     TriedToEvaluateTypeInfo(TypeInfo),
@@ -44,8 +51,49 @@ pub enum LangError {
     InitState,
     CouldNotReadFile { filename: String, reason: String },
     CouldNotParseFile { filename: String, reason: String },
+    CouldNotWriteFile { filename: String, reason: String },
+    CouldNotFormatFile { filename: String, reason: String },
+    CouldNotWriteOutput(String),
+    CouldNotReadInput(String),
+    CouldNotParseNumber(String),
+    InvalidMainSignature(TypeInfo),
+    AssertionFailed(String),
     TriedToGetLengthOfNonArray,
+    MismatchedArrayElementTypes { expected: TypeInfo, found: TypeInfo },
+    NotSortable(TypeInfo),
     RangeValueIsntNumber,
-    MutabilityRulesViolated,
+    /// The user quit the program from the debugger, which `try` doesn't catch.
+    StoppedByDebugger,
+    MutabilityRulesViolated(MutabilityError),
     NewTypeError(TypeError)
 }
+
+/// The message a program sees when it catches the error.
+/// Errors that programs aren't expected to run into are described by their name.
+impl fmt::Display for LangError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LangError::DivideByZero => write!(f, "Division by zero"),
+            LangError::IntegerOverflow => write!(f, "Integer overflow"),
+            LangError::FloatNotRepresentableAsNumber(value) => write!(f, "{} can't be represented as a Number", value),
+            LangError::NegativeExponent(exponent) => write!(f, "Can't raise a Number to the negative exponent {}", exponent),
+            LangError::ShiftOutOfRange(amount) => write!(f, "Can't shift by {} bits", amount),
+            LangError::NegativeIndex(index) => write!(f, "Negative index {}", index),
+            LangError::OutOfBoundsArrayAccess => write!(f, "Index out of bounds"),
+            LangError::InvalidIndexType(ref index) => write!(f, "{:?} can't be used as an index", index),
+            LangError::VariableDoesntExist(ref reason) => write!(f, "{}", reason),
+            LangError::TypeError { ref expected, ref found } => write!(f, "Expected {:?}, found {:?}", expected, found),
+            LangError::CouldNotReadFile { ref filename, ref reason } => write!(f, "Couldn't read {}: {}", filename, reason),
+            LangError::CouldNotParseFile { ref filename, ref reason } => write!(f, "Couldn't parse {}: {}", filename, reason),
+            LangError::CouldNotWriteFile { ref filename, ref reason } => write!(f, "Couldn't write {}: {}", filename, reason),
+            LangError::CouldNotFormatFile { ref filename, ref reason } => write!(f, "Couldn't format {}: {}", filename, reason),
+            LangError::CouldNotWriteOutput(ref reason) => write!(f, "Couldn't write output: {}", reason),
+            LangError::CouldNotReadInput(ref reason) => write!(f, "Couldn't read input: {}", reason),
+            LangError::CouldNotParseNumber(ref text) => write!(f, "\"{}\" isn't a number", text),
+            LangError::AssertionFailed(ref message) => write!(f, "{}", message),
+            LangError::NotSortable(ref type_info) => write!(f, "Arrays of {:?} can't be sorted", type_info),
+            LangError::StoppedByDebugger => write!(f, "Stopped by the debugger"),
+            ref other => write!(f, "{:?}", other),
+        }
+    }
+}
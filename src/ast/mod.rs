@@ -14,4 +14,6 @@ pub use lang_result::*;
 pub use mutability::*;
 pub use operator::*;
 pub use s_expression::*;
-pub use type_checking::*;
\ No newline at end of file
+// The type checker's Mutability and MutabilityResult are only available as `type_checking::`,
+// so the names refer to the mutability checker's.
+pub use type_checking::{TypeError, TypeResult, TypeStore};
\ No newline at end of file
@@ -10,7 +10,7 @@ pub type MutabilityMap = HashMap<String, Mutability>;
 pub type MutabilityResult = Result<(), MutabilityError>;
 
 /// Mutability values variables can have.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Mutability {
     Mutable,
     Immutable
@@ -18,7 +18,7 @@ pub enum Mutability {
 
 
 /// Types of errors that can be generated by the mutability checker.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MutabilityError {
     CanNotAssignToConstVariable,
     CanNotRedeclareConst,
@@ -66,9 +66,9 @@ impl Ast {
                         }
                     }
                     SExpression::ConstDeclaration {ref identifier, ref ast} => { // const a := 5
-                        let resolved_id: String = match **identifier {
-                            Ast::ValueIdentifier(ref id) => id.clone(),
-                            _ => return Err(MutabilityError::IsNotAVariable) // Error, AST malformed, couldn't resolve the id
+                        let resolved_id: String = match identifier.declaration_identifier() {
+                            Some((id, _)) => id.clone(),
+                            None => return Err(MutabilityError::IsNotAVariable) // Error, AST malformed, couldn't resolve the id
                         };
                         if let Some(_) = map.get(&resolved_id) {
                             Err(MutabilityError::CanNotRedeclareConst) // tried to assign a value to immutable value
@@ -79,9 +79,9 @@ impl Ast {
                     },
                     SExpression::VariableDeclaration { ref identifier, ref ast } => {
                         // let a := 5
-                        let resolved_id: String = match **identifier {
-                            Ast::ValueIdentifier(ref id) => id.clone(),
-                            _ => return Err(MutabilityError::IsNotAVariable) // Error, AST malformed, couldn't resolve the id
+                        let resolved_id: String = match identifier.declaration_identifier() {
+                            Some((id, _)) => id.clone(),
+                            None => return Err(MutabilityError::IsNotAVariable) // Error, AST malformed, couldn't resolve the id
                         };
                         {
                             if let Some(mutability) = map.get(&resolved_id) {
@@ -118,13 +118,77 @@ impl Ast {
                             Ok(())
                         }
                     }
+                    SExpression::Loop { ref body, .. } => {
+                        body.check_mutability_semantics(map) // Loop bodies may assign to variables in the enclosing scope.
+                    }
+                    SExpression::TryCatch { ref body, ref handler, .. } => {
+                        body.check_mutability_semantics(map)?;
+                        handler.check_mutability_semantics(map)
+                    }
 
                     _ => {
                         Ok(()) // if the expression doesn't add anything to the variable store, we don't care about it.
                     }
                 }
             }
+            Ast::Conditional { ref true_expr, ref false_expr, .. } => {
+                true_expr.check_mutability_semantics(map)?;
+                if let Some(ref false_expr) = *false_expr {
+                    false_expr.check_mutability_semantics(map)?;
+                }
+                Ok(())
+            }
+            Ast::Located { ref ast, .. } => {
+                ast.check_mutability_semantics(map)
+            }
             _ => Ok(())
         }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::program;
+    use nom::IResult;
+
+    #[test]
+    fn compound_assignment_to_const_is_rejected() {
+        let mut map: MutabilityMap = MutabilityMap::new();
+        let input_string = r##"
+        const a := 5
+        a += 1
+        "##;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        match ast.check_mutability_semantics(&mut map) {
+            Err(MutabilityError::CanNotAssignToConstVariable) => {}
+            other => panic!("Expected CanNotAssignToConstVariable, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn increment_of_const_in_loop_is_rejected() {
+        let mut map: MutabilityMap = MutabilityMap::new();
+        let input_string = r##"
+        const a := 5
+        while a < 10 {
+            a++
+        }
+        "##;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        match ast.check_mutability_semantics(&mut map) {
+            Err(MutabilityError::CanNotAssignToConstVariable) => {}
+            other => panic!("Expected CanNotAssignToConstVariable, found {:?}", other),
+        }
+    }
 }
\ No newline at end of file
@@ -10,6 +10,9 @@ pub enum Operator {
     Decrement,
     Negate, // -
     Invert, // !
+    BitwiseNot, // ~
+
+    Power,
 
     Times,
     Divide,
@@ -18,6 +21,13 @@ pub enum Operator {
     Plus,
     Minus,
 
+    ShiftLeft,
+    ShiftRight,
+
+    BitwiseAnd,
+    BitwiseXor,
+    BitwiseOr,
+
     GreaterThan,
     LessThan,
     GreaterThanOrEqual,
@@ -29,24 +39,97 @@ pub enum Operator {
     LogicalAnd,
     LogicalOr,
 
-    Assignment
+    Assignment,
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign
 }
 
 /// The u32 values here indicate the precedence for the operator.
 /// Accessing an array, struct or executing a function have the highest priority, and will be evaluated before any other operations are performed.
 /// Assignment has the lowest precedence, this makes sense because you only want to assign a fully evaluated value to a variable, so the assignment should always come last.
+/// Like in Rust, the bitwise operators bind tighter than comparisons, so `a & 1 == 1` compares the result of the `&`.
 impl Into<u32> for Operator {
     fn into(self) -> u32 {
         use self::Operator::*;
         match self {
             ArrayAccess | StructAccess | ExecuteFunction => 0,
-            Increment | Decrement | Negate | Invert => 1,
-            Times | Divide | Modulo => 2,
-            Plus | Minus => 3,
-            GreaterThan | LessThan | GreaterThanOrEqual | LessThanOrEqual => 4,
-            Equals | NotEquals => 5,
-            LogicalAnd | LogicalOr => 6,
-            Assignment => 7
+            Increment | Decrement | Negate | Invert | BitwiseNot => 1,
+            Power => 2,
+            Times | Divide | Modulo => 3,
+            Plus | Minus => 4,
+            ShiftLeft | ShiftRight => 5,
+            BitwiseAnd => 6,
+            BitwiseXor => 7,
+            BitwiseOr => 8,
+            GreaterThan | LessThan | GreaterThanOrEqual | LessThanOrEqual => 9,
+            Equals | NotEquals => 10,
+            LogicalAnd | LogicalOr => 11,
+            Assignment | AddAssign | SubtractAssign | MultiplyAssign | DivideAssign | ModuloAssign => 12
+        }
+    }
+}
+
+impl Operator {
+    /// Right associative operators group towards the right when chained,
+    /// so `2 ** 3 ** 2` is `2 ** (3 ** 2)`, whereas `8 - 4 - 2` is `(8 - 4) - 2`.
+    pub fn is_right_associative(&self) -> bool {
+        match *self {
+            Operator::Power | Operator::Assignment |
+            Operator::AddAssign | Operator::SubtractAssign | Operator::MultiplyAssign |
+            Operator::DivideAssign | Operator::ModuloAssign => true,
+            _ => false
+        }
+    }
+
+    /// How the operator is written in source code.
+    pub fn symbol(&self) -> &'static str {
+        use self::Operator::*;
+        match *self {
+            ArrayAccess => "[]",
+            StructAccess => ".",
+            ExecuteFunction => "()",
+            Increment => "++",
+            Decrement => "--",
+            Negate => "-",
+            Invert => "!",
+            BitwiseNot => "~",
+            Power => "**",
+            Times => "*",
+            Divide => "/",
+            Modulo => "%",
+            Plus => "+",
+            Minus => "-",
+            ShiftLeft => "<<",
+            ShiftRight => ">>",
+            BitwiseAnd => "&",
+            BitwiseXor => "^",
+            BitwiseOr => "|",
+            GreaterThan => ">",
+            LessThan => "<",
+            GreaterThanOrEqual => ">=",
+            LessThanOrEqual => "<=",
+            Equals => "==",
+            NotEquals => "!=",
+            LogicalAnd => "&&",
+            LogicalOr => "||",
+            Assignment => ":=",
+            AddAssign => "+=",
+            SubtractAssign => "-=",
+            MultiplyAssign => "*=",
+            DivideAssign => "/=",
+            ModuloAssign => "%=",
+        }
+    }
+
+    /// Postfix operators apply to the operand directly preceding them, regardless of the other operators in the expression.
+    pub fn is_postfix(&self) -> bool {
+        match *self {
+            Operator::ArrayAccess | Operator::StructAccess | Operator::ExecuteFunction |
+            Operator::Increment | Operator::Decrement => true,
+            _ => false
         }
     }
 }
@@ -19,11 +19,18 @@ pub enum SExpression {
     LessThanOrEqual(Box<Ast>, Box<Ast>),
     LogicalAnd(Box<Ast>, Box<Ast>),
     LogicalOr(Box<Ast>, Box<Ast>),
+    Power(Box<Ast>, Box<Ast>),
+    BitwiseAnd(Box<Ast>, Box<Ast>),
+    BitwiseOr(Box<Ast>, Box<Ast>),
+    BitwiseXor(Box<Ast>, Box<Ast>),
+    ShiftLeft(Box<Ast>, Box<Ast>),
+    ShiftRight(Box<Ast>, Box<Ast>),
     //Unary Operators
     Print(Box<Ast>),
     Include(Box<Ast>),
     Invert(Box<Ast>),
     Negate(Box<Ast>),
+    BitwiseNot(Box<Ast>),
     Increment(Box<Ast>),
     Decrement(Box<Ast>),
     // Language Features
@@ -47,11 +54,19 @@ pub enum SExpression {
         conditional: Box<Ast>,
         body: Box<Ast>,
     },
+    /// Evaluates the body, and if it produces an error, evaluates the handler with the error's message
+    /// stored under the error identifier.
+    TryCatch {
+        body: Box<Ast>,
+        error_identifier: Box<Ast>,
+        handler: Box<Ast>,
+    },
     AccessArray {
         identifier: Box<Ast>,
         index: Box<Ast>,
     },
     GetArrayLength ( Box<Ast> ),
+    CreateArray ( Box<Ast> ), // Holds an ExpressionList of the array's elements.
     Range{
         start: Box<Ast>,
         end: Box<Ast>
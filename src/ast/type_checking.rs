@@ -10,6 +10,14 @@ pub enum TypeError {
     LhsNotAnIdentifier,
     IdentifierDoesntExist(String),
     MalformedAST,
+    ParameterLengthMismatch { expected: usize, found: usize },
+    MismatchedArrayElementTypes { expected: TypeInfo, found: TypeInfo },
+    NotSortable(TypeInfo),
+    IndexIntoNonArray(TypeInfo),
+    AccessFieldOfNonStruct(TypeInfo),
+    StructFieldDoesntExist(String),
+    NotAStructType(String),
+    NotAFunction(TypeInfo),
     // Mutability
     CanNotAssignToConstVariable,
     CanNotRedeclareConst,
@@ -33,7 +41,7 @@ impl From<Datatype> for Mutability {
 }
 
 impl Mutability {
-    fn get_type(self) -> TypeInfo {
+    pub fn get_type(self) -> TypeInfo {
         match self {
             Mutability::Mutable(ti) => ti,
             Mutability::Immutable(ti) => ti
@@ -89,6 +97,42 @@ impl Ast {
                                 % rhs.check_types(type_store)?.get_type()
                         )
                     }
+                    SExpression::Power(ref lhs, ref rhs) => {
+                        Mutability::from_type_result(
+                            lhs.check_types(type_store)?.get_type()
+                                .pow(rhs.check_types(type_store)?.get_type())
+                        )
+                    }
+                    SExpression::BitwiseAnd(ref lhs, ref rhs) => {
+                        Mutability::from_type_result(
+                            lhs.check_types(type_store)?.get_type()
+                                & rhs.check_types(type_store)?.get_type()
+                        )
+                    }
+                    SExpression::BitwiseOr(ref lhs, ref rhs) => {
+                        Mutability::from_type_result(
+                            lhs.check_types(type_store)?.get_type()
+                                | rhs.check_types(type_store)?.get_type()
+                        )
+                    }
+                    SExpression::BitwiseXor(ref lhs, ref rhs) => {
+                        Mutability::from_type_result(
+                            lhs.check_types(type_store)?.get_type()
+                                ^ rhs.check_types(type_store)?.get_type()
+                        )
+                    }
+                    SExpression::ShiftLeft(ref lhs, ref rhs) => {
+                        Mutability::from_type_result(
+                            lhs.check_types(type_store)?.get_type()
+                                << rhs.check_types(type_store)?.get_type()
+                        )
+                    }
+                    SExpression::ShiftRight(ref lhs, ref rhs) => {
+                        Mutability::from_type_result(
+                            lhs.check_types(type_store)?.get_type()
+                                >> rhs.check_types(type_store)?.get_type()
+                        )
+                    }
                     SExpression::Equals(_, _) => {
                         Ok(Mutability::Mutable(TypeInfo::Bool))
                     }
@@ -118,8 +162,8 @@ impl Ast {
                         ref identifier,
                         ref ast,
                     } => {
-                        let rhs_mutability: Mutability = ast.check_types(type_store)?;
-                        if let Ast::ValueIdentifier(ref ident) = **identifier {
+                        if let Some((ident, annotation)) = identifier.declaration_identifier() {
+                            let rhs_mutability: Mutability = Mutability::Mutable(annotated_type(annotation, ast.check_types(type_store)?.get_type())?);
                             // hold errors that may be generated when checking types
                             let mut error: Option<TypeError> = None;
 
@@ -156,8 +200,8 @@ impl Ast {
                         ref identifier,
                         ref ast,
                     } => {
-                        let rhs_mutability: Mutability = ast.check_types(type_store)?;
-                        if let Ast::ValueIdentifier(ref ident) = **identifier {
+                        if let Some((ident, annotation)) = identifier.declaration_identifier() {
+                            let rhs_mutability: Mutability = Mutability::Mutable(annotated_type(annotation, ast.check_types(type_store)?.get_type())?);
                             // hold errors that may be generated when checking types
                             let mut error: Option<TypeError> = None;
 
@@ -215,8 +259,7 @@ impl Ast {
                         ref identifier,
                         ref struct_type_info,
                     } => {
-                        let rhs_mutability: Mutability = struct_type_info.check_types(type_store)?;
-                        // TODO, should I check if the righthand side is a struct type info?
+                        let rhs_mutability: Mutability = Mutability::Immutable(struct_declaration_type(struct_type_info)?);
                         if let Ast::ValueIdentifier(ref ident) = **identifier {
                             // hold errors that may be generated when checking types
                             let mut error: Option<TypeError> = None;
@@ -268,12 +311,16 @@ impl Ast {
                             // hold errors that may be generated when checking types
                             let mut error: Option<TypeError> = None;
 
+                            // The type of the variable may be refined by the assignment, as in assigning `[1]` to an `[Any]` array.
+                            let mut assigned_type: TypeInfo = rhs_mutability.clone().get_type();
+
                             match type_store.get(ident) {
                                 Some(lhs_mutability) => {
                                     match *lhs_mutability {
-                                        Mutability::Mutable(_) => {
-                                            if lhs_mutability.clone().get_type() != rhs_mutability.clone().get_type() {
-                                                error = Some(TypeError::TypeMismatch(lhs_mutability.clone().get_type(), rhs_mutability.clone().get_type()))
+                                        Mutability::Mutable(ref lhs_type) => {
+                                            match lhs_type.unify(&assigned_type) {
+                                                Some(unified_type) => assigned_type = unified_type,
+                                                None => error = Some(TypeError::TypeMismatch(lhs_type.clone(), assigned_type.clone()))
                                             }
                                         }
                                         Mutability::Immutable(_) => {
@@ -289,8 +336,8 @@ impl Ast {
                             if let Some(e) = error {
                                 return Err(e)
                             } else {
-                                type_store.insert(ident.clone(), Mutability::Mutable(rhs_mutability.clone().get_type()));
-                                Ok(rhs_mutability)
+                                type_store.insert(ident.clone(), Mutability::Mutable(assigned_type.clone()));
+                                Ok(Mutability::Mutable(assigned_type))
                             }
                         } else {
                             return Err(TypeError::LhsNotAnIdentifier)
@@ -303,25 +350,61 @@ impl Ast {
                         let _ = conditional.check_types(type_store)?; // Possibly return an error on checking the conditional's type.
                         body.check_types(type_store)
                     }
+                    SExpression::TryCatch {
+                        ref body,
+                        ref error_identifier,
+                        ref handler,
+                    } => {
+                        let body_type: TypeInfo = body.check_types(type_store)?.get_type();
+                        if let Ast::ValueIdentifier(ref ident) = **error_identifier {
+                            let replaced: Option<Mutability> = type_store.insert(ident.clone(), Mutability::Mutable(TypeInfo::String));
+                            let handler_type: Result<Mutability, TypeError> = handler.check_types(type_store);
+                            match replaced {
+                                Some(replaced) => type_store.insert(ident.clone(), replaced),
+                                None => type_store.remove(ident),
+                            };
+                            // If the body and handler disagree, the type can only be known at runtime.
+                            Ok(Mutability::Mutable(body_type.unify(&handler_type?.get_type()).unwrap_or(TypeInfo::Any)))
+                        } else {
+                            Err(TypeError::LhsNotAnIdentifier)
+                        }
+                    }
                     SExpression::AccessArray {
                         ref identifier,
                         ref index
                     } => {
                         if let Ast::ValueIdentifier(ref ident) = **identifier {
-                            match type_store.get(ident) {
-                                Some(lhs_type) => {
-                                    if lhs_type.clone().get_type() == TypeInfo::Array(Box::new(TypeInfo::Any)) {
-                                        return Ok(lhs_type.clone()) // The lhs will give a specific Array type, ie. Array<Number> vs the "rhs" in this case which is just Array<Any>
-                                    } else {
-                                        return Err(TypeError::TypeMismatch(lhs_type.clone().get_type(), TypeInfo::Array(Box::new(TypeInfo::Any)) ))
-                                    }
-                                }
-                                None => {
-                                    return Err(TypeError::IdentifierDoesntExist(ident.clone()))
+                            if type_store.get(ident).is_none() {
+                                return Err(TypeError::IdentifierDoesntExist(ident.clone()))
+                            }
+                        }
+                        let index_type: TypeInfo = index.check_types(type_store)?.get_type();
+                        if index_type.unify(&TypeInfo::Number).is_none() {
+                            return Err(TypeError::TypeMismatch(index_type, TypeInfo::Number))
+                        }
+                        match identifier.check_types(type_store)?.get_type() {
+                            TypeInfo::Array(element_type) => Ok(Mutability::Mutable(*element_type)),
+                            TypeInfo::String => Ok(Mutability::Mutable(TypeInfo::String)),
+                            TypeInfo::Any => Ok(Mutability::Mutable(TypeInfo::Any)),
+                            other => Err(TypeError::IndexIntoNonArray(other))
+                        }
+                    }
+                    SExpression::CreateArray(ref elements) => {
+                        if let Ast::ExpressionList(ref expressions) = **elements {
+                            let mut array_type: TypeInfo = TypeInfo::Any;
+                            for expression in expressions {
+                                let element_type: TypeInfo = expression.check_types(type_store)?.get_type();
+                                array_type = match array_type.unify(&element_type) {
+                                    Some(unified_type) => unified_type,
+                                    None => return Err(TypeError::MismatchedArrayElementTypes {
+                                        expected: array_type,
+                                        found: element_type
+                                    })
                                 }
                             }
+                            Ok(Mutability::Mutable(TypeInfo::Array(Box::new(array_type))))
                         } else {
-                            return Err(TypeError::LhsNotAnIdentifier)
+                            Err(TypeError::MalformedAST)
                         }
                     }
                     SExpression::GetArrayLength(_) => {
@@ -356,7 +439,7 @@ impl Ast {
                                         .iter()
                                         .zip( parameters.iter() )
                                         .map( |(input_type, expected_type)| {
-                                            if input_type == expected_type {
+                                            if expected_type.unify(input_type).is_some() {
                                                 Ok(input_type.clone())
                                             } else {
                                                 return Err(TypeError::TypeMismatch(input_type.clone(), expected_type.clone()))
@@ -370,6 +453,8 @@ impl Ast {
                                         }
 
                                         return Ok(Mutability::Mutable(*return_type.clone()))
+                                } else if let TypeInfo::BuiltinFunction(builtin) = (*possible_fn_datatype).clone().get_type() {
+                                    return Mutability::from_type_result(builtin.check_types(&parameter_types))
                                 } {
                                     Err(TypeError::MalformedAST)
                                 }
@@ -385,21 +470,53 @@ impl Ast {
                         ref identifier,
                         ref struct_datatype
                     } => {
-                        if let Ast::ValueIdentifier(ref id) = ** identifier {
-                            if let Some(existing_struct_type) = type_store.get(id){
-                                if let TypeInfo::Struct{ ref map } = existing_struct_type.clone().get_type() {
-
+                        let id: &String = match **identifier {
+                            Ast::ValueIdentifier(ref id) => id,
+                            _ => return Err(TypeError::LhsNotAnIdentifier)
+                        };
+                        let field_types: HashMap<String, TypeInfo> = struct_field_types(id, type_store)?;
+                        if let Ast::ExpressionList(ref assignments) = **struct_datatype {
+                            for assignment in assignments {
+                                if let Ast::SExpr(SExpression::FieldAssignment { identifier: ref field, ref ast }) = *assignment {
+                                    let field: &String = match **field {
+                                        Ast::ValueIdentifier(ref field) => field,
+                                        _ => return Err(TypeError::LhsNotAnIdentifier)
+                                    };
+                                    let expected_type: &TypeInfo = match field_types.get(field) {
+                                        Some(expected_type) => expected_type,
+                                        None => return Err(TypeError::StructFieldDoesntExist(field.clone()))
+                                    };
+                                    let found_type: TypeInfo = ast.check_types(type_store)?.get_type();
+                                    if expected_type.unify(&found_type).is_none() {
+                                        return Err(TypeError::TypeMismatch(expected_type.clone(), found_type))
+                                    }
+                                } else {
+                                    return Err(TypeError::MalformedAST)
                                 }
                             }
+                            Ok(Mutability::Mutable(TypeInfo::StructType { identifier: id.clone() }))
+                        } else {
+                            Err(TypeError::MalformedAST)
                         }
-
-                        unimplemented!()
                     }
                     SExpression::AccessStructField {
                         ref identifier,
                         ref field_identifier
                     } => {
-                        unimplemented!()
+                        let field: &String = match **field_identifier {
+                            Ast::ValueIdentifier(ref field) => field,
+                            _ => return Err(TypeError::MalformedAST)
+                        };
+                        let field_types: HashMap<String, TypeInfo> = match identifier.check_types(type_store)?.get_type() {
+                            TypeInfo::StructType { identifier: ref struct_type } => struct_field_types(struct_type, type_store)?,
+                            TypeInfo::Struct { map } => map,
+                            TypeInfo::Any => return Ok(Mutability::Mutable(TypeInfo::Any)),
+                            other => return Err(TypeError::AccessFieldOfNonStruct(other))
+                        };
+                        match field_types.get(field) {
+                            Some(field_type) => Ok(Mutability::Mutable(field_type.clone())),
+                            None => Err(TypeError::StructFieldDoesntExist(field.clone()))
+                        }
                     }
 
                     SExpression::Print(_) => {
@@ -414,6 +531,9 @@ impl Ast {
                     SExpression::Negate(ref parameter) => {
                         parameter.check_types(type_store)
                     }
+                    SExpression::BitwiseNot(ref parameter) => {
+                        Mutability::from_type_result(!parameter.check_types(type_store)?.get_type())
+                    }
                     SExpression::Increment(ref parameter) => {
                         parameter.check_types(type_store)
                     }
@@ -441,12 +561,78 @@ impl Ast {
                 }
                 Ok(checked_type)
             }
-            _ => unimplemented!("AST")
+            Ast::Conditional {
+                ref condition,
+                ref true_expr,
+                ref false_expr
+            } => {
+                let _ = condition.check_types(type_store)?;
+                let true_type: TypeInfo = true_expr.check_types(type_store)?.get_type();
+                match *false_expr {
+                    Some(ref false_expr) => {
+                        let false_type: TypeInfo = false_expr.check_types(type_store)?.get_type();
+                        // If the branches disagree, the type can only be known at runtime.
+                        Ok(Mutability::Mutable(true_type.unify(&false_type).unwrap_or(TypeInfo::Any)))
+                    }
+                    None => Ok(Mutability::Mutable(TypeInfo::Any))
+                }
+            }
+            Ast::Type(ref type_info) => {
+                Ok(Mutability::Mutable(type_info.clone()))
+            }
+            Ast::Located { ref ast, .. } => {
+                ast.check_types(type_store)
+            }
         }
 
     }
 }
 
+/// Checks the type of the right hand side of a declaration against its annotation, if it has one.
+/// The annotation may specify the element type of an array whose element type isn't known, like `[]`.
+fn annotated_type(annotation: Option<&TypeInfo>, rhs_type: TypeInfo) -> TypeResult {
+    match annotation {
+        Some(annotated_type) => {
+            match annotated_type.unify(&rhs_type) {
+                Some(unified_type) => Ok(unified_type),
+                None => Err(TypeError::TypeMismatch(annotated_type.clone(), rhs_type))
+            }
+        }
+        None => Ok(rhs_type)
+    }
+}
+
+/// Collects the field types out of the TypeAssignments that make up a struct declaration.
+fn struct_declaration_type(struct_type_info: &Ast) -> TypeResult {
+    if let Ast::ExpressionList(ref assignments) = *struct_type_info {
+        let mut field_types: HashMap<String, TypeInfo> = HashMap::new();
+        for assignment in assignments {
+            if let Ast::SExpr(SExpression::TypeAssignment { ref identifier, ref type_info }) = *assignment {
+                if let Ast::ValueIdentifier(ref field) = **identifier {
+                    if let Ast::Type(ref field_type) = **type_info {
+                        field_types.insert(field.clone(), field_type.clone());
+                        continue;
+                    }
+                }
+                return Err(TypeError::MalformedAST)
+            } else {
+                return Err(TypeError::MalformedAST)
+            }
+        }
+        Ok(TypeInfo::Struct { map: field_types })
+    } else {
+        Err(TypeError::MalformedAST)
+    }
+}
+
+/// Gets the types of the fields for the struct type declared with the given name.
+fn struct_field_types(struct_type: &str, type_store: &TypeStore) -> Result<HashMap<String, TypeInfo>, TypeError> {
+    match type_store.get(struct_type).map(|mutability| mutability.clone().get_type()) {
+        Some(TypeInfo::Struct { map }) => Ok(map),
+        _ => Err(TypeError::NotAStructType(struct_type.to_string()))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -667,6 +853,27 @@ mod test {
         assert_eq!(TypeInfo::Number, ast.check_types(&mut map).unwrap().get_type());
     }
 
+    #[test]
+    fn try_catch_error_is_a_string() {
+        let mut map: TypeStore = TypeStore::new();
+        let input_string = r##"
+            try {
+                1 / 0
+            } catch e {
+                e + 1
+            }
+        "##;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        // The branches produce different types, so the type is only known at runtime.
+        assert_eq!(TypeInfo::Any, ast.check_types(&mut map).unwrap().get_type());
+        assert!(!map.contains_key("e"));
+    }
+
     #[test]
     fn mutability_const_redeclaration_throws_error() {
         let mut map: TypeStore = TypeStore::new();
@@ -1,15 +1,23 @@
 use ast::datatype::Datatype;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
 use ast::type_checking::{TypeResult, TypeError};
 use ast::Ast;
 use ast::s_expression::SExpression;
+use std_functions::Builtin;
 
 use std::ops::Sub;
 use std::ops::Add;
 use std::ops::Mul;
 use std::ops::Div;
 use std::ops::Rem;
+use std::ops::BitAnd;
+use std::ops::BitOr;
+use std::ops::BitXor;
+use std::ops::Shl;
+use std::ops::Shr;
+use std::ops::Not;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum TypeInfo {
@@ -20,6 +28,7 @@ pub enum TypeInfo {
     Bool,
     None,
     Function{parameters: Vec<TypeInfo>, return_type: Box<TypeInfo>}, // TODO, this needs to encode the parameters and the return type, instead of just the return type.
+    BuiltinFunction(Builtin), // The builtin determines its own return type from the types of its arguments.
     Struct { map: HashMap<String, TypeInfo> },
     StructType{identifier: String},
     Any
@@ -28,6 +37,33 @@ pub enum TypeInfo {
 
 
 
+/// Shows the type the way it is written in type signatures, like `[Number]`.
+/// Types that can't be written in a signature are described in a similar style, like `fn(Number) -> String`.
+impl fmt::Display for TypeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TypeInfo::Number => write!(f, "Number"),
+            TypeInfo::Float => write!(f, "Float"),
+            TypeInfo::String => write!(f, "String"),
+            TypeInfo::Bool => write!(f, "Bool"),
+            TypeInfo::Array(ref element_type) => write!(f, "[{}]", element_type),
+            TypeInfo::None => write!(f, "None"),
+            TypeInfo::Any => write!(f, "Any"),
+            TypeInfo::Function { ref parameters, ref return_type } => {
+                let parameters: Vec<String> = parameters.iter().map(|parameter| parameter.to_string()).collect();
+                write!(f, "fn({}) -> {}", parameters.join(", "), return_type)
+            }
+            TypeInfo::BuiltinFunction(ref builtin) => write!(f, "fn {}", builtin.name()),
+            TypeInfo::Struct { ref map } => {
+                let mut fields: Vec<String> = map.iter().map(|(field, type_info)| format!("{}: {}", field, type_info)).collect();
+                fields.sort();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+            TypeInfo::StructType { ref identifier } => write!(f, "{}", identifier),
+        }
+    }
+}
+
 impl From<Datatype> for TypeInfo {
     fn from(datatype: Datatype) -> TypeInfo {
         match datatype {
@@ -66,7 +102,9 @@ impl From<Datatype> for TypeInfo {
                     }
                 } else { panic!("Malformed AST")}
             },
-            Datatype::Struct { map } => {
+            Datatype::BuiltinFunction(builtin) => TypeInfo::BuiltinFunction(builtin),
+            Datatype::NativeFunction(native) => native.type_info(),
+            Datatype::Struct { map, .. } => {
                 let mut type_map = HashMap::new();
                 for tuple in map.into_iter() {
                     let (key, value) = tuple;
@@ -74,7 +112,7 @@ impl From<Datatype> for TypeInfo {
                 }
                 TypeInfo::Struct { map: type_map }
             }
-            Datatype::StructType{ identifier, type_information} => TypeInfo::StructType{ identifier: identifier },
+            Datatype::StructType{ identifier, .. } => TypeInfo::StructType{ identifier },
         }
     }
 }
@@ -129,6 +167,14 @@ impl PartialOrd for TypeInfo {
             TypeInfo::Function{ref parameters, ref return_type} => {
                 None // TODO, is there a better way to do this? I don't think that functions should be compared in their entirety as that could require an Ast traversal, which would require loading the AST into the fn typeinfo.
             }
+            TypeInfo::BuiltinFunction(ref builtin) => {
+                if let TypeInfo::BuiltinFunction(ref rhs_builtin) = *rhs {
+                    if builtin == rhs_builtin {
+                        return Some(Ordering::Equal)
+                    }
+                }
+                None
+            }
             TypeInfo::Struct { map: ref lhs_map } => {
                 if let TypeInfo::Struct { map: ref rhs_map } = *rhs {
                     if lhs_map == rhs_map {
@@ -154,29 +200,82 @@ impl PartialOrd for TypeInfo {
     }
 }
 
+impl TypeInfo {
+    /// Determines the type produced by an arithmetic operation on two numeric types,
+    /// mirroring the numeric tower used when evaluating Datatypes.
+    /// Numbers stay Numbers, and mixing in a Float widens the result to a Float.
+    /// Any takes on the type of the other operand.
+    fn numeric_result(&self, other: &TypeInfo) -> Option<TypeInfo> {
+        match (self, other) {
+            (&TypeInfo::Number, &TypeInfo::Number) => Some(TypeInfo::Number),
+            (&TypeInfo::Number, &TypeInfo::Float) |
+            (&TypeInfo::Float, &TypeInfo::Number) |
+            (&TypeInfo::Float, &TypeInfo::Float) => Some(TypeInfo::Float),
+            (&TypeInfo::Number, &TypeInfo::Any) |
+            (&TypeInfo::Any, &TypeInfo::Number) => Some(TypeInfo::Number),
+            (&TypeInfo::Float, &TypeInfo::Any) |
+            (&TypeInfo::Any, &TypeInfo::Float) => Some(TypeInfo::Float),
+            (&TypeInfo::Any, &TypeInfo::Any) => Some(TypeInfo::Any),
+            _ => None
+        }
+    }
+
+    /// Produces the type of an arithmetic operation, or an UnsupportedOperation error if it isn't numeric.
+    fn numeric_operation(self, other: TypeInfo) -> TypeResult {
+        match self.numeric_result(&other) {
+            Some(result) => Ok(result),
+            None => Err(TypeError::UnsupportedOperation(self, other)),
+        }
+    }
+
+    /// Produces the type of an operation that is only defined for Numbers, such as the bitwise operators.
+    fn integer_operation(self, other: TypeInfo) -> TypeResult {
+        match (&self, &other) {
+            (&TypeInfo::Number, &TypeInfo::Number) |
+            (&TypeInfo::Number, &TypeInfo::Any) |
+            (&TypeInfo::Any, &TypeInfo::Number) |
+            (&TypeInfo::Any, &TypeInfo::Any) => Ok(TypeInfo::Number),
+            _ => Err(TypeError::UnsupportedOperation(self, other)),
+        }
+    }
+
+    /// Finds the type that values of both types can be treated as, if there is one.
+    /// Any will unify with every type, taking on the more specific type.
+    pub fn unify(&self, other: &TypeInfo) -> Option<TypeInfo> {
+        match (self, other) {
+            (&TypeInfo::Any, _) => Some(other.clone()),
+            (_, &TypeInfo::Any) => Some(self.clone()),
+            (&TypeInfo::Array(ref lhs), &TypeInfo::Array(ref rhs)) => {
+                lhs.unify(rhs).map(|contained_type| TypeInfo::Array(Box::new(contained_type)))
+            }
+            _ => {
+                if self == other {
+                    Some(self.clone())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Produces the type of raising one type to the power of another.
+    pub fn pow(self, other: TypeInfo) -> TypeResult {
+        self.numeric_operation(other)
+    }
+}
+
 impl Add for TypeInfo {
     type Output = TypeResult;
     fn add(self, other: TypeInfo) -> TypeResult {
+        if let Some(result) = self.numeric_result(&other) {
+            return Ok(result)
+        }
         match self {
-            TypeInfo::Number => {
+            TypeInfo::Number | TypeInfo::Float => {
                 match other {
-                    TypeInfo::Number => return Ok(TypeInfo::Number),
                     TypeInfo::String => {
                         return Ok(TypeInfo::String)// add the string to the number.
                     },
-                    TypeInfo::Float => return Ok(TypeInfo::Float),
-                    TypeInfo::Any => return Ok(TypeInfo::Number),
-                    _ => return Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            TypeInfo::Float => {
-                match other {
-                    TypeInfo::Number=> return Ok(TypeInfo::Float),
-                    TypeInfo::String => {
-                        return Ok(TypeInfo::String); // add the string to the number.
-                    }
-                    TypeInfo::Float => return Ok(TypeInfo::Float),
-                    TypeInfo::Any => return Ok(TypeInfo::Float),
                     _ => return Err(TypeError::UnsupportedOperation(self, other)),
                 }
             }
@@ -208,97 +307,72 @@ impl Add for TypeInfo {
 impl Sub for TypeInfo {
     type Output = TypeResult;
     fn sub(self, other: TypeInfo) -> TypeResult {
-        match self {
-            TypeInfo::Number => {
-                match other {
-                    TypeInfo::Number => return Ok(TypeInfo::Number),
-                    TypeInfo::Float => return Ok(TypeInfo::Float),
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            TypeInfo::Float => {
-                match other {
-                    TypeInfo::Number => return Ok(TypeInfo::Float),
-                    TypeInfo::Float => return Ok(TypeInfo::Float),
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            TypeInfo::Any => {
-                Ok(other)
-            }
-            _ => Err(TypeError::UnsupportedOperation(self, other)),
-        }
+        self.numeric_operation(other)
     }
 }
 
 impl Mul for TypeInfo {
     type Output = TypeResult;
     fn mul(self, other: TypeInfo) -> TypeResult {
-        match self {
-            TypeInfo::Number => {
-                match other {
-                    TypeInfo::Number => return Ok(TypeInfo::Number),
-                    TypeInfo::Float => return Ok(TypeInfo::Float),
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            TypeInfo::Float => {
-                match other {
-                    TypeInfo::Number => return Ok(TypeInfo::Float),
-                    TypeInfo::Float => return Ok(TypeInfo::Float),
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            TypeInfo::Any => {
-                Ok(other)
-            }
-            _ => Err(TypeError::UnsupportedOperation(self, other)),
-        }
+        self.numeric_operation(other)
     }
 }
 
 impl Div for TypeInfo {
     type Output = TypeResult;
     fn div(self, other: TypeInfo) -> TypeResult {
-        match self {
-            TypeInfo::Number => {
-                match other {
-                    TypeInfo::Number => {
-                        return Ok(TypeInfo::Number);
-                    }
-                    TypeInfo::Float => {
-                        return Ok(TypeInfo::Float);
-                    }
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            TypeInfo::Float => {
-                match other {
-                    TypeInfo::Number => {
-                        return Ok(TypeInfo::Float);
-                    }
-                    TypeInfo::Float => {
-                        return Ok(TypeInfo::Float);
-                    }
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            _ => Err(TypeError::UnsupportedOperation(self, other)),
-        }
+        self.numeric_operation(other)
     }
 }
 
 impl Rem for TypeInfo {
     type Output = TypeResult;
     fn rem(self, other: TypeInfo) -> TypeResult {
+        self.numeric_operation(other)
+    }
+}
+
+impl BitAnd for TypeInfo {
+    type Output = TypeResult;
+    fn bitand(self, other: TypeInfo) -> TypeResult {
+        self.integer_operation(other)
+    }
+}
+
+impl BitOr for TypeInfo {
+    type Output = TypeResult;
+    fn bitor(self, other: TypeInfo) -> TypeResult {
+        self.integer_operation(other)
+    }
+}
+
+impl BitXor for TypeInfo {
+    type Output = TypeResult;
+    fn bitxor(self, other: TypeInfo) -> TypeResult {
+        self.integer_operation(other)
+    }
+}
+
+impl Shl for TypeInfo {
+    type Output = TypeResult;
+    fn shl(self, other: TypeInfo) -> TypeResult {
+        self.integer_operation(other)
+    }
+}
+
+impl Shr for TypeInfo {
+    type Output = TypeResult;
+    fn shr(self, other: TypeInfo) -> TypeResult {
+        self.integer_operation(other)
+    }
+}
+
+impl Not for TypeInfo {
+    type Output = TypeResult;
+    fn not(self) -> TypeResult {
         match self {
-            TypeInfo::Number => {
-                match other {
-                    TypeInfo::Number => return Ok(TypeInfo::Number),
-                    _ => Err(TypeError::UnsupportedOperation(self, other)),
-                }
-            }
-            _ => Err(TypeError::UnsupportedOperation(self, other)),
+            TypeInfo::Number | TypeInfo::Any => Ok(TypeInfo::Number),
+            _ => Err(TypeError::TypeMismatch(self, TypeInfo::Number)),
         }
     }
 }
@@ -318,6 +392,17 @@ mod test {
 
     }
 
+    #[test]
+    fn display_type_info() {
+        assert_eq!(TypeInfo::Array(Box::new(TypeInfo::Number)).to_string(), "[Number]");
+        let function = TypeInfo::Function {
+            parameters: vec![TypeInfo::Number, TypeInfo::String],
+            return_type: Box::new(TypeInfo::Bool),
+        };
+        assert_eq!(function.to_string(), "fn(Number, String) -> Bool");
+        assert_eq!(TypeInfo::StructType { identifier: "Point".to_string() }.to_string(), "Point");
+    }
+
     #[test]
     fn comparison_type_info_with_operations() {
         assert!(TypeInfo::Number == (TypeInfo::Number + TypeInfo::Number).unwrap());
@@ -325,6 +410,17 @@ mod test {
         assert!(TypeInfo::String == (TypeInfo::String + TypeInfo::Number).unwrap());
     }
 
+    #[test]
+    fn mixed_numeric_operations_are_symmetric() {
+        assert_eq!(TypeInfo::Float, (TypeInfo::Number + TypeInfo::Float).unwrap());
+        assert_eq!(TypeInfo::Float, (TypeInfo::Number - TypeInfo::Float).unwrap());
+        assert_eq!(TypeInfo::Float, (TypeInfo::Float - TypeInfo::Number).unwrap());
+        assert_eq!(TypeInfo::Float, (TypeInfo::Number * TypeInfo::Float).unwrap());
+        assert_eq!(TypeInfo::Float, (TypeInfo::Float / TypeInfo::Number).unwrap());
+        assert_eq!(TypeInfo::Number, (TypeInfo::Number % TypeInfo::Number).unwrap());
+        assert!((TypeInfo::Bool - TypeInfo::Float).is_err());
+    }
+
     #[test]
     fn convert_from_datatype_and_perform_operation_and_compare() {
         // TODO flesh these tests out more.
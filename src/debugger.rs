@@ -0,0 +1,511 @@
+use ast::{Ast, SExpression, Datatype, VariableStore, LangResult, LangError};
+use interpreter::parse;
+use formatter::is_uuid;
+use io_context::{self, Sink, Source};
+use locations;
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io;
+use std::io::BufReader;
+use std::rc::Rc;
+
+/// How many lines are shown on each side of the current line by `list`.
+const LIST_CONTEXT: usize = 3;
+
+const HELP: &str = "\
+step, s              Run until the next statement, entering function calls
+next, n              Run until the next statement in this function, stepping over calls
+continue, c          Run until a breakpoint is reached
+break, b <line|fn>   Pause before the statement on the line, or at the start of the function
+break, b             List the breakpoints
+delete, d <line|fn>  Remove a breakpoint, or every breakpoint if none is given
+list, l              Show the source around the current line
+vars                 Show the variables that are visible here
+where, bt            Show the functions that are being called
+print, p <expr>      Evaluate the expression, which can use and assign the variables visible here
+quit, q              Stop the program
+Anything else is evaluated like `print`.
+";
+
+/// Where the debugger pauses the program.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Breakpoint {
+    /// Before the statement that starts on the line, counting from 1.
+    Line(usize),
+    /// Before the first statement of the function, each time it is called.
+    Function(String),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Breakpoint::Line(line) => write!(f, "line {}", line),
+            Breakpoint::Function(ref name) => write!(f, "fn {}", name),
+        }
+    }
+}
+
+/// When the program should pause next, besides at breakpoints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    /// At the next statement, wherever it is.
+    Step,
+    /// At the next statement that isn't inside a call made from this many calls deep.
+    Next(usize),
+    /// Only at breakpoints.
+    Continue,
+}
+
+/// Pauses programs before their statements, so they can be stepped through and their variables inspected.
+///
+/// The file being run is parsed with the line of each statement that starts a line, and the program pauses before those statements.
+/// Included files are parsed without their lines, so their code is run without pausing.
+pub struct Debugger {
+    commands: Source,
+    output: Sink,
+    source_lines: Vec<String>,
+    /// The lines that statements of the file start on, which breakpoints can be put on.
+    statement_lines: BTreeSet<usize>,
+    breakpoints: Vec<Breakpoint>,
+    mode: Mode,
+    /// The names of the functions being called, outermost first.
+    calls: Vec<String>,
+    /// Set when a function with a breakpoint is called, until its first statement is reached.
+    entered_breakpoint: bool,
+    current_line: usize,
+}
+
+impl Debugger {
+    /// Reads commands from stdin and writes to stdout, pausing before the first statement.
+    pub fn standard() -> Debugger {
+        // Like the program's own input, a capacity of 1 leaves the lines after each command for `read_line()`.
+        Debugger::new(
+            Rc::new(RefCell::new(BufReader::with_capacity(1, io::stdin()))),
+            Rc::new(RefCell::new(io::stdout())),
+        )
+    }
+
+    /// Reads commands from the source, one per line, and writes what it shows to the sink.
+    /// The program pauses before its first statement, and runs to the end without pausing once the commands run out.
+    pub fn new(commands: Source, output: Sink) -> Debugger {
+        Debugger {
+            commands,
+            output,
+            source_lines: vec![],
+            statement_lines: BTreeSet::new(),
+            breakpoints: vec![],
+            mode: Mode::Step,
+            calls: vec![],
+            entered_breakpoint: false,
+            current_line: 0,
+        }
+    }
+
+    /// Shows the source of the file being run, and finds the lines of its statements in the program parsed from it.
+    pub(crate) fn load_source(&mut self, source: &str, program: &Ast) {
+        self.source_lines = source.lines().map(String::from).collect();
+        self.statement_lines = locations::statement_lines(program);
+    }
+
+    fn before_statement(&mut self, ast: &Ast, map: &mut VariableStore) -> Result<(), LangError> {
+        if is_declaration(ast) {
+            return Ok(());
+        }
+        // Statements that aren't in the file, like the hidden parts of `for` loops, are run without pausing.
+        let line: usize = match *ast {
+            Ast::Located { line, .. } => line,
+            _ => return Ok(()),
+        };
+        let pause: bool = self.breakpoints.contains(&Breakpoint::Line(line)) || self.entered_breakpoint || match self.mode {
+            Mode::Step => true,
+            Mode::Next(depth) => self.calls.len() <= depth,
+            Mode::Continue => false,
+        };
+        if !pause {
+            return Ok(());
+        }
+        self.entered_breakpoint = false;
+        self.current_line = line;
+        self.pause(map)
+    }
+
+    fn enter_function(&mut self, name: String) {
+        if self.breakpoints.contains(&Breakpoint::Function(name.clone())) {
+            self.entered_breakpoint = true;
+        }
+        self.calls.push(name);
+    }
+
+    fn exit_function(&mut self) {
+        self.calls.pop();
+    }
+
+    /// Reads commands until one resumes the program.
+    fn pause(&mut self, map: &mut VariableStore) -> Result<(), LangError> {
+        let location: String = match self.calls.last() {
+            Some(name) => format!(" in fn {}", name),
+            None => String::new(),
+        };
+        let text: String = format!("Paused at line {}{}\n{}", self.current_line, location, self.source_line(self.current_line, ""));
+        self.write(&text)?;
+        loop {
+            self.write("(debug) ")?;
+            self.output.borrow_mut().flush().map_err(|e| LangError::CouldNotWriteOutput(e.to_string()))?;
+            let mut input: String = String::new();
+            let read: usize = self.commands.borrow_mut()
+                .read_line(&mut input)
+                .map_err(|e| LangError::CouldNotReadInput(e.to_string()))?;
+            if read == 0 {
+                // Without any more commands, let the program finish.
+                self.write("\n")?;
+                self.breakpoints.clear();
+                self.mode = Mode::Continue;
+                return Ok(());
+            }
+            let input: &str = input.trim();
+            let (command, argument) = match input.find(char::is_whitespace) {
+                Some(index) => (&input[..index], input[index..].trim()),
+                None => (input, ""),
+            };
+            match command {
+                "" => {}
+                "step" | "s" => {
+                    self.mode = Mode::Step;
+                    return Ok(());
+                }
+                "next" | "n" => {
+                    self.mode = Mode::Next(self.calls.len());
+                    return Ok(());
+                }
+                "continue" | "c" => {
+                    self.mode = Mode::Continue;
+                    return Ok(());
+                }
+                "break" | "b" => self.add_breakpoint(argument)?,
+                "delete" | "d" => self.delete_breakpoint(argument)?,
+                "list" | "l" => self.list()?,
+                "vars" => self.show_variables(map)?,
+                "where" | "bt" => self.show_calls()?,
+                "print" | "p" => self.evaluate(argument, map)?,
+                "help" | "h" => self.write(HELP)?,
+                "quit" | "q" => return Err(LangError::StoppedByDebugger),
+                _ => self.evaluate(input, map)?,
+            }
+        }
+    }
+
+    fn add_breakpoint(&mut self, argument: &str) -> Result<(), LangError> {
+        if argument.is_empty() {
+            let listed: String = self.breakpoints.iter().map(|breakpoint| format!("{}\n", breakpoint)).collect();
+            return self.write(if listed.is_empty() { "No breakpoints\n" } else { &listed });
+        }
+        let breakpoint: Breakpoint = match argument.parse::<usize>() {
+            Ok(line) if self.statement_lines.contains(&line) => Breakpoint::Line(line),
+            Ok(line) => return self.write(&format!("No statement starts on line {}\n", line)),
+            Err(_) => Breakpoint::Function(argument.to_string()),
+        };
+        let text: String = format!("Breakpoint at {}\n", breakpoint);
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+        self.write(&text)
+    }
+
+    fn delete_breakpoint(&mut self, argument: &str) -> Result<(), LangError> {
+        if argument.is_empty() {
+            self.breakpoints.clear();
+            return self.write("Deleted every breakpoint\n");
+        }
+        let breakpoint: Breakpoint = match argument.parse::<usize>() {
+            Ok(line) => Breakpoint::Line(line),
+            Err(_) => Breakpoint::Function(argument.to_string()),
+        };
+        match self.breakpoints.iter().position(|existing| *existing == breakpoint) {
+            Some(index) => {
+                self.breakpoints.remove(index);
+                self.write(&format!("Deleted the breakpoint at {}\n", breakpoint))
+            }
+            None => self.write(&format!("There is no breakpoint at {}\n", breakpoint)),
+        }
+    }
+
+    fn list(&self) -> Result<(), LangError> {
+        let first: usize = self.current_line.saturating_sub(LIST_CONTEXT).max(1);
+        let last: usize = (self.current_line + LIST_CONTEXT).min(self.source_lines.len());
+        let text: String = (first..=last)
+            .map(|line| self.source_line(line, if line == self.current_line { ">" } else { " " }))
+            .collect();
+        self.write(&text)
+    }
+
+    /// Shows the variables in the order of their names, leaving out functions, struct types, and the hidden variables of `for` loops.
+    fn show_variables(&self, map: &VariableStore) -> Result<(), LangError> {
+        let mut names: Vec<&String> = map.iter()
+            .filter(|&(name, value)| {
+                !is_uuid(name) && !matches!(
+                    **value,
+                    Datatype::Function { .. } | Datatype::BuiltinFunction(_) | Datatype::NativeFunction(_) | Datatype::StructType { .. }
+                )
+            })
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        let text: String = names.into_iter().map(|name| format!("{} = {}\n", name, map[name].representation())).collect();
+        self.write(if text.is_empty() { "No variables\n" } else { &text })
+    }
+
+    fn show_calls(&self) -> Result<(), LangError> {
+        let mut text: String = self.calls.iter().rev().map(|name| format!("fn {}\n", name)).collect();
+        text.push_str("top level\n");
+        self.write(&text)
+    }
+
+    /// Evaluates the source against the variables where the program is paused.
+    /// The debugger isn't installed while this runs, so functions called by the expression don't pause.
+    fn evaluate(&self, source: &str, map: &mut VariableStore) -> Result<(), LangError> {
+        let result: LangResult = parse(source).and_then(|ast| ast.evaluate(map));
+        match result {
+            Ok(value) => self.write(&format!("{}\n", value.representation())),
+            Err(e) => self.write(&format!("Error: {}\n", e)),
+        }
+    }
+
+    fn source_line(&self, line: usize, marker: &str) -> String {
+        let text: &str = self.source_lines.get(line.wrapping_sub(1)).map_or("", String::as_str);
+        format!("{:1}{:>4} | {}\n", marker, line, text)
+    }
+
+    fn write(&self, text: &str) -> Result<(), LangError> {
+        io_context::write(&self.output, text)
+    }
+}
+
+/// Functions and structs are declared before anything else runs, so stepping over them would only be noise.
+fn is_declaration(ast: &Ast) -> bool {
+    matches!(*ast.unlocated(), Ast::SExpr(SExpression::DeclareFunction { .. }) | Ast::SExpr(SExpression::StructDeclaration { .. }))
+}
+
+thread_local! {
+    static CURRENT_DEBUGGER: RefCell<Option<Debugger>> = const { RefCell::new(None) };
+}
+
+/// Runs the function with the debugger installed, putting the debugger back afterwards,
+/// so the breakpoints set while it was paused are kept for the next evaluation.
+pub fn with_debugger<F, R>(debugger: &mut Option<Debugger>, function: F) -> R
+    where F: FnOnce() -> R
+{
+    let previous: Option<Debugger> = CURRENT_DEBUGGER.with(|current| current.replace(debugger.take()));
+    let _restore = RestoreDebugger { debugger, previous: Some(previous) };
+    function()
+}
+
+/// Gives the debugger back and puts the previous one back when it is dropped, so both happen even if the function panics.
+struct RestoreDebugger<'a> {
+    debugger: &'a mut Option<Debugger>,
+    previous: Option<Option<Debugger>>,
+}
+
+impl<'a> Drop for RestoreDebugger<'a> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            // The thread local may already be gone if this is dropped while the thread exits.
+            if let Ok(debugger) = CURRENT_DEBUGGER.try_with(|current| current.replace(previous)) {
+                *self.debugger = debugger;
+            }
+        }
+    }
+}
+
+/// Called by `Ast::evaluate` before each statement in an expression list, which pauses if the debugger should stop there.
+///
+/// The debugger is taken out while it is paused, so evaluating expressions at the pause doesn't pause again.
+pub(crate) fn before_statement(ast: &Ast, map: &mut VariableStore) -> Result<(), LangError> {
+    let mut debugger: Debugger = match CURRENT_DEBUGGER.with(|current| current.borrow_mut().take()) {
+        Some(debugger) => debugger,
+        None => return Ok(()),
+    };
+    let result: Result<(), LangError> = debugger.before_statement(ast, map);
+    CURRENT_DEBUGGER.with(|current| *current.borrow_mut() = Some(debugger));
+    result
+}
+
+/// Keeps track of the function being called while it runs, for `next`, `where` and breakpoints on functions.
+pub(crate) fn calling<F>(identifier: &Ast, function: F) -> LangResult
+    where F: FnOnce() -> LangResult
+{
+    let name: String = match *identifier {
+        Ast::ValueIdentifier(ref name) => name.clone(),
+        _ => return function(),
+    };
+    let debugging: bool = CURRENT_DEBUGGER.with(|current| {
+        current.borrow_mut().as_mut().map(|debugger| debugger.enter_function(name)).is_some()
+    });
+    let result: LangResult = function();
+    if debugging {
+        CURRENT_DEBUGGER.with(|current| {
+            if let Some(ref mut debugger) = *current.borrow_mut() {
+                debugger.exit_function();
+            }
+        });
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interpreter::Interpreter;
+    use io_context::IoContext;
+    use std::io::Cursor;
+    use interpreter::parse_located;
+    use std::env;
+    use std::fs;
+
+    const PROGRAM: &str = "\
+fn add(a: Number, b: Number) -> Number {
+    let total := a + b
+    total
+}
+
+fn main() -> Number {
+    let x := 3
+    let y := add(x, 4)
+    let z := y * 2
+    for i in [0..2] {
+        z += i
+    }
+    z
+}
+";
+
+    /// Runs the program under the debugger with the commands, producing what the debugger and program wrote, and the result.
+    fn debug(name: &str, source: &str, commands: &str) -> (String, LangResult) {
+        let path = env::temp_dir().join(format!("hlwnpa_debugger_{}.hlw", name));
+        fs::write(&path, source).unwrap();
+        let output: Rc<RefCell<Vec<u8>>> = Rc::new(RefCell::new(vec![]));
+        let mut interpreter: Interpreter = Interpreter::new();
+        interpreter.set_io_context(IoContext {
+            stdout: output.clone(),
+            ..IoContext::standard()
+        });
+        interpreter.set_debugger(Debugger::new(Rc::new(RefCell::new(Cursor::new(commands.to_string()))), output.clone()));
+        let result: LangResult = interpreter.eval_file(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        let written: String = String::from_utf8(output.borrow().clone()).unwrap();
+        (written, result)
+    }
+
+    /// The lines the program paused at, in order.
+    fn paused_lines(output: &str) -> Vec<String> {
+        output.lines()
+            .filter_map(|line| line.find("Paused at ").map(|index| line[index + "Paused at ".len()..].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn statements_are_located() {
+        let lines: Vec<usize> = locations::statement_lines(&parse_located(PROGRAM).unwrap()).into_iter().collect();
+        assert_eq!(vec![1, 2, 3, 6, 7, 8, 9, 10, 11, 13], lines);
+        let parts_of_expressions: Vec<usize> = locations::statement_lines(
+            &parse_located("let s := new Point {\n    x: 1\n    y: 2\n}\nlet a := [\n    1,\n    2, 3\n]").unwrap()
+        ).into_iter().collect();
+        assert_eq!(vec![1, 5], parts_of_expressions);
+    }
+
+    #[test]
+    fn step_enters_functions() {
+        let (output, result) = debug("step", PROGRAM, "s\ns\ns\ns\ns\nc\n");
+        assert_eq!(Datatype::Number(15), *result.unwrap());
+        assert_eq!(
+            vec!["line 7 in fn main", "line 8 in fn main", "line 2 in fn add", "line 3 in fn add", "line 9 in fn main", "line 10 in fn main"],
+            paused_lines(&output)
+        );
+        assert!(output.contains("   8 |     let y := add(x, 4)\n"));
+    }
+
+    #[test]
+    fn next_steps_over_calls_and_through_loops() {
+        let (output, _) = debug("next", PROGRAM, "n\nn\nn\nn\nn\nn\nn\n");
+        assert_eq!(
+            vec!["line 7 in fn main", "line 8 in fn main", "line 9 in fn main", "line 10 in fn main", "line 11 in fn main", "line 11 in fn main", "line 13 in fn main"],
+            paused_lines(&output)
+        );
+    }
+
+    #[test]
+    fn breakpoints_pause_on_lines_and_functions() {
+        let (output, result) = debug("breakpoints", PROGRAM, "b add\nb 11\nb 5\nc\nc\nd 11\nb\nc\n");
+        assert_eq!(Datatype::Number(15), *result.unwrap());
+        assert_eq!(vec!["line 7 in fn main", "line 2 in fn add", "line 11 in fn main"], paused_lines(&output));
+        assert!(output.contains("Breakpoint at fn add\n"));
+        assert!(output.contains("Breakpoint at line 11\n"));
+        assert!(output.contains("No statement starts on line 5\n"));
+        assert!(output.contains("Deleted the breakpoint at line 11\n(debug) fn add\n"));
+    }
+
+    #[test]
+    fn repeated_statements_are_told_apart() {
+        let source: &str = "\
+let count := 0
+count += 1
+println(count)
+count += 1
+";
+        let (output, _) = debug("repeated", source, "b 4\nc\np count\nc\n");
+        assert_eq!(vec!["line 1", "line 4"], paused_lines(&output));
+        assert!(output.contains("(debug) 1\n"), "{}", output);
+
+        let calls: &str = "fn bump(count: Number) -> Number {\n    count += 1\n    count += 1\n    count\n}\nbump(0)\nbump(5)\n";
+        let (output, result) = debug("repeated_in_function", calls, "b 3\nc\np count\nc\np count\nc\n");
+        assert_eq!(Datatype::Number(7), *result.unwrap());
+        assert_eq!(vec!["line 6", "line 3 in fn bump", "line 3 in fn bump"], paused_lines(&output));
+        assert!(output.contains("(debug) 1\n"), "{}", output);
+        assert!(output.contains("(debug) 6\n"), "{}", output);
+    }
+
+    #[test]
+    fn expressions_are_evaluated_where_the_program_is_paused() {
+        let (output, result) = debug("inspect", PROGRAM, "b add\nc\np a + b\nvars\nwhere\np missing\na := 10\nc\n");
+        assert_eq!(Datatype::Number(29), *result.unwrap());
+        assert!(output.contains("(debug) 7\n"));
+        assert!(output.contains("(debug) a = 3\nb = 4\nx = 3\n"), "{}", output);
+        assert!(output.contains("(debug) fn add\nfn main\ntop level\n"));
+        assert!(output.contains("(debug) Error: "));
+    }
+
+    #[test]
+    fn list_marks_the_current_line() {
+        let (output, _) = debug("list", PROGRAM, "l\n");
+        assert!(output.contains("    4 | }\n    5 | \n    6 | fn main() -> Number {\n>   7 |     let x := 3\n    8 |     let y := add(x, 4)\n"), "{}", output);
+    }
+
+    #[test]
+    fn quit_stops_the_program_even_inside_try() {
+        let source: &str = "try {\n    println(\"before\")\n    println(\"after\")\n} catch e {\n    println(e)\n}\n";
+        let (output, result) = debug("quit", source, "n\nq\n");
+        assert_eq!(Err(LangError::StoppedByDebugger), result);
+        assert_eq!(vec!["line 1", "line 2"], paused_lines(&output));
+        assert!(!output.contains("\nbefore\n"));
+    }
+
+    #[test]
+    fn the_program_finishes_once_the_commands_run_out() {
+        let (output, result) = debug("finishes", "println(\"one\")\nprintln(\"two\")\n", "");
+        assert_eq!(Datatype::String("two\n".to_string()), *result.unwrap());
+        assert_eq!(vec!["line 1"], paused_lines(&output));
+        assert!(output.ends_with("one\ntwo\n"));
+    }
+
+    #[test]
+    fn nothing_pauses_without_a_debugger() {
+        let mut debugger: Option<Debugger> = None;
+        let result: LangResult = with_debugger(&mut debugger, || {
+            let mut map: VariableStore = VariableStore::new();
+            parse("let a := 1\na + 1").and_then(|ast| ast.evaluate(&mut map))
+        });
+        assert_eq!(Datatype::Number(2), *result.unwrap());
+    }
+}
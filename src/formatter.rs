@@ -0,0 +1,705 @@
+use ast::{Ast, SExpression, Datatype, TypeInfo, Operator, LangError};
+use parser::program;
+use preprocessor::preprocess;
+use nom::IResult;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The indentation used for each level of nesting.
+const INDENT: &str = "    ";
+/// The precedence of expressions that don't need parentheses anywhere, like literals and function calls.
+const ATOM_PRECEDENCE: u32 = 0;
+/// The precedence of `-`, `!` and `~`, which bind tighter than any binary operator.
+const UNARY_PRECEDENCE: u32 = 1;
+/// The precedence of anything that can only be a statement, like a declaration or loop.
+const STATEMENT_PRECEDENCE: u32 = 13;
+
+/// Reasons an AST can't be turned back into source code.
+#[derive(PartialEq, Debug, Clone)]
+pub enum FormatError {
+    /// Strings can't contain `"` or `\`, because the language has no way to escape them.
+    UnrepresentableString(String),
+    /// Infinite and NaN floats have no literal.
+    UnrepresentableFloat(f64),
+    /// Only the types that can be written in a type signature can be formatted.
+    UnrepresentableType(TypeInfo),
+    /// The AST has a shape that the parser never produces, like a struct value that doesn't know which struct it is.
+    UnrepresentableAst(Ast),
+    /// The source doesn't parse, or only the start of it does.
+    InvalidSyntax(String),
+    /// The formatted source parses to a different program, so it isn't used.
+    ChangesProgram(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatError::UnrepresentableString(ref string) => write!(f, "The string {:?} can't be written as a literal", string),
+            FormatError::UnrepresentableFloat(value) => write!(f, "The float {} can't be written as a literal", value),
+            FormatError::UnrepresentableType(ref type_info) => write!(f, "The type {} can't be written in a type signature", type_info),
+            FormatError::UnrepresentableAst(ref ast) => write!(f, "The parser doesn't produce {:?}", ast),
+            FormatError::InvalidSyntax(ref reason) => write!(f, "{}", reason),
+            FormatError::ChangesProgram(ref formatted) => write!(f, "Formatting would change the program into:\n{}", formatted),
+        }
+    }
+}
+
+/// Formats the source of a program.
+/// The formatted source is parsed again, and is only returned if it is the same program as the original.
+///
+/// The language doesn't have comments yet, so there aren't any to keep.
+pub fn format_source(source: &str) -> Result<String, FormatError> {
+    let ast: Ast = parse_all(source)?;
+    let formatted: String = format_program(&ast)?;
+    match parse_all(&formatted) {
+        Ok(ref reparsed) if same_program(&ast, reparsed) => Ok(formatted),
+        _ => Err(FormatError::ChangesProgram(formatted)),
+    }
+}
+
+/// Formats the `.hlw` file in place, returning whether it changed.
+/// With `check`, the file is left alone, and the result only says whether it would change.
+pub fn format_file(file: &Path, check: bool) -> Result<bool, LangError> {
+    let filename: String = file.display().to_string();
+    let source: String = fs::read_to_string(file).map_err(|e| {
+        LangError::CouldNotReadFile { filename: filename.clone(), reason: e.to_string() }
+    })?;
+    let formatted: String = format_source(&source).map_err(|e| {
+        LangError::CouldNotFormatFile { filename: filename.clone(), reason: e.to_string() }
+    })?;
+    if formatted == source {
+        return Ok(false);
+    }
+    if !check {
+        fs::write(file, formatted).map_err(|e| {
+            LangError::CouldNotWriteFile { filename: filename.clone(), reason: e.to_string() }
+        })?;
+    }
+    Ok(true)
+}
+
+/// Parses the source, failing if any of it is left over.
+/// Formatting a program that only partly parsed would throw away the rest of it.
+fn parse_all(source: &str) -> Result<Ast, FormatError> {
+    if source.trim().is_empty() {
+        return Ok(Ast::Literal(Datatype::None));
+    }
+    let preprocessed: String = preprocess(source);
+    match program(preprocessed.as_bytes()) {
+        IResult::Done(rest, ast) => {
+            let rest: String = String::from_utf8_lossy(rest).to_string();
+            match rest.trim().lines().next() {
+                Some(line) => Err(FormatError::InvalidSyntax(format!("Couldn't parse from: {}", line))),
+                None => Ok(ast),
+            }
+        }
+        IResult::Error(e) => Err(FormatError::InvalidSyntax(e.to_string())),
+        IResult::Incomplete(i) => Err(FormatError::InvalidSyntax(format!("Parser returned incomplete: {:?}", i))),
+    }
+}
+
+/// Checks if two ASTs are the same program.
+/// The variables that `for` loops are desugared with get new names each time they are parsed, so their names are ignored.
+pub fn same_program(lhs: &Ast, rhs: &Ast) -> bool {
+    hide_generated_names(&format!("{:?}", lhs)) == hide_generated_names(&format!("{:?}", rhs))
+}
+
+/// Replaces the uuids that name generated variables with their order of appearance, so `#0` is the first one found.
+fn hide_generated_names(debug: &str) -> String {
+    let mut names: Vec<&str> = vec![];
+    let mut hidden: String = String::new();
+    let mut rest: &str = debug;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_hexdigit()) {
+        hidden.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let candidate: &str = rest.get(..UUID_LENGTH).unwrap_or("");
+        if is_uuid(candidate) {
+            let index: usize = names.iter().position(|name| *name == candidate).unwrap_or_else(|| {
+                names.push(candidate);
+                names.len() - 1
+            });
+            hidden.push_str(&format!("#{}", index));
+            rest = &rest[UUID_LENGTH..];
+        } else {
+            let next: usize = rest.chars().next().map_or(1, char::len_utf8);
+            hidden.push_str(&rest[..next]);
+            rest = &rest[next..];
+        }
+    }
+    hidden.push_str(rest);
+    hidden
+}
+
+/// The length of a hyphenated uuid, like `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+const UUID_LENGTH: usize = 36;
+
+pub(crate) fn is_uuid(candidate: &str) -> bool {
+    candidate.len() == UUID_LENGTH && candidate.char_indices().all(|(index, c)| {
+        match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        }
+    })
+}
+
+/// Turns the AST of a program back into source code, with each statement on its own line.
+/// Parsing the source produces the same AST, except that the variables hidden in `for` loops get new names.
+pub fn format_program(ast: &Ast) -> Result<String, FormatError> {
+    match *ast {
+        Ast::Literal(Datatype::None) => Ok(String::new()), // What an empty program parses to.
+        Ast::ExpressionList(ref statements) => {
+            let mut source: String = String::new();
+            let mut previous: Option<&Ast> = None;
+            for statement in statements {
+                // Separate functions and structs from what is around them.
+                if let Some(previous) = previous {
+                    if is_declaration(previous) || is_declaration(statement) {
+                        source.push('\n');
+                    }
+                }
+                source.push_str(&format_statement(statement, 0)?);
+                source.push('\n');
+                previous = Some(statement);
+            }
+            Ok(source)
+        }
+        ref other => Ok(format_statement(other, 0)? + "\n"),
+    }
+}
+
+/// Writes the type the way it is written in a type signature.
+pub fn format_type(type_info: &TypeInfo) -> Result<String, FormatError> {
+    match *type_info {
+        TypeInfo::Number => Ok("Number".to_string()),
+        TypeInfo::Float => Ok("Float".to_string()),
+        TypeInfo::String => Ok("String".to_string()),
+        TypeInfo::Bool => Ok("Bool".to_string()),
+        TypeInfo::Array(ref element_type) => Ok(format!("[{}]", format_type(element_type)?)),
+        TypeInfo::StructType { ref identifier } => Ok(identifier.clone()),
+        ref other => Err(FormatError::UnrepresentableType(other.clone())),
+    }
+}
+
+fn is_declaration(ast: &Ast) -> bool {
+    matches!(*ast.unlocated(), Ast::SExpr(SExpression::DeclareFunction { .. }) | Ast::SExpr(SExpression::StructDeclaration { .. }))
+}
+
+/// Formats a statement, indenting every line after the first to the given depth.
+fn format_statement(ast: &Ast, depth: usize) -> Result<String, FormatError> {
+    match *ast {
+        Ast::Located { ref ast, .. } => format_statement(ast, depth),
+        Ast::ExpressionList(ref statements) => {
+            match format_for_loop(statements, depth) {
+                Some(for_loop) => for_loop,
+                None => format_statements(statements, depth), // Only for loops create nested lists, but this keeps anything else readable.
+            }
+        }
+        Ast::Conditional { ref condition, ref true_expr, ref false_expr } => {
+            let mut source: String = format!("if {} {}", format_expression(condition)?, format_block(true_expr, depth)?);
+            if let Some(ref false_expr) = *false_expr {
+                source.push_str(&format!(" else {}", format_block(false_expr, depth)?));
+            }
+            Ok(source)
+        }
+        Ast::SExpr(ref sexpr) => {
+            match *sexpr {
+                SExpression::Loop { ref conditional, ref body } => {
+                    Ok(format!("while {} {}", format_expression(conditional)?, format_block(body, depth)?))
+                }
+                SExpression::TryCatch { ref body, ref error_identifier, ref handler } => {
+                    Ok(format!(
+                        "try {} catch {} {}",
+                        format_block(body, depth)?,
+                        format_expression(error_identifier)?,
+                        format_block(handler, depth)?
+                    ))
+                }
+                SExpression::VariableDeclaration { ref identifier, ref ast } => {
+                    Ok(format!("let {} := {}", format_declared_identifier(identifier)?, format_expression(ast)?))
+                }
+                SExpression::ConstDeclaration { ref identifier, ref ast } => {
+                    Ok(format!("const {} := {}", format_declared_identifier(identifier)?, format_expression(ast)?))
+                }
+                SExpression::DeclareFunction { ref identifier, ref function_datatype } => {
+                    match **function_datatype {
+                        Ast::Literal(Datatype::Function { ref parameters, ref body, ref return_type }) => {
+                            Ok(format!(
+                                "fn {}({}) -> {} {}",
+                                format_expression(identifier)?,
+                                format_list(parameters, ", ", format_declared_identifier)?,
+                                format_type(return_type)?,
+                                format_block(body, depth)?
+                            ))
+                        }
+                        _ => Err(FormatError::UnrepresentableAst(ast.clone())),
+                    }
+                }
+                SExpression::StructDeclaration { ref identifier, ref struct_type_info } => {
+                    let fields: Vec<Ast> = match **struct_type_info {
+                        Ast::ExpressionList(ref fields) => fields.clone(),
+                        _ => return Err(FormatError::UnrepresentableAst(ast.clone())),
+                    };
+                    let mut source: String = format!("struct {} {{", format_expression(identifier)?);
+                    if fields.is_empty() {
+                        return Ok(source + "}");
+                    }
+                    source.push('\n');
+                    for field in fields {
+                        source.push_str(&indentation(depth + 1));
+                        source.push_str(&format_declared_identifier(&field)?);
+                        source.push('\n');
+                    }
+                    source.push_str(&indentation(depth));
+                    source.push('}');
+                    Ok(source)
+                }
+                SExpression::Include(ref filename) => Ok(format!("include {}", format_expression(filename)?)),
+                _ => format_expression(ast),
+            }
+        }
+        _ => format_expression(ast),
+    }
+}
+
+/// Formats the statements one per line, with every line but the first indented to the given depth.
+fn format_statements(statements: &[Ast], depth: usize) -> Result<String, FormatError> {
+    let lines: Vec<String> = statements.iter()
+        .map(|statement| format_statement(statement, depth))
+        .collect::<Result<Vec<String>, FormatError>>()?;
+    Ok(lines.join(&format!("\n{}", indentation(depth))))
+}
+
+/// Formats the body of a function, loop, or conditional, with its statements indented one level deeper than the braces.
+fn format_block(body: &Ast, depth: usize) -> Result<String, FormatError> {
+    let statements: &[Ast] = match *body {
+        Ast::ExpressionList(ref statements) => statements,
+        ref other => return Err(FormatError::UnrepresentableAst(other.clone())),
+    };
+    if statements.is_empty() {
+        return Ok("{}".to_string());
+    }
+    Ok(format!(
+        "{{\n{}{}\n{}}}",
+        indentation(depth + 1),
+        format_statements(statements, depth + 1)?,
+        indentation(depth)
+    ))
+}
+
+fn indentation(depth: usize) -> String {
+    INDENT.repeat(depth)
+}
+
+/// Recognizes the while loop that a `for` loop is desugared into, and formats it as the `for` loop.
+/// The loop walks a hidden index over the array, declaring the loop variable from the array at the start of each iteration.
+/// If the array isn't a variable, it is stored in a hidden variable before the loop.
+fn format_for_loop(statements: &[Ast], depth: usize) -> Option<Result<String, FormatError>> {
+    let (array, length, loop_) = match *statements {
+        [Ast::SExpr(SExpression::VariableDeclaration { .. }), ref length, ref loop_] => {
+            match *length {
+                Ast::SExpr(SExpression::VariableDeclaration { ref ast, .. }) => match **ast {
+                    Ast::SExpr(SExpression::GetArrayLength(ref array)) => (array.as_ref(), length, loop_),
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+        [Ast::SExpr(SExpression::VariableDeclaration { .. }), Ast::SExpr(SExpression::VariableDeclaration { ref ast, .. }), ref length, ref loop_] => {
+            (ast.as_ref(), length, loop_)
+        }
+        _ => return None,
+    };
+    match *length {
+        Ast::SExpr(SExpression::VariableDeclaration { ref ast, .. }) if matches!(**ast, Ast::SExpr(SExpression::GetArrayLength(_))) => {}
+        _ => return None,
+    }
+    let body: &[Ast] = match *loop_ {
+        Ast::SExpr(SExpression::Loop { ref body, .. }) => match **body {
+            Ast::ExpressionList(ref body) => body,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match *body {
+        [Ast::SExpr(SExpression::VariableDeclaration { ref identifier, ref ast }), ref for_body, Ast::SExpr(SExpression::Assignment { .. })]
+            if matches!(**ast, Ast::SExpr(SExpression::AccessArray { .. })) => {
+            Some(format_expression(identifier).and_then(|identifier| {
+                Ok(format!("for {} in {} {}", identifier, format_expression(array)?, format_block(for_body, depth)?))
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Formats the identifier of a declaration or parameter, along with its type if one is given.
+fn format_declared_identifier(identifier: &Ast) -> Result<String, FormatError> {
+    match *identifier {
+        Ast::SExpr(SExpression::TypeAssignment { ref identifier, ref type_info }) => {
+            Ok(format!("{}: {}", format_expression(identifier)?, format_expression(type_info)?))
+        }
+        ref other => format_expression(other),
+    }
+}
+
+/// Formats the elements of an expression list, separated by the separator.
+fn format_list(list: &Ast, separator: &str, format: fn(&Ast) -> Result<String, FormatError>) -> Result<String, FormatError> {
+    match *list {
+        Ast::ExpressionList(ref elements) => {
+            let elements: Vec<String> = elements.iter().map(format).collect::<Result<Vec<String>, FormatError>>()?;
+            Ok(elements.join(separator))
+        }
+        ref other => Err(FormatError::UnrepresentableAst(other.clone())),
+    }
+}
+
+fn format_expression(ast: &Ast) -> Result<String, FormatError> {
+    match *ast {
+        Ast::Literal(ref datatype) => format_literal(datatype),
+        Ast::ValueIdentifier(ref identifier) => Ok(identifier.clone()),
+        Ast::Type(ref type_info) => format_type(type_info),
+        Ast::SExpr(ref sexpr) => {
+            if let Some((operator, lhs, rhs)) = binary_operation(sexpr) {
+                let precedence: u32 = operator.clone().into();
+                let right_associative: bool = operator.is_right_associative();
+                return Ok(format!(
+                    "{} {} {}",
+                    format_operand(lhs, precedence, right_associative)?,
+                    operator.symbol(),
+                    format_operand(rhs, precedence, !right_associative)?
+                ));
+            }
+            match *sexpr {
+                SExpression::Invert(ref operand) => Ok(format!("!{}", format_unary_operand(operand)?)),
+                SExpression::Negate(ref operand) => Ok(format!("-{}", format_unary_operand(operand)?)),
+                SExpression::BitwiseNot(ref operand) => Ok(format!("~{}", format_unary_operand(operand)?)),
+                SExpression::Increment(ref operand) if !is_identifier(operand) => Ok(format!("{}++", format_postfix_target(operand)?)),
+                SExpression::Decrement(ref operand) if !is_identifier(operand) => Ok(format!("{}--", format_postfix_target(operand)?)),
+                SExpression::Assignment { ref identifier, ref ast } => format_assignment(identifier, ast),
+                SExpression::AccessArray { ref identifier, ref index } => {
+                    Ok(format!("{}[{}]", format_postfix_target(identifier)?, format_expression(index)?))
+                }
+                SExpression::AccessStructField { ref identifier, ref field_identifier } => {
+                    Ok(format!("{}.{}", format_postfix_target(identifier)?, format_expression(field_identifier)?))
+                }
+                SExpression::ExecuteFn { ref identifier, ref parameters } => {
+                    Ok(format!("{}({})", format_postfix_target(identifier)?, format_list(parameters, ", ", format_expression)?))
+                }
+                SExpression::CreateStruct { ref identifier, ref struct_datatype } => {
+                    let fields: String = format_list(struct_datatype, " ", format_field_assignment)?;
+                    if fields.is_empty() {
+                        Ok(format!("new {} {{}}", format_expression(identifier)?))
+                    } else {
+                        Ok(format!("new {} {{ {} }}", format_expression(identifier)?, fields))
+                    }
+                }
+                SExpression::CreateArray(ref elements) => Ok(format!("[{}]", format_list(elements, ", ", format_expression)?)),
+                SExpression::Range { ref start, ref end } => Ok(format!("[{}..{}]", format_expression(start)?, format_expression(end)?)),
+                _ => Err(FormatError::UnrepresentableAst(ast.clone())),
+            }
+        }
+        _ => Err(FormatError::UnrepresentableAst(ast.clone())),
+    }
+}
+
+/// `x := x + 1` is written as `x += 1`, and `x := x++` as `x++`, because that is what they were parsed from.
+fn format_assignment(identifier: &Ast, ast: &Ast) -> Result<String, FormatError> {
+    if let Ast::SExpr(ref sexpr) = *ast {
+        match *sexpr {
+            SExpression::Increment(ref operand) if **operand == *identifier => return Ok(format!("{}++", format_expression(identifier)?)),
+            SExpression::Decrement(ref operand) if **operand == *identifier => return Ok(format!("{}--", format_expression(identifier)?)),
+            _ => {}
+        }
+        if let Some((operator, lhs, rhs)) = binary_operation(sexpr) {
+            let compound_operator: Option<Operator> = match operator {
+                Operator::Plus => Some(Operator::AddAssign),
+                Operator::Minus => Some(Operator::SubtractAssign),
+                Operator::Times => Some(Operator::MultiplyAssign),
+                Operator::Divide => Some(Operator::DivideAssign),
+                Operator::Modulo => Some(Operator::ModuloAssign),
+                _ => None,
+            };
+            if let Some(compound_operator) = compound_operator {
+                if *lhs == *identifier {
+                    return Ok(format!("{} {} {}", format_expression(identifier)?, compound_operator.symbol(), format_expression(rhs)?));
+                }
+            }
+        }
+    }
+    Ok(format!(
+        "{} {} {}",
+        format_operand(identifier, Operator::Assignment.into(), true)?,
+        Operator::Assignment.symbol(),
+        format_expression(ast)?
+    ))
+}
+
+fn format_field_assignment(field: &Ast) -> Result<String, FormatError> {
+    match *field {
+        Ast::SExpr(SExpression::FieldAssignment { ref identifier, ref ast }) => {
+            Ok(format!("{}: {}", format_expression(identifier)?, format_expression(ast)?))
+        }
+        ref other => Err(FormatError::UnrepresentableAst(other.clone())),
+    }
+}
+
+/// Formats an operand of a binary operator, wrapping it in parentheses if it would otherwise group differently.
+/// `tie_needs_parentheses` says if an operand with the same precedence as the operator needs them.
+fn format_operand(operand: &Ast, precedence: u32, tie_needs_parentheses: bool) -> Result<String, FormatError> {
+    let operand_precedence: u32 = precedence_of(operand);
+    if operand_precedence > precedence || (operand_precedence == precedence && tie_needs_parentheses) {
+        Ok(format!("({})", format_expression(operand)?))
+    } else {
+        format_expression(operand)
+    }
+}
+
+/// `-`, `!` and `~` only take a literal, identifier, parenthesized expression or another unary operation.
+/// Postfix operators after them apply to the result, so `-a[0]` indexes into `-a`.
+fn format_unary_operand(operand: &Ast) -> Result<String, FormatError> {
+    if precedence_of(operand) > UNARY_PRECEDENCE || is_postfix(operand) {
+        Ok(format!("({})", format_expression(operand)?))
+    } else {
+        format_expression(operand)
+    }
+}
+
+fn format_postfix_target(target: &Ast) -> Result<String, FormatError> {
+    if precedence_of(target) > ATOM_PRECEDENCE {
+        Ok(format!("({})", format_expression(target)?))
+    } else {
+        format_expression(target)
+    }
+}
+
+fn format_literal(datatype: &Datatype) -> Result<String, FormatError> {
+    match *datatype {
+        Datatype::Number(value) if value == i32::MIN => Ok(format!("(-{} - 1)", i32::MAX)), // The positive value doesn't fit.
+        Datatype::Number(value) if value < 0 => Ok(format!("-{}", -value)),
+        Datatype::Number(value) => Ok(value.to_string()),
+        Datatype::Float(value) if !value.is_finite() => Err(FormatError::UnrepresentableFloat(value)),
+        Datatype::Float(value) => {
+            let mut source: String = value.abs().to_string();
+            if !source.contains('.') {
+                source.push_str(".0"); // Floats need a decimal point, or they would be parsed as Numbers.
+            }
+            if value.is_sign_negative() {
+                source.insert(0, '-');
+            }
+            Ok(source)
+        }
+        Datatype::String(ref value) => {
+            if value.contains('"') || value.contains('\\') {
+                return Err(FormatError::UnrepresentableString(value.clone()));
+            }
+            let escaped: String = value.replace('\n', "\\n").replace('\t', "\\t").replace('\r', "\\r");
+            Ok(format!("\"{}\"", escaped))
+        }
+        Datatype::Bool(value) => Ok(value.to_string()),
+        Datatype::Array { ref value, .. } => {
+            let elements: Vec<String> = value.iter()
+                .map(|element| format_literal(element))
+                .collect::<Result<Vec<String>, FormatError>>()?;
+            Ok(format!("[{}]", elements.join(", ")))
+        }
+        ref other => Err(FormatError::UnrepresentableAst(Ast::Literal(other.clone()))),
+    }
+}
+
+/// The operator and operands of S-Expressions that are written with a binary operator.
+fn binary_operation(sexpr: &SExpression) -> Option<(Operator, &Ast, &Ast)> {
+    let (operator, lhs, rhs) = match *sexpr {
+        SExpression::Add(ref lhs, ref rhs) => (Operator::Plus, lhs, rhs),
+        SExpression::Subtract(ref lhs, ref rhs) => (Operator::Minus, lhs, rhs),
+        SExpression::Multiply(ref lhs, ref rhs) => (Operator::Times, lhs, rhs),
+        SExpression::Divide(ref lhs, ref rhs) => (Operator::Divide, lhs, rhs),
+        SExpression::Modulo(ref lhs, ref rhs) => (Operator::Modulo, lhs, rhs),
+        SExpression::Equals(ref lhs, ref rhs) => (Operator::Equals, lhs, rhs),
+        SExpression::NotEquals(ref lhs, ref rhs) => (Operator::NotEquals, lhs, rhs),
+        SExpression::GreaterThan(ref lhs, ref rhs) => (Operator::GreaterThan, lhs, rhs),
+        SExpression::LessThan(ref lhs, ref rhs) => (Operator::LessThan, lhs, rhs),
+        SExpression::GreaterThanOrEqual(ref lhs, ref rhs) => (Operator::GreaterThanOrEqual, lhs, rhs),
+        SExpression::LessThanOrEqual(ref lhs, ref rhs) => (Operator::LessThanOrEqual, lhs, rhs),
+        SExpression::LogicalAnd(ref lhs, ref rhs) => (Operator::LogicalAnd, lhs, rhs),
+        SExpression::LogicalOr(ref lhs, ref rhs) => (Operator::LogicalOr, lhs, rhs),
+        SExpression::Power(ref lhs, ref rhs) => (Operator::Power, lhs, rhs),
+        SExpression::BitwiseAnd(ref lhs, ref rhs) => (Operator::BitwiseAnd, lhs, rhs),
+        SExpression::BitwiseOr(ref lhs, ref rhs) => (Operator::BitwiseOr, lhs, rhs),
+        SExpression::BitwiseXor(ref lhs, ref rhs) => (Operator::BitwiseXor, lhs, rhs),
+        SExpression::ShiftLeft(ref lhs, ref rhs) => (Operator::ShiftLeft, lhs, rhs),
+        SExpression::ShiftRight(ref lhs, ref rhs) => (Operator::ShiftRight, lhs, rhs),
+        _ => return None,
+    };
+    Some((operator, lhs.as_ref(), rhs.as_ref()))
+}
+
+/// How tightly the expression binds, using the same values as the operators, where lower values bind more tightly.
+fn precedence_of(ast: &Ast) -> u32 {
+    match *ast {
+        Ast::Literal(Datatype::Number(value)) if value < 0 => UNARY_PRECEDENCE, // Written with a `-`.
+        Ast::Literal(Datatype::Float(value)) if value.is_sign_negative() => UNARY_PRECEDENCE,
+        Ast::Literal(_) | Ast::ValueIdentifier(_) | Ast::Type(_) => ATOM_PRECEDENCE,
+        Ast::SExpr(ref sexpr) => {
+            if let Some((operator, _, _)) = binary_operation(sexpr) {
+                return operator.into();
+            }
+            match *sexpr {
+                SExpression::Invert(_) | SExpression::Negate(_) | SExpression::BitwiseNot(_) => UNARY_PRECEDENCE,
+                SExpression::Assignment { .. } => Operator::Assignment.into(),
+                SExpression::Increment(_) | SExpression::Decrement(_) |
+                SExpression::AccessArray { .. } | SExpression::AccessStructField { .. } | SExpression::ExecuteFn { .. } |
+                SExpression::CreateStruct { .. } | SExpression::CreateArray(_) | SExpression::Range { .. } => ATOM_PRECEDENCE,
+                _ => STATEMENT_PRECEDENCE,
+            }
+        }
+        _ => STATEMENT_PRECEDENCE,
+    }
+}
+
+fn is_postfix(ast: &Ast) -> bool {
+    matches!(
+        *ast,
+        Ast::SExpr(SExpression::Increment(_)) | Ast::SExpr(SExpression::Decrement(_)) |
+        Ast::SExpr(SExpression::AccessArray { .. }) | Ast::SExpr(SExpression::AccessStructField { .. }) |
+        Ast::SExpr(SExpression::ExecuteFn { .. })
+    )
+}
+
+fn is_identifier(ast: &Ast) -> bool {
+    matches!(*ast, Ast::ValueIdentifier(_))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interpreter::parse;
+    use test_runner;
+    use std::path::PathBuf;
+
+    /// Formats the source, and checks that the formatted source parses to the same AST.
+    fn round_trip(source: &str) -> String {
+        let ast: Ast = parse(source).unwrap();
+        let formatted: String = format_program(&ast).unwrap();
+        assert!(same_program(&ast, &parse(&formatted).unwrap()), "Formatted as:\n{}", formatted);
+        formatted
+    }
+
+    #[test]
+    fn formats_declarations() {
+        let source: &str = "\
+struct Point {
+    x: Number
+    y: Number
+}
+
+fn add(a: Number, b: Number) -> Number {
+    a + b
+}
+
+let origin: Point := new Point { x: 0 y: 0 }
+const names := [\"a\", \"b\"]
+let empty := new Point {}
+";
+        assert_eq!(source, round_trip(source));
+    }
+
+    #[test]
+    fn formats_control_flow() {
+        let source: &str = "\
+fn f(x: Number) -> Number {
+    let total := 0
+    while total < x {
+        if total % 2 == 0 {
+            total += 3
+        } else {
+            total++
+        }
+    }
+    try {
+        total / 0
+    } catch e {
+        println(e)
+    }
+    total
+}
+";
+        assert_eq!(source, round_trip(source));
+    }
+
+    #[test]
+    fn only_necessary_parentheses_are_kept() {
+        assert_eq!("(1 + 2) * 3\n", round_trip("((1 + 2) * (3))"));
+        assert_eq!("1 - (2 - 3) - 4\n", round_trip("1-(2-3)-4"));
+        assert_eq!("(2 ** 3) ** 2 ** 1\n", round_trip("(2**3)**(2**1)"));
+        assert_eq!("-(a[0]) + (-a)[0] + -a\n", round_trip("-(a[0]) + (-a)[0] + (-a)"));
+        assert_eq!("!(a == b) && f(x, y).z\n", round_trip("!(a==b)&&f(x,y).z"));
+        assert_eq!("a & 1 == 1\n", round_trip("(a & 1) == 1"));
+        assert_eq!("x := y := 3\n", round_trip("x := (y := 3)"));
+    }
+
+    #[test]
+    fn formats_literals() {
+        assert_eq!("[1.5, 2.0]\n", round_trip("[1.5, 2.0]"));
+        assert_eq!("[[1], []]\n", round_trip("[[1],[]]"));
+        assert_eq!("[0..n + 1]\n", round_trip("[0..n+1]"));
+        assert_eq!("include \"lib.hlw\"\n", round_trip("include \"lib.hlw\""));
+        assert_eq!("-3\n", format_program(&Ast::Literal(Datatype::Number(-3))).unwrap());
+        assert_eq!("\"a\\nb\"\n", format_program(&Ast::Literal(Datatype::String("a\nb".to_string()))).unwrap());
+        assert_eq!(
+            Err(FormatError::UnrepresentableString("\"".to_string())),
+            format_program(&Ast::Literal(Datatype::String("\"".to_string())))
+        );
+    }
+
+    #[test]
+    fn formats_for_loops() {
+        let source: &str = "\
+for x in xs {
+    println(x)
+}
+for i in [0..3] {
+    i
+}
+";
+        assert_eq!(source, round_trip(source));
+    }
+
+    #[test]
+    fn formats_the_examples() {
+        let files: Vec<PathBuf> = test_runner::source_files(Path::new("examples")).unwrap();
+        assert!(!files.is_empty());
+        for file in files {
+            let source: String = fs::read_to_string(&file).unwrap();
+            let formatted: String = format_source(&source).unwrap();
+            assert!(same_program(&parse(&source).unwrap(), &parse(&formatted).unwrap()), "{} changed", file.display());
+            // Formatting again doesn't change anything.
+            assert_eq!(formatted, format_source(&formatted).unwrap());
+        }
+    }
+
+    #[test]
+    fn formats_untidy_source() {
+        let source: &str = "let a:=3\nfn   f( s: Number )->Number{\ns*2}\nf( a )";
+        assert_eq!(Ok("let a := 3\n\nfn f(s: Number) -> Number {\n    s * 2\n}\n\nf(a)\n".to_string()), format_source(source));
+    }
+
+    #[test]
+    fn source_that_only_partly_parses_isnt_formatted() {
+        assert_eq!(
+            Err(FormatError::InvalidSyntax("Couldn't parse from: ) + 2".to_string())),
+            format_source("let a := 1\n) + 2\nlet b := 3")
+        );
+    }
+
+    #[test]
+    fn generated_names_are_ignored() {
+        assert!(same_program(&parse("for x in xs { x }").unwrap(), &parse("for x in xs { x }").unwrap()));
+        assert!(!same_program(&parse("for x in xs { x }").unwrap(), &parse("for y in xs { y }").unwrap()));
+        assert_eq!(
+            "a(#0, #1, #0, 12)",
+            hide_generated_names("a(67e55044-10b1-426f-9247-bb680e5fe0c8, 00000000-10b1-426f-9247-bb680e5fe0c8, 67e55044-10b1-426f-9247-bb680e5fe0c8, 12)")
+        );
+    }
+}
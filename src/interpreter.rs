@@ -0,0 +1,573 @@
+use ast::{Ast, SExpression, Datatype, RcDatatype, TypeInfo, VariableStore, LangResult, LangError};
+use ast::mutability::{MutabilityMap, Mutability};
+use ast::type_checking::{self, TypeStore};
+use parser::{self, program};
+use preprocessor::preprocess;
+use std_functions::{self, NativeFunction};
+use io_context::{self, IoContext, FileSystemAccess};
+use debugger::{self, Debugger};
+use session;
+use nom::IResult;
+
+use std::io::prelude::*;
+use std::fs::{self, File};
+use std::rc::Rc;
+
+
+/// Holds everything needed to run HLWNPA programs: the variables, and the stores used by the
+/// mutability and type checkers.
+///
+/// State persists between calls, so a function declared by one call to `eval_str` can be called by the next.
+pub struct Interpreter {
+    map: VariableStore,
+    mutability_map: MutabilityMap,
+    type_store: TypeStore,
+    io_context: IoContext,
+    /// The source of the structs and functions that were declared, so sessions can be saved.
+    declarations: session::Declarations,
+    debugger: Option<Debugger>,
+}
+
+/// The globals of an interpreter at some point, taken by `Interpreter::snapshot`.
+/// Values are shared until they are reassigned, so taking a snapshot doesn't copy them.
+pub(crate) struct Snapshot {
+    map: VariableStore,
+    mutability_map: MutabilityMap,
+    type_store: TypeStore,
+    declarations: session::Declarations,
+}
+
+impl Interpreter {
+    /// Creates an interpreter with the standard functions already added.
+    pub fn new() -> Interpreter {
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        std_functions::add_std_functions(&mut map);
+        std_functions::add_std_function_types(&mut type_store);
+        Interpreter {
+            map,
+            mutability_map: MutabilityMap::new(),
+            type_store,
+            io_context: IoContext::standard(),
+            declarations: session::Declarations::new(),
+            debugger: None,
+        }
+    }
+
+    /// Forgets everything programs have declared, leaving only the standard functions.
+    /// The streams, file system access and debugger are kept, but native functions have to be added again.
+    pub fn reset(&mut self) {
+        let io_context: IoContext = self.io_context.clone();
+        let debugger: Option<Debugger> = self.debugger.take();
+        *self = Interpreter::new();
+        self.io_context = io_context;
+        self.debugger = debugger;
+    }
+
+    /// Copies the globals and what the checkers know about them, so `restore_snapshot` can undo whatever is evaluated afterwards.
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            map: self.map.clone(),
+            mutability_map: self.mutability_map.clone(),
+            type_store: self.type_store.clone(),
+            declarations: self.declarations.clone(),
+        }
+    }
+
+    /// Puts back the globals from when the snapshot was taken.
+    pub(crate) fn restore_snapshot(&mut self, snapshot: &Snapshot) {
+        self.map = snapshot.map.clone();
+        self.mutability_map = snapshot.mutability_map.clone();
+        self.type_store = snapshot.type_store.clone();
+        self.declarations = snapshot.declarations.clone();
+    }
+
+    /// Sets the streams that programs write to, which are the process's stdout and stderr by default.
+    pub fn set_io_context(&mut self, io_context: IoContext) {
+        self.io_context = io_context;
+    }
+
+    /// Sets which files programs can use with the file builtins. Programs can't use any files by default.
+    pub fn set_file_system_access(&mut self, file_system: FileSystemAccess) {
+        self.io_context.file_system = file_system;
+    }
+
+    /// Pauses the files that are evaluated before each of their statements, and at breakpoints,
+    /// for the debugger to step through them and inspect their variables.
+    pub fn set_debugger(&mut self, debugger: Debugger) {
+        self.debugger = Some(debugger);
+    }
+
+    /// Checks and evaluates the source, returning the value of the last expression.
+    pub fn eval_str(&mut self, source: &str) -> LangResult {
+        let ast: Ast = parse(source)?;
+        self.check(&ast)?;
+        let value: RcDatatype = self.evaluate(&ast)?;
+        session::record_declarations(source, &self.map, &mut self.declarations);
+        Ok(value)
+    }
+
+    /// Reads the file and evaluates it like `eval_str`, with the functions and structs hoisted to the top.
+    /// If the file declares a `main()` function, it is called after the rest of the file is evaluated,
+    /// and its value is returned.
+    pub fn eval_file(&mut self, filename: &str) -> LangResult {
+        self.eval_file_with_arguments(filename, &[]).map(|(value, _)| value)
+    }
+
+    /// Evaluates the file like `eval_file`, passing the arguments to `main(args: [String])`.
+    /// Along with the value, reports whether the file declared `main()`, so the value is what `main` returned.
+    pub fn eval_file_with_arguments(&mut self, filename: &str, arguments: &[String]) -> Result<(RcDatatype, bool), LangError> {
+        let (ast, value) = self.evaluate_file(filename)?;
+        if ast.main_fn_exists() {
+            let map: &mut VariableStore = &mut self.map;
+            let debugger: &mut Option<Debugger> = &mut self.debugger;
+            let value: RcDatatype = io_context::with_context(&self.io_context, || {
+                debugger::with_debugger(debugger, || ast.execute_main(map, arguments))
+            })?;
+            Ok((value, true))
+        } else {
+            Ok((value, false))
+        }
+    }
+
+    /// Evaluates the file without calling `main()`, so its functions, structs and globals can be used afterwards.
+    pub fn load_file(&mut self, filename: &str) -> LangResult {
+        let (_, value) = self.evaluate_file(filename)?;
+        Ok(value)
+    }
+
+    /// Writes the structs, functions and variables that have been declared to the file,
+    /// as source that `restore_session` can evaluate to declare them again.
+    /// Returns the names of the globals that couldn't be written, like native functions.
+    pub fn save_session(&self, filename: &str) -> Result<Vec<String>, LangError> {
+        let (source, unsaved) = session::session_source(&self.map, &self.type_store, &self.mutability_map, &self.declarations);
+        fs::write(filename, source).map_err(|e| LangError::CouldNotWriteFile {
+            filename: filename.to_string(),
+            reason: e.to_string(),
+        })?;
+        Ok(unsaved)
+    }
+
+    /// Replaces everything that has been declared with the declarations in a file written by `save_session`.
+    /// If the file can't be loaded, the current declarations are kept.
+    /// Like `reset`, the streams, file system access and debugger are kept. Native functions can't be saved,
+    /// so they are kept as well, instead of having to be added again.
+    pub fn restore_session(&mut self, filename: &str) -> LangResult {
+        let mut restored: Interpreter = Interpreter::new();
+        restored.io_context = self.io_context.clone();
+        for value in self.map.values() {
+            if let Datatype::NativeFunction(ref function) = **value {
+                restored.add_native_function(function.clone());
+            }
+        }
+        restored.debugger = self.debugger.take();
+        match restored.load_file(filename) {
+            Ok(value) => {
+                *self = restored;
+                Ok(value)
+            }
+            Err(e) => {
+                self.debugger = restored.debugger.take();
+                Err(e)
+            }
+        }
+    }
+
+    fn evaluate_file(&mut self, filename: &str) -> Result<(Ast, RcDatatype), LangError> {
+        let mut file_contents: String = String::new();
+        let read_result = File::open(filename).and_then(|mut file| file.read_to_string(&mut file_contents));
+        if let Err(e) = read_result {
+            return Err(LangError::CouldNotReadFile {
+                filename: filename.to_string(),
+                reason: e.to_string(),
+            });
+        }
+        // The debugger needs to know which line each statement is on.
+        let parsed: Result<Ast, LangError> = match self.debugger {
+            Some(_) => parse_located(&file_contents),
+            None => parse(&file_contents),
+        };
+        let ast: Ast = match parsed {
+            Ok(ast) => ast.hoist_functions_and_structs(),
+            Err(LangError::InvalidSyntax(reason)) => {
+                return Err(LangError::CouldNotParseFile {
+                    filename: filename.to_string(),
+                    reason,
+                })
+            }
+            Err(e) => return Err(e),
+        };
+        self.check(&ast)?;
+        if let Some(ref mut debugger) = self.debugger {
+            debugger.load_source(&file_contents, &ast);
+        }
+        let value: RcDatatype = self.evaluate(&ast)?;
+        session::record_declarations(&file_contents, &self.map, &mut self.declarations);
+        Ok((ast, value))
+    }
+
+    /// Determines the type the source would produce, without evaluating it or keeping its declarations.
+    pub fn type_of(&self, source: &str) -> Result<TypeInfo, LangError> {
+        let ast: Ast = parse(source)?;
+        let mut type_store: TypeStore = self.type_store.clone();
+        ast.check_types(&mut type_store)
+            .map(type_checking::Mutability::get_type)
+            .map_err(LangError::NewTypeError)
+    }
+
+    /// Calls a function that was declared by an evaluated program, or added to the interpreter, by name.
+    /// The arguments are type checked against the function's parameters before it is called.
+    pub fn call_function(&mut self, name: &str, arguments: Vec<Datatype>) -> LangResult {
+        let ast: Ast = Ast::SExpr(SExpression::ExecuteFn {
+            identifier: Box::new(Ast::ValueIdentifier(name.to_string())),
+            parameters: Box::new(Ast::ExpressionList(arguments.into_iter().map(Ast::Literal).collect())),
+        });
+        self.check(&ast)?;
+        self.evaluate(&ast)
+    }
+
+    /// Gets the value of a global variable, function, or struct type.
+    pub fn get_global(&self, name: &str) -> Option<RcDatatype> {
+        self.map.get(name).cloned()
+    }
+
+    /// The type the type checker has recorded for a global.
+    pub fn global_type(&self, name: &str) -> Option<TypeInfo> {
+        self.type_store.get(name).cloned().map(type_checking::Mutability::get_type)
+    }
+
+    /// Whether the mutability checker allows a global to be reassigned.
+    pub fn mutability(&self, name: &str) -> Option<Mutability> {
+        self.mutability_map.get(name).cloned()
+    }
+
+    /// The names of every global variable, function, and struct type, in alphabetical order.
+    pub fn global_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.map.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Sets a global variable, declaring it if it doesn't exist yet.
+    /// The variable is mutable, and replaces any existing global with the same name, even a constant.
+    pub fn set_global(&mut self, name: &str, value: Datatype) {
+        self.type_store.insert(name.to_string(), type_checking::Mutability::Mutable(TypeInfo::from(value.clone())));
+        self.mutability_map.insert(name.to_string(), Mutability::Mutable);
+        self.map.insert(name.to_string(), Rc::new(value));
+    }
+
+    /// Makes a function implemented in Rust callable from programs.
+    /// Like other functions, programs can't reassign it.
+    pub fn add_native_function(&mut self, function: NativeFunction) {
+        self.mutability_map.insert(function.name.clone(), Mutability::Immutable);
+        std_functions::add_native_function(&mut self.map, &mut self.type_store, function);
+    }
+
+    /// Runs the mutability and type checkers over the AST, recording the declarations it makes.
+    ///
+    /// The mutability checker gives every expression list a new scope,
+    /// so the top level expressions are checked individually, to keep their declarations for later calls.
+    fn check(&mut self, ast: &Ast) -> Result<(), LangError> {
+        let top_level_expressions: Vec<&Ast> = match *ast {
+            Ast::ExpressionList(ref expressions) => expressions.iter().collect(),
+            ref other => vec![other],
+        };
+        for expression in top_level_expressions {
+            expression.check_mutability_semantics(&mut self.mutability_map).map_err(LangError::MutabilityRulesViolated)?;
+        }
+        ast.check_types(&mut self.type_store).map_err(LangError::NewTypeError)?;
+        Ok(())
+    }
+
+    /// Evaluates the AST with the interpreter's streams and debugger installed.
+    fn evaluate(&mut self, ast: &Ast) -> LangResult {
+        let map: &mut VariableStore = &mut self.map;
+        let debugger: &mut Option<Debugger> = &mut self.debugger;
+        io_context::with_context(&self.io_context, || debugger::with_debugger(debugger, || ast.evaluate(map)))
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+/// Preprocesses and parses the source.
+pub fn parse(source: &str) -> Result<Ast, LangError> {
+    if source.trim().is_empty() {
+        return Ok(Ast::Literal(Datatype::None));
+    }
+    let preprocessed: String = preprocess(source);
+    parse_preprocessed(&preprocessed)
+}
+
+/// Like `parse`, but each statement that starts a line is wrapped in an `Ast::Located` holding the line,
+/// for the debugger and linter.
+pub(crate) fn parse_located(source: &str) -> Result<Ast, LangError> {
+    if source.trim().is_empty() {
+        return Ok(Ast::Literal(Datatype::None));
+    }
+    let preprocessed: String = preprocess(source);
+    parser::with_lines(source, &preprocessed, || parse_preprocessed(&preprocessed))
+}
+
+fn parse_preprocessed(preprocessed: &str) -> Result<Ast, LangError> {
+    match program(preprocessed.as_bytes()) {
+        IResult::Done(rest, ast) => {
+            // The parser stops at the first statement it can't parse, rather than failing.
+            let rest: String = String::from_utf8_lossy(rest).to_string();
+            match rest.trim().lines().next() {
+                Some(line) => Err(LangError::InvalidSyntax(format!("Couldn't parse from: {}", line))),
+                None => Ok(ast),
+            }
+        }
+        IResult::Error(e) => Err(LangError::InvalidSyntax(e.to_string())),
+        IResult::Incomplete(i) => Err(LangError::InvalidSyntax(format!("Parser returned incomplete: {:?}", i))),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ast::type_checking::TypeError;
+    use ast::mutability::MutabilityError;
+    use std::convert::TryFrom;
+    use std::cell::RefCell;
+    use std::io;
+    use std::env;
+
+    #[test]
+    fn declarations_persist_between_evaluations() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("fn add_one(x: Number) -> Number { x + 1 }").unwrap();
+        interpreter.eval_str("let a := 4").unwrap();
+        assert_eq!(Datatype::Number(5), *interpreter.eval_str("add_one(a)").unwrap());
+    }
+
+    #[test]
+    fn checker_errors_are_returned() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("const a := 4").unwrap();
+        assert_eq!(
+            LangError::MutabilityRulesViolated(MutabilityError::CanNotAssignToConstVariable),
+            interpreter.eval_str("a := 5").unwrap_err()
+        );
+        assert_eq!(
+            LangError::NewTypeError(TypeError::IdentifierDoesntExist("missing".to_string())),
+            interpreter.eval_str("missing(4)").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn elements_and_fields_cant_be_modified_in_place() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("let a := [1, 2]").unwrap();
+        interpreter.eval_str("struct P { x: Number }").unwrap();
+        interpreter.eval_str("let p := new P { x: 1 }").unwrap();
+        for source in &["a[0]++", "a[0]--", "a[0] += 5", "p.x++", "p.x += 2"] {
+            assert_eq!(
+                LangError::MutabilityRulesViolated(MutabilityError::IsNotAVariable),
+                interpreter.eval_str(source).unwrap_err(),
+                "{}", source
+            );
+        }
+        assert_eq!(Datatype::from(vec![1, 2]), *interpreter.eval_str("a").unwrap());
+    }
+
+    #[test]
+    fn decrement_isnt_read_as_subtracting_the_next_line() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(Datatype::Number(2), *interpreter.eval_str("let b := 3\nb--\nb").unwrap());
+    }
+
+    #[test]
+    fn call_function_by_name() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("fn greet(name: String) -> String { \"Hello \" + name }").unwrap();
+        let greeting = interpreter.call_function("greet", vec![Datatype::from("World")]).unwrap();
+        assert_eq!(Ok("Hello World".to_string()), String::try_from(greeting.as_ref()));
+        assert_eq!(
+            LangError::NewTypeError(TypeError::TypeMismatch(TypeInfo::Number, TypeInfo::String)),
+            interpreter.call_function("greet", vec![Datatype::from(3)]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn globals_are_shared_with_programs() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("limit", Datatype::from(10));
+        interpreter.eval_str("limit := limit * 2\nlet doubled := limit").unwrap();
+        assert_eq!(Some(Rc::new(Datatype::Number(20))), interpreter.get_global("doubled"));
+        assert_eq!(None, interpreter.get_global("missing"));
+    }
+
+    #[test]
+    fn native_functions_are_callable() {
+        let mut interpreter = Interpreter::new();
+        interpreter.add_native_function(NativeFunction::new("answer", vec![], TypeInfo::Number, |_| Ok(Rc::new(Datatype::from(42)))));
+        assert_eq!(Datatype::Number(43), *interpreter.eval_str("answer() + 1").unwrap());
+        assert!(interpreter.eval_str("answer := 3").is_err());
+    }
+
+    #[test]
+    fn eval_file_calls_main() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(Datatype::Number(10), *interpreter.eval_file("examples/hoisted_test.hlw").unwrap());
+        assert_eq!(Datatype::Number(3), *interpreter.call_function("add_two_to_value", vec![Datatype::from(1)]).unwrap());
+        match interpreter.eval_file("examples/does_not_exist.hlw") {
+            Err(LangError::CouldNotReadFile { .. }) => {}
+            other => panic!("Expected the file to not be found, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn main_receives_arguments() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_io_context(IoContext {
+            stdin: Rc::new(RefCell::new(io::empty())),
+            stdout: Rc::new(RefCell::new(io::sink())),
+            stderr: Rc::new(RefCell::new(io::sink())),
+            file_system: FileSystemAccess::Denied,
+        });
+        let arguments: Vec<String> = vec!["first".to_string(), "second".to_string()];
+        let (value, main_called) = interpreter.eval_file_with_arguments("examples/args_test.hlw", &arguments).unwrap();
+        assert_eq!(Datatype::Number(2), *value);
+        assert!(main_called);
+    }
+
+    #[test]
+    fn a_global_named_main_isnt_called() {
+        let path = env::temp_dir().join("hlwnpa_main_global.hlw");
+        fs::write(&path, "let main := 7\nmain").unwrap();
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.eval_file_with_arguments(path.to_str().unwrap(), &[]);
+        fs::remove_file(&path).unwrap();
+        let (value, main_called) = result.unwrap();
+        assert_eq!(Datatype::Number(7), *value);
+        assert!(!main_called);
+    }
+
+    #[test]
+    fn main_with_unsupported_parameters_is_rejected() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("fn main(count: Number) -> Number { count }").unwrap();
+        let main_ast = parse("main").unwrap();
+        assert_eq!(
+            LangError::InvalidMainSignature(TypeInfo::Function {
+                parameters: vec![TypeInfo::Number],
+                return_type: Box::new(TypeInfo::Number),
+            }),
+            main_ast.execute_main(&mut interpreter.map, &[]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn state_can_be_inspected() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("const limit := 4\nlet names := [\"a\"]").unwrap();
+        assert_eq!(Some(TypeInfo::Array(Box::new(TypeInfo::String))), interpreter.global_type("names"));
+        assert_eq!(Some(::ast::Mutability::Immutable), interpreter.mutability("limit")); // The name embedders use isn't ambiguous.
+        assert_eq!(Some(Mutability::Mutable), interpreter.mutability("names"));
+        assert_eq!(Ok(TypeInfo::Float), interpreter.type_of("let scaled := to_float(limit) * 1.5\nscaled"));
+        assert_eq!(None, interpreter.global_type("scaled")); // type_of doesn't keep declarations.
+    }
+
+    #[test]
+    fn reset_forgets_declarations() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("const limit := 4").unwrap();
+        interpreter.reset();
+        assert_eq!(None, interpreter.get_global("limit"));
+        assert_eq!(None, interpreter.mutability("limit"));
+        assert_eq!(Datatype::Number(5), *interpreter.eval_str("let limit := 5\nlimit").unwrap());
+    }
+
+    #[test]
+    fn sessions_can_be_saved_and_restored() {
+        let filename = env::temp_dir().join(format!("hlwnpa_session_test_{}.hlw", ::std::process::id()));
+        let filename: &str = filename.to_str().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("\
+struct Point { x: Number y: Number }
+fn sum(points: [Point]) -> Number {
+    let total := 0
+    for p in points {
+        total += p.x + p.y
+    }
+    total
+}
+const points := [new Point { x: 1 y: 2 }, new Point { x: 3 y: -4 }]
+let empty := []
+let names: [String] := []
+let ratio := 0.5
+let backslash := \"a \\\\ b\"").unwrap();
+        interpreter.add_native_function(NativeFunction::new("double", vec![TypeInfo::Number], TypeInfo::Number, |_| Ok(Rc::new(Datatype::Number(0)))));
+        assert_eq!(vec!["backslash".to_string(), "double".to_string()], interpreter.save_session(filename).unwrap());
+
+        let mut restored = Interpreter::new();
+        restored.add_native_function(NativeFunction::new("triple", vec![TypeInfo::Number], TypeInfo::Number, |arguments| {
+            match *arguments[0] {
+                Datatype::Number(n) => Ok(Rc::new(Datatype::Number(n * 3))),
+                _ => Err(LangError::ExecuteNonFunction),
+            }
+        }));
+        restored.set_debugger(Debugger::new(Rc::new(RefCell::new(io::empty())), Rc::new(RefCell::new(io::sink()))));
+        restored.restore_session(filename).unwrap();
+        fs::remove_file(filename).unwrap();
+        assert_eq!(Datatype::Number(2), *restored.eval_str("sum(points)").unwrap());
+        assert_eq!(Datatype::Number(6), *restored.eval_str("triple(2)").unwrap());
+        assert!(restored.debugger.is_some());
+        assert_eq!(Some(Mutability::Immutable), restored.mutability("points"));
+        assert_eq!(Some(Mutability::Mutable), restored.mutability("ratio"));
+        assert_eq!(Some(TypeInfo::Array(Box::new(TypeInfo::String))), restored.global_type("names"));
+        assert_eq!(interpreter.global_type("points"), restored.global_type("points"));
+        assert_eq!(Datatype::Float(0.5), *restored.eval_str("ratio").unwrap());
+        assert_eq!(None, restored.get_global("backslash"));
+    }
+
+    #[test]
+    fn sessions_keep_declarations_as_they_were_written() {
+        let filename = env::temp_dir().join(format!("hlwnpa_session_source_test_{}.hlw", ::std::process::id()));
+        let filename: &str = filename.to_str().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("fn greeting() -> String {\n    \"hello\\n\"\n}\nlet count := -3").unwrap();
+        interpreter.eval_str("fn answer() -> Number { 42 }").unwrap();
+        interpreter.set_global("answer", Datatype::Number(42));
+        assert_eq!(Vec::<String>::new(), interpreter.save_session(filename).unwrap());
+        let source: String = fs::read_to_string(filename).unwrap();
+        fs::remove_file(filename).unwrap();
+        assert_eq!("fn greeting() -> String {\n    \"hello\\n\"\n}\nlet answer := 42\nlet count := -3\n", source);
+    }
+
+    #[test]
+    fn failed_restores_keep_the_session() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("const limit := 4").unwrap();
+        match interpreter.restore_session("this_file_does_not_exist.hlw") {
+            Err(LangError::CouldNotReadFile { .. }) => {}
+            other => panic!("Expected the file to be unreadable, got: {:?}", other),
+        }
+        assert_eq!(Datatype::Number(4), *interpreter.eval_str("limit").unwrap());
+    }
+
+    #[test]
+    fn syntax_errors_are_returned() {
+        let mut interpreter = Interpreter::new();
+        match interpreter.eval_str("}") {
+            Err(LangError::InvalidSyntax(_)) => {}
+            other => panic!("Expected a syntax error, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn statements_after_a_syntax_error_are_not_ignored() {
+        let mut interpreter = Interpreter::new();
+        match interpreter.eval_str("let a := 4\na (((") {
+            Err(LangError::InvalidSyntax(ref reason)) => assert_eq!("Couldn't parse from: (((", reason),
+            other => panic!("Expected a syntax error, got: {:?}", other),
+        }
+        assert_eq!(None, interpreter.get_global("a"));
+    }
+}
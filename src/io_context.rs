@@ -0,0 +1,312 @@
+use ast::LangError;
+
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// A destination for program output that can be shared between the interpreter and its host.
+pub type Sink = Rc<RefCell<dyn Write>>;
+/// Where programs read their input from, which can be shared between the interpreter and its host.
+pub type Source = Rc<RefCell<dyn BufRead>>;
+
+/// Which files programs are allowed to read and write with the file builtins.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileSystemAccess {
+    Denied,
+    Unrestricted,
+    /// Only files inside the directory, or its subdirectories, can be used.
+    Within(PathBuf),
+}
+
+/// The streams programs read from and write to, and the files they are allowed to use.
+///
+/// `Ast::evaluate` only has access to the variables, so the context used while evaluating
+/// is held per thread, and the interpreter installs its own for the duration of each evaluation.
+#[derive(Clone)]
+pub struct IoContext {
+    pub stdin: Source,
+    pub stdout: Sink,
+    pub stderr: Sink,
+    pub file_system: FileSystemAccess,
+}
+
+impl IoContext {
+    /// Reads from the process's stdin, and writes to its stdout and stderr.
+    /// Files can't be accessed, so untrusted programs are sandboxed unless the host allows it.
+    pub fn standard() -> IoContext {
+        IoContext {
+            // Stdin is already buffered, a capacity of 1 prevents this reader from taking input
+            // that would otherwise be read by the REPL.
+            stdin: Rc::new(RefCell::new(BufReader::with_capacity(1, io::stdin()))),
+            stdout: Rc::new(RefCell::new(io::stdout())),
+            stderr: Rc::new(RefCell::new(io::stderr())),
+            file_system: FileSystemAccess::Denied,
+        }
+    }
+}
+
+impl Default for IoContext {
+    fn default() -> IoContext {
+        IoContext::standard()
+    }
+}
+
+thread_local! {
+    static CURRENT_CONTEXT: RefCell<IoContext> = RefCell::new(IoContext::standard());
+}
+
+/// Runs the function with the context installed, restoring the previous context afterwards.
+pub fn with_context<F, R>(context: &IoContext, function: F) -> R
+    where F: FnOnce() -> R
+{
+    let previous: IoContext = CURRENT_CONTEXT.with(|current| current.replace(context.clone()));
+    let _restore = RestoreContext(Some(previous));
+    function()
+}
+
+/// Puts the previous context back when it is dropped, so it is restored even if the function panics,
+/// like a native function supplied by the host might.
+struct RestoreContext(Option<IoContext>);
+
+impl Drop for RestoreContext {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            // The thread local may already be gone if this is dropped while the thread exits.
+            let _ = CURRENT_CONTEXT.try_with(|current| *current.borrow_mut() = previous);
+        }
+    }
+}
+
+pub fn write_stdout(text: &str) -> Result<(), LangError> {
+    let stdout: Sink = CURRENT_CONTEXT.with(|current| current.borrow().stdout.clone());
+    write(&stdout, text)
+}
+
+pub fn write_stderr(text: &str) -> Result<(), LangError> {
+    let stderr: Sink = CURRENT_CONTEXT.with(|current| current.borrow().stderr.clone());
+    write(&stderr, text)
+}
+
+/// Reads the next line without its line ending, producing an empty string once the input is exhausted.
+pub fn read_line() -> Result<String, LangError> {
+    flush_stdout()?;
+    let stdin: Source = CURRENT_CONTEXT.with(|current| current.borrow().stdin.clone());
+    let mut line: String = String::new();
+    stdin.borrow_mut()
+        .read_line(&mut line)
+        .map_err(|e| LangError::CouldNotReadInput(e.to_string()))?;
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(line)
+}
+
+/// Reads everything left in the input.
+pub fn read_all() -> Result<String, LangError> {
+    flush_stdout()?;
+    let stdin: Source = CURRENT_CONTEXT.with(|current| current.borrow().stdin.clone());
+    let mut input: String = String::new();
+    stdin.borrow_mut()
+        .read_to_string(&mut input)
+        .map_err(|e| LangError::CouldNotReadInput(e.to_string()))?;
+    Ok(input)
+}
+
+/// Makes sure a prompt written with `print` is shown before the program waits for input.
+fn flush_stdout() -> Result<(), LangError> {
+    let stdout: Sink = CURRENT_CONTEXT.with(|current| current.borrow().stdout.clone());
+    let result = stdout.borrow_mut().flush();
+    result.map_err(|e| LangError::CouldNotWriteOutput(e.to_string()))
+}
+
+/// Checks that the program may use the file or directory at the path, producing the path to use.
+/// The error describes why access was refused.
+pub fn allowed_path(path: &str) -> Result<PathBuf, String> {
+    let access: FileSystemAccess = CURRENT_CONTEXT.with(|current| current.borrow().file_system.clone());
+    match access {
+        FileSystemAccess::Denied => Err(format!("File system access is not allowed, so \"{}\" can't be used", path)),
+        FileSystemAccess::Unrestricted => Ok(PathBuf::from(path)),
+        FileSystemAccess::Within(directory) => {
+            let directory: PathBuf = directory.canonicalize().map_err(|e| e.to_string())?;
+            let resolved: PathBuf = resolve(Path::new(path)).map_err(|e| e.to_string())?;
+            if resolved.starts_with(&directory) {
+                Ok(resolved)
+            } else {
+                Err(format!("\"{}\" is outside of the allowed directory \"{}\"", path, directory.display()))
+            }
+        }
+    }
+}
+
+/// Produces the absolute path without any symbolic links or `..`.
+/// Files that don't exist yet are resolved through their parent directory, so they can be created.
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+    resolve_links(path, 0)
+}
+
+/// How many symbolic links can be followed before giving up, like the operating system's limit.
+const MAX_SYMBOLIC_LINKS: usize = 40;
+
+/// Resolves the path, following links that point to files that don't exist yet,
+/// as writing to such a link creates the file it points to.
+fn resolve_links(path: &Path, followed_links: usize) -> io::Result<PathBuf> {
+    if path.exists() {
+        return path.canonicalize();
+    }
+    let parent: &Path = match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+    let is_link: bool = fs::symlink_metadata(path).map(|metadata| metadata.file_type().is_symlink()).unwrap_or(false);
+    if is_link {
+        if followed_links == MAX_SYMBOLIC_LINKS {
+            return Err(io::Error::other("Too many levels of symbolic links"));
+        }
+        // Relative targets are relative to the directory holding the link.
+        return resolve_links(&parent.join(fs::read_link(path)?), followed_links + 1);
+    }
+    match path.file_name() {
+        Some(file_name) => Ok(parent.canonicalize()?.join(file_name)),
+        None => Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory")),
+    }
+}
+
+pub(crate) fn write(sink: &Sink, text: &str) -> Result<(), LangError> {
+    sink.borrow_mut()
+        .write_all(text.as_bytes())
+        .map_err(|e| LangError::CouldNotWriteOutput(e.to_string()))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::panic;
+
+    #[test]
+    fn output_goes_to_the_installed_context() {
+        let stdout = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let stderr = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let context = IoContext {
+            stdin: Rc::new(RefCell::new(io::empty())),
+            stdout: stdout.clone(),
+            stderr: stderr.clone(),
+            file_system: FileSystemAccess::Denied,
+        };
+        with_context(&context, || {
+            write_stdout("out").unwrap();
+            write_stderr("err").unwrap();
+        });
+        assert_eq!(b"out".to_vec(), *stdout.borrow());
+        assert_eq!(b"err".to_vec(), *stderr.borrow());
+    }
+
+    #[test]
+    fn the_previous_context_is_restored_after_a_panic() {
+        let outer = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let context = IoContext {
+            stdout: outer.clone(),
+            ..context_with_access(FileSystemAccess::Denied)
+        };
+        with_context(&context, || {
+            let panicked = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                with_context(&context_with_access(FileSystemAccess::Denied), || panic!("native function failed"))
+            }));
+            assert!(panicked.is_err());
+            write_stdout("still here").unwrap();
+        });
+        assert_eq!(b"still here".to_vec(), *outer.borrow());
+    }
+
+    #[test]
+    fn input_comes_from_the_installed_context() {
+        let context = IoContext {
+            stdin: Rc::new(RefCell::new(io::Cursor::new("first\r\nsecond\nrest"))),
+            stdout: Rc::new(RefCell::new(io::sink())),
+            stderr: Rc::new(RefCell::new(io::sink())),
+            file_system: FileSystemAccess::Denied,
+        };
+        with_context(&context, || {
+            assert_eq!(Ok("first".to_string()), read_line());
+            assert_eq!(Ok("second".to_string()), read_line());
+            assert_eq!(Ok("rest".to_string()), read_all());
+            assert_eq!(Ok("".to_string()), read_line());
+        });
+    }
+
+    #[test]
+    fn prompts_are_flushed_before_reading() {
+        let stdout = Rc::new(RefCell::new(io::BufWriter::new(Vec::<u8>::new())));
+        let context = IoContext {
+            stdin: Rc::new(RefCell::new(io::Cursor::new("Ada\n"))),
+            stdout: stdout.clone(),
+            ..context_with_access(FileSystemAccess::Denied)
+        };
+        with_context(&context, || {
+            write_stdout("name? ").unwrap();
+            assert_eq!(Ok("Ada".to_string()), read_line());
+        });
+        assert_eq!(b"name? ".to_vec(), *stdout.borrow().get_ref());
+    }
+
+    fn context_with_access(file_system: FileSystemAccess) -> IoContext {
+        IoContext {
+            stdin: Rc::new(RefCell::new(io::empty())),
+            stdout: Rc::new(RefCell::new(io::sink())),
+            stderr: Rc::new(RefCell::new(io::sink())),
+            file_system,
+        }
+    }
+
+    #[test]
+    fn file_access_is_denied_by_default() {
+        assert!(allowed_path("Cargo.toml").is_err());
+        with_context(&context_with_access(FileSystemAccess::Unrestricted), || {
+            assert_eq!(Ok(PathBuf::from("Cargo.toml")), allowed_path("Cargo.toml"));
+        });
+    }
+
+    #[test]
+    fn file_access_can_be_limited_to_a_directory() {
+        with_context(&context_with_access(FileSystemAccess::Within(PathBuf::from("examples"))), || {
+            let examples: PathBuf = Path::new("examples").canonicalize().unwrap();
+            assert_eq!(Ok(examples.join("test.hlw")), allowed_path("examples/test.hlw"));
+            assert_eq!(Ok(examples.join("new_file.txt")), allowed_path("examples/new_file.txt"));
+            assert!(allowed_path("Cargo.toml").is_err());
+            assert!(allowed_path("examples/../Cargo.toml").is_err());
+            assert!(allowed_path("examples/missing_directory/file.txt").is_err());
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn links_to_missing_files_outside_the_directory_are_refused() {
+        use std::env;
+        use std::os::unix::fs::symlink;
+
+        let directory: PathBuf = env::temp_dir().join(format!("hlwnpa_link_test_{}", ::std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let outside: PathBuf = env::temp_dir().join(format!("hlwnpa_link_target_{}.txt", ::std::process::id()));
+        symlink(&outside, directory.join("outside_link")).unwrap();
+        symlink("target.txt", directory.join("inside_link")).unwrap();
+        symlink("loop", directory.join("loop")).unwrap();
+
+        let result = panic::catch_unwind(|| {
+            with_context(&context_with_access(FileSystemAccess::Within(directory.clone())), || {
+                let link: String = directory.join("outside_link").display().to_string();
+                assert!(allowed_path(&link).is_err());
+                let link: String = directory.join("inside_link").display().to_string();
+                assert_eq!(Ok(directory.canonicalize().unwrap().join("target.txt")), allowed_path(&link));
+                assert!(allowed_path(&directory.join("loop").display().to_string()).is_err());
+            })
+        });
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,30 @@
+#![cfg_attr(test, feature(test))]
+#![recursion_limit="100"]
+
+extern crate nom;
+#[cfg(test)]
+extern crate test;
+extern crate uuid;
+#[macro_use]
+extern crate serde_json;
+
+pub mod ast;
+pub mod parser;
+pub mod std_functions;
+#[cfg(test)]
+mod testing;
+mod include;
+pub mod preprocessor;
+mod interpreter;
+mod session;
+mod locations;
+pub mod formatter;
+pub mod linter;
+pub mod lsp;
+pub mod debugger;
+pub mod test_runner;
+pub mod io_context;
+
+use ast::*;
+
+pub use interpreter::{Interpreter, parse};
@@ -0,0 +1,679 @@
+use ast::{Ast, SExpression, Datatype, TypeInfo, LangError};
+use ast::type_checking::{TypeStore, Mutability};
+use formatter;
+use interpreter::parse_located;
+use std_functions;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The function a warning was found in.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Location {
+    TopLevel,
+    Function(String),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Location::TopLevel => write!(f, "top level"),
+            Location::Function(ref name) => write!(f, "fn {}", name),
+        }
+    }
+}
+
+/// Mistakes that don't stop a program from running, but probably aren't what was meant.
+#[derive(PartialEq, Debug, Clone)]
+pub enum WarningKind {
+    /// A variable, parameter or caught error that is never read.
+    UnusedVariable(String),
+    /// A `let` that is never reassigned.
+    CouldBeConst(String),
+    /// A variable or parameter with the name of a function, or a function with the name of a standard function.
+    ShadowedFunction(String),
+    /// Code that can never run, shown as its first line.
+    UnreachableCode(String),
+    /// A comparison between values of different types, which always has the same result.
+    MismatchedComparison { comparison: String, lhs: TypeInfo, rhs: TypeInfo, always: bool },
+    /// A call to a function that isn't declared anywhere.
+    UndefinedFunction(String),
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WarningKind::UnusedVariable(ref name) => write!(f, "`{}` is never used", name),
+            WarningKind::CouldBeConst(ref name) => write!(f, "`{}` is never reassigned, so it could be const", name),
+            WarningKind::ShadowedFunction(ref name) => write!(f, "`{}` hides the function with the same name", name),
+            WarningKind::UnreachableCode(ref code) => write!(f, "`{}` can never run", code),
+            WarningKind::MismatchedComparison { ref comparison, ref lhs, ref rhs, always } => {
+                write!(f, "`{}` compares {} with {}, so it is always {}", comparison, lhs, rhs, always)
+            }
+            WarningKind::UndefinedFunction(ref name) => write!(f, "`{}` is called, but isn't declared", name),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Warning {
+    /// The line the warning is about, counting from 1, when the program was linted from its source.
+    pub line: Option<usize>,
+    pub location: Location,
+    pub kind: WarningKind,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+/// Reads and lints the `.hlw` file.
+pub fn lint_file(file: &Path) -> Result<Vec<Warning>, LangError> {
+    let filename: String = file.display().to_string();
+    let source: String = fs::read_to_string(file).map_err(|e| {
+        LangError::CouldNotReadFile { filename: filename.clone(), reason: e.to_string() }
+    })?;
+    match lint_source(&source) {
+        Err(LangError::InvalidSyntax(reason)) => Err(LangError::CouldNotParseFile { filename, reason }),
+        result => result,
+    }
+}
+
+/// Parses and lints the source, finding the line of each warning, and putting the warnings in the order of their lines.
+pub fn lint_source(source: &str) -> Result<Vec<Warning>, LangError> {
+    let ast: Ast = parse_located(source)?.hoist_functions_and_structs();
+    let mut warnings: Vec<Warning> = lint_hoisted(&ast);
+    warnings.sort_by_key(|warning| warning.line);
+    Ok(warnings)
+}
+
+/// Finds likely mistakes in the program, after hoisting its functions and structs like a file is when it runs.
+///
+/// Warnings about the top level come first, followed by each function in turn.
+/// Variables whose names start with `_` aren't reported as unused.
+/// Calls to undefined functions aren't reported if the program includes other files, which could declare them.
+/// The lines of the warnings are only known if the program was parsed with the lines of its statements.
+pub fn lint(ast: &Ast) -> Vec<Warning> {
+    lint_hoisted(&ast.hoist_functions_and_structs())
+}
+
+fn lint_hoisted(ast: &Ast) -> Vec<Warning> {
+    let statements: Vec<&Ast> = match *ast {
+        Ast::ExpressionList(ref statements) => statements.iter().collect(),
+        ref other => vec![other],
+    };
+
+    let mut type_store: TypeStore = TypeStore::new();
+    std_functions::add_std_function_types(&mut type_store);
+    let standard_functions: HashSet<String> = type_store.keys().cloned().collect();
+    let mut linter = Linter {
+        functions: standard_functions.clone(),
+        check_calls: !statements.iter().any(|statement| includes_files(statement)),
+        globals: vec![],
+        locals: None,
+        location: Location::TopLevel,
+        line: None,
+        type_store,
+        assigning: None,
+        warnings: vec![],
+    };
+
+    let mut functions: Vec<(String, &Ast, Option<usize>)> = vec![];
+    for statement in &statements {
+        if let Ast::SExpr(SExpression::DeclareFunction { ref identifier, ref function_datatype }) = *statement.unlocated() {
+            if let Ast::ValueIdentifier(ref name) = **identifier {
+                let line: Option<usize> = line_of(statement);
+                if standard_functions.contains(name) {
+                    linter.warn_at(WarningKind::ShadowedFunction(name.clone()), line);
+                }
+                linter.functions.insert(name.clone());
+                functions.push((name.clone(), function_datatype, line));
+            }
+        }
+    }
+
+    // The functions are linted after the rest of the top level, so the globals they use have been declared.
+    let top_level: Vec<&Ast> = statements.iter()
+        .filter(|statement| !matches!(*statement.unlocated(), Ast::SExpr(SExpression::DeclareFunction { .. })))
+        .cloned()
+        .collect();
+    for statement in &statements {
+        if let Ast::SExpr(SExpression::DeclareFunction { .. }) = *statement.unlocated() {
+            let _ = statement.check_types(&mut linter.type_store);
+        }
+    }
+    linter.lint_statements(&top_level);
+    for (name, function, line) in functions {
+        linter.lint_function(name, function, line);
+    }
+    let globals: Vec<Binding> = linter.globals.drain(..).collect();
+    linter.location = Location::TopLevel;
+    linter.report_bindings(globals);
+    linter.warnings
+}
+
+fn includes_files(ast: &Ast) -> bool {
+    match *ast {
+        Ast::SExpr(SExpression::Include(_)) => true,
+        Ast::ExpressionList(ref statements) => statements.iter().any(includes_files),
+        Ast::Conditional { ref true_expr, ref false_expr, .. } => {
+            includes_files(true_expr) || false_expr.as_ref().is_some_and(|false_expr| includes_files(false_expr))
+        }
+        Ast::SExpr(SExpression::Loop { ref body, .. }) => includes_files(body),
+        Ast::SExpr(SExpression::TryCatch { ref body, ref handler, .. }) => includes_files(body) || includes_files(handler),
+        Ast::Located { ref ast, .. } => includes_files(ast),
+        _ => false,
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum BindingKind {
+    Let,
+    Const,
+    Parameter,
+    /// The variable a `for` loop declares for each element.
+    LoopVariable,
+    /// The error of a `try`'s `catch`.
+    CaughtError,
+}
+
+/// A variable, and what has been done with it so far.
+struct Binding {
+    name: String,
+    kind: BindingKind,
+    used: bool,
+    reassigned: bool,
+    /// Declared again in the same scope, which isn't allowed for constants.
+    redeclared: bool,
+    line: Option<usize>,
+}
+
+struct Linter {
+    /// The standard functions and the functions the program declares.
+    functions: HashSet<String>,
+    check_calls: bool,
+    globals: Vec<Binding>,
+    /// The parameters and variables of the function being linted, or None at the top level.
+    locals: Option<Vec<Binding>>,
+    location: Location,
+    /// The line of the statement being linted.
+    line: Option<usize>,
+    /// The types of what has been declared so far, used to find the types of comparisons.
+    type_store: TypeStore,
+    /// The variable whose new value is being calculated, as reading it to calculate its new value doesn't count as using it.
+    assigning: Option<String>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter {
+    fn warn(&mut self, kind: WarningKind) {
+        let line: Option<usize> = self.line;
+        self.warn_at(kind, line);
+    }
+
+    fn warn_at(&mut self, kind: WarningKind, line: Option<usize>) {
+        self.warnings.push(Warning { line, location: self.location.clone(), kind });
+    }
+
+    /// Warns that the code can never run, on the line of its first statement.
+    fn warn_unreachable(&mut self, code: &Ast) {
+        let line: Option<usize> = line_of(first_statement(code)).or(self.line);
+        self.warn_at(WarningKind::UnreachableCode(first_line(code)), line);
+    }
+
+    fn lint_function(&mut self, name: String, function: &Ast, line: Option<usize>) {
+        let (parameters, body) = match *function {
+            Ast::Literal(Datatype::Function { ref parameters, ref body, .. }) => (parameters, body),
+            _ => return,
+        };
+        let globals_type_store: TypeStore = self.type_store.clone();
+        self.location = Location::Function(name);
+        self.line = line;
+        self.locals = Some(vec![]);
+        if let Ast::ExpressionList(ref parameters) = **parameters {
+            for parameter in parameters {
+                if let Some((parameter_name, _)) = parameter.declaration_identifier() {
+                    self.declare(parameter_name, BindingKind::Parameter);
+                }
+                let _ = parameter.check_types(&mut self.type_store);
+            }
+        }
+        self.lint_block(body);
+        let locals: Vec<Binding> = self.locals.take().unwrap_or_default();
+        self.report_bindings(locals);
+        self.type_store = globals_type_store;
+    }
+
+    fn report_bindings(&mut self, bindings: Vec<Binding>) {
+        for binding in bindings {
+            if !binding.used && !binding.name.starts_with('_') {
+                // Whether it could be const doesn't matter if it can be removed.
+                self.warn_at(WarningKind::UnusedVariable(binding.name), binding.line);
+            } else if binding.kind == BindingKind::Let && !binding.reassigned && !binding.redeclared {
+                self.warn_at(WarningKind::CouldBeConst(binding.name), binding.line);
+            }
+        }
+    }
+
+    fn bindings(&mut self) -> &mut Vec<Binding> {
+        match self.locals {
+            Some(ref mut locals) => locals,
+            None => &mut self.globals,
+        }
+    }
+
+    fn declare(&mut self, name: &str, kind: BindingKind) {
+        if formatter::is_uuid(name) {
+            return; // The hidden variables of `for` loops.
+        }
+        if self.functions.contains(name) {
+            self.warn(WarningKind::ShadowedFunction(name.to_string()));
+        }
+        let line: Option<usize> = self.line;
+        let bindings: &mut Vec<Binding> = self.bindings();
+        for binding in bindings.iter_mut().filter(|binding| binding.name == name) {
+            binding.redeclared = true;
+        }
+        bindings.push(Binding { name: name.to_string(), kind, used: false, reassigned: false, redeclared: false, line });
+    }
+
+    /// Finds the innermost variable with the name, looking through the function's variables before the globals.
+    fn binding(&mut self, name: &str) -> Option<&mut Binding> {
+        if let Some(ref mut locals) = self.locals {
+            if let Some(binding) = locals.iter_mut().rev().find(|binding| binding.name == name) {
+                return Some(binding);
+            }
+        }
+        self.globals.iter_mut().rev().find(|binding| binding.name == name)
+    }
+
+    fn read(&mut self, name: &str) {
+        if self.assigning.as_deref() == Some(name) {
+            return;
+        }
+        if let Some(binding) = self.binding(name) {
+            binding.used = true;
+        }
+    }
+
+    /// Lints the statements in order, keeping track of the types of what they declare.
+    fn lint_statements(&mut self, statements: &[&Ast]) {
+        let mut reported_unreachable: bool = false;
+        for (index, statement) in statements.iter().enumerate() {
+            // Statements that aren't in the source, like the hidden parts of `for` loops, keep the line of the statement around them.
+            let outer_line: Option<usize> = self.line;
+            self.line = line_of(statement).or(outer_line);
+            self.lint_ast(statement);
+            let _ = statement.check_types(&mut self.type_store);
+            if let Ast::SExpr(SExpression::Loop { ref conditional, .. }) = *statement.unlocated() {
+                if let (Ast::Literal(Datatype::Bool(true)), Some(next)) = (conditional.as_ref(), statements.get(index + 1)) {
+                    if !reported_unreachable {
+                        self.warn_unreachable(next);
+                        reported_unreachable = true;
+                    }
+                }
+            }
+            self.line = outer_line;
+        }
+    }
+
+    fn lint_block(&mut self, block: &Ast) {
+        match *block {
+            Ast::ExpressionList(ref statements) => {
+                let statements: Vec<&Ast> = statements.iter().collect();
+                self.lint_statements(&statements);
+            }
+            ref other => self.lint_statements(&[other]),
+        }
+    }
+
+    fn lint_ast(&mut self, ast: &Ast) {
+        match *ast {
+            Ast::ValueIdentifier(ref name) => self.read(name),
+            Ast::ExpressionList(_) => self.lint_block(ast),
+            Ast::Conditional { ref condition, ref true_expr, ref false_expr } => {
+                self.lint_ast(condition);
+                match (condition.as_ref(), false_expr) {
+                    (Ast::Literal(Datatype::Bool(false)), _) => self.warn_unreachable(true_expr),
+                    (Ast::Literal(Datatype::Bool(true)), Some(false_expr)) => self.warn_unreachable(false_expr),
+                    _ => {}
+                }
+                self.lint_block(true_expr);
+                if let Some(ref false_expr) = *false_expr {
+                    self.lint_block(false_expr);
+                }
+            }
+            Ast::Literal(_) | Ast::Type(_) => {}
+            Ast::SExpr(ref sexpr) => self.lint_sexpr(sexpr),
+            Ast::Located { ref ast, .. } => self.lint_ast(ast),
+        }
+    }
+
+    fn lint_sexpr(&mut self, sexpr: &SExpression) {
+        match *sexpr {
+            SExpression::Equals(ref lhs, ref rhs) => self.lint_comparison(sexpr, lhs, rhs, false),
+            SExpression::NotEquals(ref lhs, ref rhs) => self.lint_comparison(sexpr, lhs, rhs, true),
+            SExpression::GreaterThan(ref lhs, ref rhs) | SExpression::LessThan(ref lhs, ref rhs) |
+            SExpression::GreaterThanOrEqual(ref lhs, ref rhs) | SExpression::LessThanOrEqual(ref lhs, ref rhs) => {
+                self.lint_comparison(sexpr, lhs, rhs, false)
+            }
+            SExpression::Add(ref lhs, ref rhs) | SExpression::Subtract(ref lhs, ref rhs) |
+            SExpression::Multiply(ref lhs, ref rhs) | SExpression::Divide(ref lhs, ref rhs) |
+            SExpression::Modulo(ref lhs, ref rhs) | SExpression::LogicalAnd(ref lhs, ref rhs) |
+            SExpression::LogicalOr(ref lhs, ref rhs) | SExpression::Power(ref lhs, ref rhs) |
+            SExpression::BitwiseAnd(ref lhs, ref rhs) | SExpression::BitwiseOr(ref lhs, ref rhs) |
+            SExpression::BitwiseXor(ref lhs, ref rhs) | SExpression::ShiftLeft(ref lhs, ref rhs) |
+            SExpression::ShiftRight(ref lhs, ref rhs) => {
+                self.lint_ast(lhs);
+                self.lint_ast(rhs);
+            }
+            SExpression::Print(ref operand) | SExpression::Include(ref operand) |
+            SExpression::Invert(ref operand) | SExpression::Negate(ref operand) |
+            SExpression::BitwiseNot(ref operand) | SExpression::Increment(ref operand) |
+            SExpression::Decrement(ref operand) | SExpression::GetArrayLength(ref operand) |
+            SExpression::CreateArray(ref operand) => self.lint_ast(operand),
+            SExpression::VariableDeclaration { ref identifier, ref ast } => {
+                self.lint_ast(ast);
+                let kind: BindingKind = match **ast {
+                    Ast::SExpr(SExpression::AccessArray { ref index, .. }) if is_hidden_variable(index) => BindingKind::LoopVariable,
+                    _ => BindingKind::Let,
+                };
+                if let Some((name, _)) = identifier.declaration_identifier() {
+                    self.declare(name, kind);
+                }
+            }
+            SExpression::ConstDeclaration { ref identifier, ref ast } => {
+                self.lint_ast(ast);
+                if let Some((name, _)) = identifier.declaration_identifier() {
+                    self.declare(name, BindingKind::Const);
+                }
+            }
+            SExpression::Assignment { ref identifier, ref ast } => {
+                let target: Option<String> = assigned_variable(identifier).cloned();
+                if let Some(ref target) = target {
+                    if let Some(binding) = self.binding(target) {
+                        binding.reassigned = true;
+                    }
+                }
+                // Assigning to an element or field reads the rest of the variable.
+                if !matches!(**identifier, Ast::ValueIdentifier(_)) {
+                    self.lint_ast(identifier);
+                }
+                let outer_target: Option<String> = ::std::mem::replace(&mut self.assigning, target);
+                self.lint_ast(ast);
+                self.assigning = outer_target;
+            }
+            SExpression::TypeAssignment { .. } | SExpression::StructDeclaration { .. } => {}
+            SExpression::FieldAssignment { ref ast, .. } => self.lint_ast(ast),
+            SExpression::DeclareFunction { .. } => {} // Functions are linted on their own.
+            SExpression::CreateStruct { ref struct_datatype, .. } => self.lint_ast(struct_datatype),
+            SExpression::Loop { ref conditional, ref body } => {
+                self.lint_ast(conditional);
+                if let Ast::Literal(Datatype::Bool(false)) = **conditional {
+                    self.warn_unreachable(body);
+                }
+                self.lint_block(body);
+            }
+            SExpression::TryCatch { ref body, ref error_identifier, ref handler } => {
+                self.lint_block(body);
+                if let Ast::ValueIdentifier(ref name) = **error_identifier {
+                    self.declare(name, BindingKind::CaughtError);
+                    let replaced: Option<Mutability> = self.type_store.insert(name.clone(), Mutability::Mutable(TypeInfo::String));
+                    self.lint_block(handler);
+                    match replaced {
+                        Some(replaced) => self.type_store.insert(name.clone(), replaced),
+                        None => self.type_store.remove(name),
+                    };
+                } else {
+                    self.lint_block(handler);
+                }
+            }
+            SExpression::AccessArray { ref identifier, ref index } => {
+                self.lint_ast(identifier);
+                self.lint_ast(index);
+            }
+            SExpression::Range { ref start, ref end } => {
+                self.lint_ast(start);
+                self.lint_ast(end);
+            }
+            SExpression::AccessStructField { ref identifier, .. } => self.lint_ast(identifier),
+            SExpression::ExecuteFn { ref identifier, ref parameters } => {
+                if let Ast::ValueIdentifier(ref name) = **identifier {
+                    let declared: bool = self.functions.contains(name) || self.binding(name).is_some() || self.type_store.contains_key(name);
+                    if self.check_calls && !declared {
+                        self.warn(WarningKind::UndefinedFunction(name.clone()));
+                    }
+                }
+                self.lint_ast(identifier);
+                self.lint_ast(parameters);
+            }
+        }
+    }
+
+    /// Warns if the operands of the comparison have types that can't be compared.
+    /// Only types that are known for certain are compared, so `Any` and structs are never reported.
+    fn lint_comparison(&mut self, sexpr: &SExpression, lhs: &Ast, rhs: &Ast, always: bool) {
+        self.lint_ast(lhs);
+        self.lint_ast(rhs);
+        let lhs_type: Option<TypeInfo> = lhs.check_types(&mut self.type_store.clone()).ok().map(Mutability::get_type);
+        let rhs_type: Option<TypeInfo> = rhs.check_types(&mut self.type_store.clone()).ok().map(Mutability::get_type);
+        if let (Some(lhs_type), Some(rhs_type)) = (lhs_type, rhs_type) {
+            if is_mismatched(&lhs_type, &rhs_type) {
+                self.warn(WarningKind::MismatchedComparison {
+                    comparison: first_line(&Ast::SExpr(sexpr.clone())),
+                    lhs: lhs_type,
+                    rhs: rhs_type,
+                    always,
+                });
+            }
+        }
+    }
+}
+
+/// The variable that an assignment changes, including when it changes an element or field of it.
+fn assigned_variable(target: &Ast) -> Option<&String> {
+    match *target {
+        Ast::ValueIdentifier(ref name) => Some(name),
+        Ast::SExpr(SExpression::AccessArray { ref identifier, .. }) |
+        Ast::SExpr(SExpression::AccessStructField { ref identifier, .. }) => assigned_variable(identifier),
+        _ => None,
+    }
+}
+
+fn is_hidden_variable(ast: &Ast) -> bool {
+    match *ast {
+        Ast::ValueIdentifier(ref name) => formatter::is_uuid(name),
+        _ => false,
+    }
+}
+
+/// Checks if values of the types can never be equal.
+/// Numbers and Floats can be compared with each other.
+fn is_mismatched(lhs: &TypeInfo, rhs: &TypeInfo) -> bool {
+    match (lhs, rhs) {
+        (TypeInfo::Array(lhs), TypeInfo::Array(rhs)) => is_mismatched(lhs, rhs),
+        (TypeInfo::Number, TypeInfo::Float) | (TypeInfo::Float, TypeInfo::Number) => false,
+        _ => is_simple_type(lhs) && is_simple_type(rhs) && lhs != rhs,
+    }
+}
+
+fn is_simple_type(type_info: &TypeInfo) -> bool {
+    match *type_info {
+        TypeInfo::Number | TypeInfo::Float | TypeInfo::String | TypeInfo::Bool => true,
+        TypeInfo::Array(ref element_type) => is_simple_type(element_type),
+        _ => false,
+    }
+}
+
+/// The line the statement was parsed from, if the program was parsed with the lines of its statements.
+fn line_of(statement: &Ast) -> Option<usize> {
+    match *statement {
+        Ast::Located { line, .. } => Some(line),
+        _ => None,
+    }
+}
+
+fn first_statement(ast: &Ast) -> &Ast {
+    match *ast {
+        Ast::ExpressionList(ref statements) if !statements.is_empty() => &statements[0],
+        ref other => other,
+    }
+}
+
+/// The first line of the code as it would be formatted, to show in a warning.
+fn first_line(ast: &Ast) -> String {
+    formatter::format_program(first_statement(ast)).ok()
+        .and_then(|source| source.lines().next().map(String::from))
+        .unwrap_or_else(|| "code".to_string())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interpreter::parse;
+
+    fn lint_str(source: &str) -> Vec<String> {
+        lint(&parse(source).unwrap()).iter().map(|warning| warning.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_unused_variables() {
+        let warnings: Vec<String> = lint_str("\
+fn f(a: Number, _b: Number) -> Number {
+    let total := 0
+    total := total + 1
+    const unused := 3
+    a
+}
+const used := 4
+f(used, 1)
+");
+        assert_eq!(vec!["fn f: `total` is never used", "fn f: `unused` is never used"], warnings);
+    }
+
+    #[test]
+    fn finds_lets_that_could_be_const() {
+        let warnings: Vec<String> = lint_str("\
+let width := 3
+let count := 0
+count += width
+let again := 1
+let again := again + width
+for x in [1, 2] { count := count + x }
+let forgotten := 2
+count + again
+");
+        // Only the last `again` could be const, as constants can't be declared again, and `forgotten` should be removed instead.
+        assert_eq!(vec![
+            "top level: `width` is never reassigned, so it could be const",
+            "top level: `again` is never reassigned, so it could be const",
+            "top level: `forgotten` is never used",
+        ], warnings);
+    }
+
+    #[test]
+    fn finds_shadowed_functions() {
+        let warnings: Vec<String> = lint_str("\
+fn double(x: Number) -> Number { x * 2 }
+fn len(x: Number) -> Number { x }
+fn f(double: Number) -> Number { double }
+");
+        assert_eq!(vec![
+            "top level: `len` hides the function with the same name",
+            "fn f: `double` hides the function with the same name",
+        ], warnings);
+    }
+
+    #[test]
+    fn finds_unreachable_code() {
+        let warnings: Vec<String> = lint_str("\
+fn f() -> Number {
+    while true {
+        println(\"forever\")
+    }
+    println(\"done\")
+    if false { 1 } else { 2 }
+}
+");
+        assert_eq!(vec!["fn f: `println(\"done\")` can never run", "fn f: `1` can never run"], warnings);
+    }
+
+    #[test]
+    fn finds_mismatched_comparisons() {
+        let warnings: Vec<String> = lint_str("\
+[1] == [\"1\"]
+let count := 3
+let name := \"count\"
+count == name
+count != \"3\"
+count < 3.5
+");
+        assert_eq!(vec![
+            "top level: `[1] == [\"1\"]` compares [Number] with [String], so it is always false",
+            "top level: `count == name` compares Number with String, so it is always false",
+            "top level: `count != \"3\"` compares Number with String, so it is always true",
+            "top level: `count` is never reassigned, so it could be const",
+            "top level: `name` is never reassigned, so it could be const",
+        ], warnings);
+    }
+
+    #[test]
+    fn finds_calls_to_undefined_functions() {
+        let warnings: Vec<String> = lint_str("\
+fn main() -> Number {
+    const twice := double
+    twice(2) + triple(1) + len([1])
+}
+fn double(x: Number) -> Number { x * 2 }
+");
+        assert_eq!(vec!["fn main: `triple` is called, but isn't declared"], warnings);
+        // Included files could declare anything.
+        assert_eq!(Vec::<String>::new(), lint_str("include \"lib.hlw\"\ntriple(1)"));
+    }
+
+    #[test]
+    fn warnings_are_on_the_lines_they_are_about() {
+        let source: &str = "\
+let unused := 1
+fn f(x: Number) -> Number {
+    let total := x
+    while true {
+        total += 1
+    }
+    total += 1
+}
+for i in [0..2] {
+    let y := 2
+}
+const unused := 3
+";
+        let warnings: Vec<(Option<usize>, String)> = lint_source(source).unwrap()
+            .iter()
+            .map(|warning| (warning.line, warning.to_string()))
+            .collect();
+        // `unused` is reported on both of the lines that declare it, and `y` can be removed rather than made const.
+        assert_eq!(vec![
+            (Some(1), "top level: `unused` is never used".to_string()),
+            (Some(3), "fn f: `total` is never used".to_string()),
+            (Some(7), "fn f: `total += 1` can never run".to_string()),
+            (Some(9), "top level: `i` is never used".to_string()),
+            (Some(10), "top level: `y` is never used".to_string()),
+            (Some(12), "top level: `unused` is never used".to_string()),
+        ], warnings);
+        assert_eq!(vec![None], lint(&parse("let a := 1\na").unwrap()).iter().map(|warning| warning.line).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn for_loops_and_caught_errors_are_variables() {
+        let warnings: Vec<String> = lint_str("\
+for x in [1, 2] { println(\"\") }
+try { 1 / 0 } catch e { e }
+");
+        assert_eq!(vec!["top level: `x` is never used"], warnings);
+    }
+}
@@ -0,0 +1,188 @@
+use ast::{Ast, SExpression, Datatype};
+use parser::statement;
+use preprocessor::preprocess;
+use nom::IResult;
+
+use std::collections::BTreeSet;
+
+/// A statement parsed from part of the source, and where it is in the source.
+pub(crate) struct SourceStatement {
+    pub(crate) ast: Ast,
+    /// The byte offsets of where the statement starts and ends, leaving out the whitespace around it.
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+/// Parses the source one statement at a time, so each of the top level statements can be located.
+/// If part of the source can't be parsed, the statements before it are returned along with the offset of the part
+/// and why it couldn't be parsed.
+pub(crate) fn top_level_statements(source: &str) -> (Vec<SourceStatement>, Option<(usize, String)>) {
+    let preprocessed: String = preprocess(source);
+    let offsets: Vec<usize> = original_offsets(source, &preprocessed);
+    let mut statements: Vec<SourceStatement> = vec![];
+    let mut offset: usize = 0;
+    loop {
+        let rest: &str = &preprocessed[offset..];
+        let start: usize = offset + (rest.len() - rest.trim_start().len());
+        if start == preprocessed.len() {
+            return (statements, None);
+        }
+        let problem: String = match statement(&preprocessed.as_bytes()[start..]) {
+            IResult::Done(remaining, ast) if remaining.len() < preprocessed.len() - start => {
+                let next: usize = preprocessed.len() - remaining.len();
+                let end: usize = start + preprocessed[start..next].trim_end().len();
+                statements.push(SourceStatement { ast, start: offsets[start], end: offsets[end] });
+                offset = next;
+                continue;
+            }
+            IResult::Error(e) => format!("Couldn't parse this: {}", e),
+            _ => "Couldn't parse this".to_string(),
+        };
+        return (statements, Some((offsets[start], problem)));
+    }
+}
+
+/// The lines of the located statements in the program, including those in the blocks of its statements and functions.
+pub(crate) fn statement_lines(program: &Ast) -> BTreeSet<usize> {
+    let mut lines: BTreeSet<usize> = BTreeSet::new();
+    add_statement_lines(program, &mut lines);
+    lines
+}
+
+fn add_statement_lines(ast: &Ast, lines: &mut BTreeSet<usize>) {
+    match *ast {
+        Ast::Located { line, ref ast } => {
+            lines.insert(line);
+            add_statement_lines(ast, lines);
+        }
+        Ast::ExpressionList(ref statements) => {
+            for statement in statements {
+                add_statement_lines(statement, lines);
+            }
+        }
+        ref other => {
+            for block in blocks(other) {
+                add_statement_lines(block, lines);
+            }
+        }
+    }
+}
+
+/// The blocks of statements that the AST holds directly, in the order they appear in the source.
+fn blocks(ast: &Ast) -> Vec<&Ast> {
+    match *ast {
+        Ast::Conditional { ref true_expr, ref false_expr, .. } => {
+            let mut blocks: Vec<&Ast> = vec![true_expr];
+            blocks.extend(false_expr.as_ref().map(|false_expr| &**false_expr));
+            blocks
+        }
+        Ast::Literal(Datatype::Function { ref body, .. }) => vec![body],
+        Ast::SExpr(SExpression::DeclareFunction { ref function_datatype, .. }) => vec![function_datatype],
+        Ast::SExpr(SExpression::Loop { ref body, .. }) => vec![body],
+        Ast::SExpr(SExpression::TryCatch { ref body, ref handler, .. }) => vec![body, handler],
+        _ => vec![],
+    }
+}
+
+/// Preprocessing replaces escape sequences like `\n` with the character they stand for,
+/// so this maps each byte offset in the preprocessed source, and its end, back to an offset in the original.
+pub(crate) fn original_offsets(source: &str, preprocessed: &str) -> Vec<usize> {
+    let bytes: &[u8] = source.as_bytes();
+    let mut offsets: Vec<usize> = Vec::with_capacity(preprocessed.len() + 1);
+    let mut index: usize = 0;
+    while index < bytes.len() {
+        offsets.push(index);
+        let is_escape: bool = bytes[index] == b'\\' && matches!(bytes.get(index + 1), Some(b'n') | Some(b't') | Some(b'r') | Some(b'\\') | Some(b'"'));
+        index += if is_escape { 2 } else { 1 };
+    }
+    offsets.push(bytes.len());
+    if offsets.len() != preprocessed.len() + 1 {
+        // Escapes next to each other can be replaced differently, so fall back to the offsets being the same.
+        return (0..preprocessed.len() + 1).map(|offset| offset.min(source.len())).collect();
+    }
+    offsets
+}
+
+/// The line at each offset in the preprocessed source, and its end, if only whitespace comes before it on its line in the source.
+/// A statement starting at one of these offsets starts the line.
+pub(crate) fn statement_start_lines(source: &str, preprocessed: &str) -> Vec<Option<usize>> {
+    let mut lines: Vec<Option<usize>> = Vec::with_capacity(source.len() + 1);
+    let mut line: usize = 1;
+    let mut blank: bool = true;
+    for &byte in source.as_bytes() {
+        lines.push(if blank { Some(line) } else { None });
+        if byte == b'\n' {
+            line += 1;
+            blank = true;
+        } else if !byte.is_ascii_whitespace() {
+            blank = false;
+        }
+    }
+    lines.push(if blank { Some(line) } else { None });
+    original_offsets(source, preprocessed).into_iter().map(|offset| lines[offset]).collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use interpreter::{parse, parse_located};
+
+    const PROGRAM: &str = "\
+let count := 0
+fn bump() -> Number {
+    count += 1
+    if count > 2 {
+        count += 1
+    }
+    count += 1
+}
+count += 1
+for i in [0..2] {
+    count += i
+}
+";
+
+    #[test]
+    fn statements_that_start_lines_are_located() {
+        let program: Ast = parse_located(PROGRAM).unwrap();
+        assert_eq!(vec![1, 2, 3, 4, 5, 7, 9, 10, 11], statement_lines(&program).into_iter().collect::<Vec<usize>>());
+        let one_line: Ast = parse_located("let a := \"\\n\"\nif true { a } else { a }\n").unwrap();
+        assert_eq!(vec![1, 2], statement_lines(&one_line).into_iter().collect::<Vec<usize>>());
+        assert!(statement_lines(&parse(PROGRAM).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn repeated_statements_are_located_on_their_own_lines() {
+        let program: Ast = parse_located("count += 1\ncount += 1\n").unwrap();
+        let lines: Vec<Option<usize>> = match program {
+            Ast::ExpressionList(ref statements) => statements.iter()
+                .map(|statement| match *statement {
+                    Ast::Located { line, .. } => Some(line),
+                    _ => None,
+                })
+                .collect(),
+            _ => panic!("The program isn't a list"),
+        };
+        assert_eq!(vec![Some(1), Some(2)], lines);
+    }
+
+    #[test]
+    fn statement_start_lines_follow_escapes() {
+        let source: &str = "let a := \"\\n\"\n  b";
+        let preprocessed: String = preprocess(source);
+        let lines: Vec<Option<usize>> = statement_start_lines(source, &preprocessed);
+        assert_eq!(preprocessed.len() + 1, lines.len());
+        assert_eq!(Some(1), lines[0]);
+        assert_eq!(None, lines[1]);
+        assert_eq!(Some(2), lines[preprocessed.find('b').unwrap()]);
+    }
+
+    #[test]
+    fn top_level_statements_stop_at_syntax_errors() {
+        let (statements, problem) = top_level_statements("let a := \"\\n\"\nlet b := 2\n)");
+        let spans: Vec<(usize, usize)> = statements.iter().map(|parsed| (parsed.start, parsed.end)).collect();
+        assert_eq!(vec![(0, 13), (14, 24)], spans);
+        assert_eq!(Some(25), problem.map(|(offset, _)| offset));
+    }
+}
@@ -0,0 +1,577 @@
+use ast::{Ast, SExpression, TypeInfo, LangError};
+use ast::mutability::MutabilityMap;
+use ast::type_checking::{TypeStore, Mutability};
+use parser::KEYWORDS;
+use locations::{SourceStatement, top_level_statements};
+use std_functions;
+use serde_json::{self, Value};
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+
+/// Error code for a message that isn't valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// Error code for a request the server can't answer in its state, like one sent after `shutdown`.
+const INVALID_REQUEST: i64 = -32600;
+/// Error code for a request the server doesn't support.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The longest message the server reads, in bytes, so a client can't make it allocate any amount of memory.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+/// The kinds of completions, as numbered by the protocol.
+const FUNCTION_COMPLETION: u32 = 3;
+const FIELD_COMPLETION: u32 = 5;
+const VARIABLE_COMPLETION: u32 = 6;
+const KEYWORD_COMPLETION: u32 = 14;
+const STRUCT_COMPLETION: u32 = 22;
+
+/// Runs a Language Server Protocol server, reading requests from the input and writing responses to the output,
+/// until the client sends `exit` or closes the input.
+///
+/// Returns the status the process should exit with, which is 0 if the client asked the server to `shutdown` first, and 1 otherwise.
+/// Requests sent after `shutdown`, other than `exit`, are rejected.
+///
+/// Open documents are checked whenever they change, and their parse, mutability and type errors are published as diagnostics.
+/// The server also finds the declarations of functions and structs, shows the types of globals on hover,
+/// and completes keywords, globals and struct fields.
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> Result<i32, LangError> {
+    let mut server: Server = Server { documents: HashMap::new(), shut_down: false };
+    while let Some(incoming) = read_message(&mut input)? {
+        let (replies, exit): (Vec<Value>, bool) = match incoming {
+            Incoming::Message(message) => server.handle(message),
+            Incoming::TooLong(length) => {
+                let reason: String = format!("The message is {} bytes long, but can't be longer than {} bytes", length, MAX_MESSAGE_LENGTH);
+                (vec![error_response(Value::Null, INVALID_REQUEST, &reason)], false)
+            }
+        };
+        for reply in replies {
+            write_message(&mut output, &reply)?;
+        }
+        if exit {
+            break;
+        }
+    }
+    Ok(if server.shut_down { 0 } else { 1 })
+}
+
+/// What the client sent.
+enum Incoming {
+    Message(Value),
+    /// A message longer than `MAX_MESSAGE_LENGTH`, which was skipped without being read, with its length.
+    TooLong(usize),
+}
+
+/// Reads a message, which is a `Content-Length` header followed by that many bytes of JSON.
+/// Messages that aren't valid JSON are read as `null`. Returns None when the input has ended.
+fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Incoming>, LangError> {
+    let could_not_read = |e: ::std::io::Error| LangError::CouldNotReadInput(e.to_string());
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut header: String = String::new();
+        if input.read_line(&mut header).map_err(could_not_read)? == 0 {
+            return Ok(None);
+        }
+        let header: &str = header.trim();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse().ok();
+        }
+    }
+    let content_length: usize = content_length.unwrap_or(0);
+    if content_length > MAX_MESSAGE_LENGTH {
+        io::copy(&mut input.take(content_length as u64), &mut io::sink()).map_err(could_not_read)?;
+        return Ok(Some(Incoming::TooLong(content_length)));
+    }
+    let mut content: Vec<u8> = vec![0; content_length];
+    input.read_exact(&mut content).map_err(could_not_read)?;
+    Ok(Some(Incoming::Message(serde_json::from_slice(&content).unwrap_or(Value::Null))))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> Result<(), LangError> {
+    let content: String = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", content.len(), content)
+        .and_then(|_| output.flush())
+        .map_err(|e| LangError::CouldNotWriteOutput(e.to_string()))
+}
+
+struct Server {
+    /// The open documents, by their uri.
+    documents: HashMap<String, Document>,
+    /// Set by `shutdown`, after which the only message the server acts on is `exit`.
+    shut_down: bool,
+}
+
+impl Server {
+    /// Handles a message from the client, returning the messages to send back, and whether the server should exit.
+    fn handle(&mut self, message: Value) -> (Vec<Value>, bool) {
+        let method: &str = match message["method"].as_str() {
+            Some(method) => method,
+            None if message.is_object() => return (vec![], false), // A response to a request the server didn't make.
+            None => return (vec![error_response(Value::Null, PARSE_ERROR, "Couldn't parse the message")], false),
+        };
+        let id: Value = message["id"].clone();
+        let params: &Value = &message["params"];
+        if self.shut_down && method != "exit" {
+            return match id {
+                Value::Null => (vec![], false),
+                id => (vec![error_response(id, INVALID_REQUEST, "The server has shut down")], false),
+            };
+        }
+        let result: Option<Value> = match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    "textDocumentSync": 1, // The client sends the whole document when it changes.
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                },
+                "serverInfo": { "name": "hlwnpa" },
+            })),
+            "shutdown" => {
+                self.shut_down = true;
+                Some(Value::Null)
+            }
+            "exit" => return (vec![], true),
+            "textDocument/didOpen" => {
+                let uri: String = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+                let text: &str = params["textDocument"]["text"].as_str().unwrap_or_default();
+                return (vec![self.open(uri, text)], false);
+            }
+            "textDocument/didChange" => {
+                let uri: String = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+                let changes: &[Value] = params["contentChanges"].as_array().map_or(&[], Vec::as_slice);
+                return match changes.last().and_then(|change| change["text"].as_str()) {
+                    Some(text) => (vec![self.open(uri, text)], false),
+                    None => (vec![], false),
+                };
+            }
+            "textDocument/didClose" => {
+                let uri: String = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+                self.documents.remove(&uri);
+                return (vec![diagnostics_notification(&uri, vec![])], false);
+            }
+            "textDocument/definition" => Some(self.with_document(params, |document, offset, uri| document.definition(offset, uri))),
+            "textDocument/hover" => Some(self.with_document(params, |document, offset, _| document.hover(offset))),
+            "textDocument/completion" => Some(self.with_document(params, |document, offset, _| document.completion(offset))),
+            _ => None,
+        };
+        let is_request: bool = !id.is_null();
+        match result {
+            Some(result) if is_request => (vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })], false),
+            None if is_request => (vec![error_response(id, METHOD_NOT_FOUND, &format!("{} isn't supported", method))], false),
+            _ => (vec![], false), // Notifications don't get a response.
+        }
+    }
+
+    /// Stores the document, returning the notification of its diagnostics.
+    fn open(&mut self, uri: String, text: &str) -> Value {
+        let document: Document = Document::new(text);
+        let notification: Value = diagnostics_notification(&uri, document.diagnostics());
+        self.documents.insert(uri, document);
+        notification
+    }
+
+    /// Answers a request about a position in a document, or with `null` if the document isn't open.
+    fn with_document<F>(&self, params: &Value, answer: F) -> Value
+        where F: Fn(&Document, usize, &str) -> Value
+    {
+        let uri: &str = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match self.documents.get(uri) {
+            Some(document) => {
+                let line: usize = params["position"]["line"].as_u64().unwrap_or(0) as usize;
+                let character: usize = params["position"]["character"].as_u64().unwrap_or(0) as usize;
+                answer(document, document.offset(line, character), uri)
+            }
+            None => Value::Null,
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// An error found in a document, between two byte offsets.
+struct Problem {
+    start: usize,
+    end: usize,
+    message: String,
+}
+
+/// An open document, and what was found when checking it.
+struct Document {
+    source: String,
+    statements: Vec<SourceStatement>,
+    /// The types of the globals, as far as the checker got.
+    type_store: TypeStore,
+    problems: Vec<Problem>,
+}
+
+impl Document {
+    /// Parses the source one statement at a time, so each can be located, and checks them in the order they would be evaluated.
+    fn new(source: &str) -> Document {
+        let (statements, syntax_error) = top_level_statements(source);
+        let mut problems: Vec<Problem> = vec![];
+        if let Some((start, message)) = syntax_error {
+            let end: usize = source[start..].find('\n').map_or(source.len(), |line_end| start + line_end);
+            problems.push(Problem { start, end, message });
+        }
+
+        let mut mutability_map: MutabilityMap = MutabilityMap::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        std_functions::add_std_function_types(&mut type_store);
+        for index in evaluation_order(&statements) {
+            let SourceStatement { ref ast, start, end } = statements[index];
+            let message: String = match ast.check_mutability_semantics(&mut mutability_map) {
+                Err(e) => format!("Mutability error: {:?}", e),
+                Ok(()) => match ast.check_types(&mut type_store) {
+                    Err(e) => format!("Type error: {:?}", e),
+                    Ok(_) => continue,
+                },
+            };
+            problems.push(Problem { start, end, message });
+        }
+        problems.sort_by_key(|problem| problem.start);
+
+        Document { source: source.to_string(), statements, type_store, problems }
+    }
+
+    fn diagnostics(&self) -> Vec<Value> {
+        self.problems.iter()
+            .map(|problem| json!({
+                "range": self.range(problem.start, problem.end),
+                "severity": 1, // Error
+                "source": "hlwnpa",
+                "message": problem.message,
+            }))
+            .collect()
+    }
+
+    /// Finds the declaration of the function or struct under the cursor.
+    fn definition(&self, offset: usize, uri: &str) -> Value {
+        let name: &str = match self.word_at(offset) {
+            Some(name) => name,
+            None => return Value::Null,
+        };
+        for statement in &self.statements {
+            let keyword: &str = match statement.ast {
+                Ast::SExpr(SExpression::DeclareFunction { ref identifier, .. }) if **identifier == Ast::ValueIdentifier(name.to_string()) => "fn",
+                Ast::SExpr(SExpression::StructDeclaration { ref identifier, .. }) if **identifier == Ast::ValueIdentifier(name.to_string()) => "struct",
+                _ => continue,
+            };
+            let text: &str = &self.source[statement.start..statement.end];
+            let name_start: usize = statement.start + keyword.len() + text[keyword.len()..].find(name).unwrap_or(0);
+            return json!({ "uri": uri, "range": self.range(name_start, name_start + name.len()) });
+        }
+        Value::Null
+    }
+
+    /// Describes the global under the cursor.
+    fn hover(&self, offset: usize) -> Value {
+        match self.word_at(offset).and_then(|name| self.type_store.get(name).map(|mutability| describe(name, mutability))) {
+            Some(description) => json!({ "contents": { "kind": "markdown", "value": format!("```hlwnpa\n{}\n```", description) } }),
+            None => Value::Null,
+        }
+    }
+
+    /// Lists the keywords and globals that start with the word before the cursor, or the fields of a struct after a `.`.
+    fn completion(&self, offset: usize) -> Value {
+        let line_start: usize = self.source[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let before_cursor: &str = &self.source[line_start..offset];
+        let start: usize = word_start(before_cursor);
+        let prefix: &str = &before_cursor[start..];
+
+        let mut items: Vec<(String, u32, Option<String>)> = vec![];
+        if before_cursor[..start].ends_with('.') {
+            let receiver: &str = &before_cursor[word_start(&before_cursor[..start - 1])..start - 1];
+            let receiver_fields: Option<Vec<&String>> = match self.type_store.get(receiver).map(|mutability| mutability.clone().get_type()) {
+                Some(TypeInfo::StructType { ref identifier }) => self.type_store.get(identifier).and_then(struct_fields),
+                _ => None,
+            };
+            let fields: Vec<&String> = receiver_fields.unwrap_or_else(|| {
+                self.type_store.values().filter_map(struct_fields).flatten().collect()
+            });
+            items.extend(fields.into_iter().map(|field| (field.clone(), FIELD_COMPLETION, None)));
+        } else {
+            items.extend(KEYWORDS.iter().map(|keyword| (keyword.to_string(), KEYWORD_COMPLETION, None)));
+            for (name, mutability) in &self.type_store {
+                let kind: u32 = match mutability.clone().get_type() {
+                    TypeInfo::Function { .. } | TypeInfo::BuiltinFunction(_) => FUNCTION_COMPLETION,
+                    TypeInfo::Struct { .. } => STRUCT_COMPLETION,
+                    _ => VARIABLE_COMPLETION,
+                };
+                items.push((name.clone(), kind, Some(describe(name, mutability))));
+            }
+        }
+        items.retain(|item| item.0.starts_with(prefix));
+        items.sort();
+        items.dedup();
+        Value::Array(items.into_iter()
+            .map(|(label, kind, detail)| match detail {
+                Some(detail) => json!({ "label": label, "kind": kind, "detail": detail }),
+                None => json!({ "label": label, "kind": kind }),
+            })
+            .collect())
+    }
+
+    /// The identifier that the cursor is in, or just after.
+    fn word_at(&self, offset: usize) -> Option<&str> {
+        let start: usize = word_start(&self.source[..offset]);
+        let end: usize = offset + self.source[offset..].find(|character: char| !is_identifier_character(character))
+            .unwrap_or(self.source.len() - offset);
+        if start == end { None } else { Some(&self.source[start..end]) }
+    }
+
+    /// Converts a position in the protocol, where characters are counted in UTF-16, to a byte offset.
+    fn offset(&self, line: usize, character: usize) -> usize {
+        let line_start: usize = self.source.split('\n').take(line).map(|line| line.len() + 1).sum::<usize>().min(self.source.len());
+        let mut utf16_count: usize = 0;
+        for (index, c) in self.source[line_start..].char_indices() {
+            if utf16_count >= character || c == '\n' {
+                return line_start + index;
+            }
+            utf16_count += c.len_utf16();
+        }
+        self.source.len()
+    }
+
+    fn position(&self, offset: usize) -> Value {
+        let before: &str = &self.source[..offset];
+        let line_start: usize = before.rfind('\n').map_or(0, |index| index + 1);
+        json!({ "line": before.matches('\n').count(), "character": before[line_start..].encode_utf16().count() })
+    }
+
+    fn range(&self, start: usize, end: usize) -> Value {
+        json!({ "start": self.position(start), "end": self.position(end) })
+    }
+}
+
+/// The indices of the statements in the order they are evaluated, with the functions and structs hoisted to the top.
+fn evaluation_order(statements: &[SourceStatement]) -> Vec<usize> {
+    let asts: Vec<Ast> = statements.iter().map(|statement| statement.ast.clone()).collect();
+    let hoisted: Vec<Ast> = match Ast::ExpressionList(asts.clone()).hoist_functions_and_structs() {
+        Ast::ExpressionList(hoisted) => hoisted,
+        _ => asts.clone(),
+    };
+    let mut placed: Vec<bool> = vec![false; asts.len()];
+    hoisted.iter()
+        .filter_map(|ast| {
+            let index: usize = (0..asts.len()).find(|&index| !placed[index] && asts[index] == *ast)?;
+            placed[index] = true;
+            Some(index)
+        })
+        .collect()
+}
+
+/// Shows a global the way it would be declared.
+fn describe(name: &str, mutability: &Mutability) -> String {
+    match mutability.clone().get_type() {
+        TypeInfo::Function { ref parameters, ref return_type } => {
+            let parameters: Vec<String> = parameters.iter().map(|parameter| parameter.to_string()).collect();
+            format!("fn {}({}) -> {}", name, parameters.join(", "), return_type)
+        }
+        TypeInfo::BuiltinFunction(_) => format!("fn {}", name),
+        ref struct_type @ TypeInfo::Struct { .. } => format!("struct {} {}", name, struct_type),
+        ref type_info => match *mutability {
+            Mutability::Mutable(_) => format!("let {}: {}", name, type_info),
+            Mutability::Immutable(_) => format!("const {}: {}", name, type_info),
+        },
+    }
+}
+
+fn struct_fields(mutability: &Mutability) -> Option<Vec<&String>> {
+    match *mutability {
+        Mutability::Mutable(TypeInfo::Struct { ref map }) | Mutability::Immutable(TypeInfo::Struct { ref map }) => Some(map.keys().collect()),
+        _ => None,
+    }
+}
+
+fn is_identifier_character(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+/// The start of the identifier that the text ends with.
+fn word_start(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .take_while(|&(_, character)| is_identifier_character(character))
+        .last()
+        .map_or(text.len(), |(index, _)| index)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    const URI: &str = "file:///project/main.hlw";
+
+    /// Sends the messages to a server, and returns the messages it sent back.
+    fn run_script(messages: Vec<Value>) -> Vec<Value> {
+        run_script_with_status(messages).0
+    }
+
+    /// Serves the messages, producing the replies and the status the server would exit with.
+    fn run_script_with_status(messages: Vec<Value>) -> (Vec<Value>, i32) {
+        let mut input: Vec<u8> = vec![];
+        for message in messages {
+            write_message(&mut input, &message).unwrap();
+        }
+        let mut output: Vec<u8> = vec![];
+        let status: i32 = serve(Cursor::new(input), &mut output).unwrap();
+        let mut output: Cursor<Vec<u8>> = Cursor::new(output);
+        let mut replies: Vec<Value> = vec![];
+        while let Some(reply) = read_reply(&mut output) {
+            replies.push(reply);
+        }
+        (replies, status)
+    }
+
+    fn read_reply(output: &mut Cursor<Vec<u8>>) -> Option<Value> {
+        match read_message(output).unwrap() {
+            Some(Incoming::Message(reply)) => Some(reply),
+            Some(Incoming::TooLong(length)) => panic!("The server sent a message {} bytes long", length),
+            None => None,
+        }
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn open(text: &str) -> Value {
+        notification("textDocument/didOpen", json!({ "textDocument": { "uri": URI, "languageId": "hlwnpa", "version": 1, "text": text } }))
+    }
+
+    fn at(id: u64, method: &str, line: u64, character: u64) -> Value {
+        request(id, method, json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } }))
+    }
+
+    const PROGRAM: &str = "\
+struct Point {
+    x: Number
+    y: Number
+}
+
+fn length(p: Point) -> Number {
+    p.x + p.y
+}
+
+let origin := new Point { x: 1 y: 2 }
+length(origin)
+origin.
+";
+
+    #[test]
+    fn answers_requests_about_a_document() {
+        let replies: Vec<Value> = run_script(vec![
+            request(1, "initialize", json!({ "capabilities": {} })),
+            notification("initialized", json!({})),
+            open(PROGRAM),
+            at(2, "textDocument/definition", 10, 2),
+            at(3, "textDocument/hover", 10, 2),
+            at(4, "textDocument/hover", 9, 6),
+            at(5, "textDocument/completion", 10, 3),
+            at(6, "textDocument/completion", 11, 7),
+            request(7, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+            request(8, "shutdown", Value::Null),
+        ]);
+        assert_eq!(8, replies.len());
+        assert_eq!(json!(true), replies[0]["result"]["capabilities"]["hoverProvider"]);
+        // The unfinished field access on the last line can't be checked.
+        assert_eq!(json!(11), replies[1]["params"]["diagnostics"][0]["range"]["start"]["line"]);
+        assert_eq!(
+            json!({ "uri": URI, "range": { "start": { "line": 5, "character": 3 }, "end": { "line": 5, "character": 9 } } }),
+            replies[2]["result"]
+        );
+        assert_eq!(json!("```hlwnpa\nfn length(Point) -> Number\n```"), replies[3]["result"]["contents"]["value"]);
+        assert_eq!(json!("```hlwnpa\nlet origin: Point\n```"), replies[4]["result"]["contents"]["value"]);
+        let labels: Vec<&str> = replies[5]["result"].as_array().unwrap().iter().map(|item| item["label"].as_str().unwrap()).collect();
+        assert_eq!(vec!["len", "length"], labels);
+        assert_eq!(json!([{ "label": "x", "kind": 5 }, { "label": "y", "kind": 5 }]), replies[6]["result"]);
+        assert_eq!(json!({ "jsonrpc": "2.0", "id": 7, "result": null }), replies[7]);
+    }
+
+    #[test]
+    fn exits_with_a_failure_unless_shut_down_first() {
+        let (replies, status) = run_script_with_status(vec![
+            request(1, "shutdown", Value::Null),
+            request(2, "textDocument/hover", json!({ "textDocument": { "uri": URI }, "position": { "line": 0, "character": 0 } })),
+            open("let a := 1"),
+            notification("exit", Value::Null),
+        ]);
+        assert_eq!(0, status);
+        assert_eq!(2, replies.len());
+        assert_eq!(json!({ "jsonrpc": "2.0", "id": 1, "result": null }), replies[0]);
+        assert_eq!(json!(INVALID_REQUEST), replies[1]["error"]["code"]);
+        assert_eq!(json!(2), replies[1]["id"]);
+
+        let (_, status) = run_script_with_status(vec![notification("exit", Value::Null)]);
+        assert_eq!(1, status);
+        let (_, status) = run_script_with_status(vec![request(1, "initialize", json!({ "capabilities": {} }))]);
+        assert_eq!(1, status);
+    }
+
+    #[test]
+    fn reports_diagnostics_where_they_are() {
+        let replies: Vec<Value> = run_script(vec![
+            open("println(\"a\\nb\")\nconst limit := 3\nlimit := 4\n) + 1\n"),
+            notification("textDocument/didChange", json!({ "textDocument": { "uri": URI, "version": 2 }, "contentChanges": [{ "text": "let x := \"a\" - 1\n" }] })),
+            notification("textDocument/didClose", json!({ "textDocument": { "uri": URI } })),
+        ]);
+        let diagnostics: &Vec<Value> = replies[0]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(2, diagnostics.len());
+        assert_eq!(json!({ "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 10 } }), diagnostics[0]["range"]);
+        assert!(diagnostics[0]["message"].as_str().unwrap().starts_with("Mutability error"));
+        // The escaped newline in the string doesn't move the error to the next line.
+        assert_eq!(json!({ "line": 3, "character": 0 }), diagnostics[1]["range"]["start"]);
+        assert!(diagnostics[1]["message"].as_str().unwrap().starts_with("Couldn't parse this"));
+
+        assert_eq!(json!("Type error: UnsupportedOperation(String, Number)"), replies[1]["params"]["diagnostics"][0]["message"]);
+        assert_eq!(json!([]), replies[2]["params"]["diagnostics"]);
+    }
+
+    #[test]
+    fn unsupported_and_invalid_messages_get_errors() {
+        let mut input: Vec<u8> = b"Content-Length: 5\r\n\r\nnope!".to_vec();
+        write_message(&mut input, &request(1, "workspace/symbol", json!({}))).unwrap();
+        let mut output: Vec<u8> = vec![];
+        serve(Cursor::new(input), &mut output).unwrap();
+        let mut output: Cursor<Vec<u8>> = Cursor::new(output);
+        assert_eq!(PARSE_ERROR, read_reply(&mut output).unwrap()["error"]["code"].as_i64().unwrap());
+        assert_eq!(METHOD_NOT_FOUND, read_reply(&mut output).unwrap()["error"]["code"].as_i64().unwrap());
+    }
+
+    #[test]
+    fn messages_that_are_too_long_are_skipped() {
+        let length: usize = MAX_MESSAGE_LENGTH + 1;
+        let mut input: Vec<u8> = format!("Content-Length: {}\r\n\r\n", length).into_bytes();
+        input.resize(input.len() + length, b' ');
+        write_message(&mut input, &request(1, "shutdown", Value::Null)).unwrap();
+        let mut output: Vec<u8> = vec![];
+        serve(Cursor::new(input), &mut output).unwrap();
+        let mut output: Cursor<Vec<u8>> = Cursor::new(output);
+        assert_eq!(INVALID_REQUEST, read_reply(&mut output).unwrap()["error"]["code"].as_i64().unwrap());
+        assert_eq!(json!(1), read_reply(&mut output).unwrap()["id"]);
+    }
+}
@@ -1,39 +1,39 @@
-//#![feature(discriminant_value)]
-#![feature(trace_macros)]
-#![feature(test)]
-#![recursion_limit="100"]
-
-#![macro_use]
-extern crate nom;
-#[cfg(test)]
-extern crate test;
+extern crate hlwnpa;
 extern crate clap;
-extern crate uuid;
-
-use clap::{Arg, App};
-use nom::IResult;
+extern crate nom;
+extern crate rustyline;
 
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::fs::OpenOptions;
+use clap::{Arg, App, ArgMatches, SubCommand};
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
 
-mod ast;
-mod parser;
 mod repl;
-mod std_functions;
-#[cfg(test)]
-mod testing;
-mod include;
-mod preprocessor;
 
-use ast::datatype::VariableStore;
-use ast::*;
+use hlwnpa::Interpreter;
+use hlwnpa::io_context::FileSystemAccess;
+use hlwnpa::test_runner;
+use hlwnpa::formatter;
+use hlwnpa::linter;
+use hlwnpa::lsp;
+use hlwnpa::debugger::Debugger;
+use hlwnpa::ast::{Datatype, RcDatatype, LangError};
 use repl::{repl, create_repl};
-use ast::lang_result::{LangResult, LangError};
-use ast::mutability::MutabilityMap;
 
-use parser::program;
-//use std_functions;
+/// Exit codes used when a program doesn't produce its own with `main`.
+const RUNTIME_ERROR_EXIT_CODE: i32 = 1;
+const PARSE_ERROR_EXIT_CODE: i32 = 2;
+const CHECK_ERROR_EXIT_CODE: i32 = 3;
+const FILE_ERROR_EXIT_CODE: i32 = 4;
+/// The largest exit code the OS keeps, which Numbers returned from `main` are limited to.
+const MAX_EXIT_CODE: i32 = 255;
+/// Exit code used when `hlwnpa test` has a failing test.
+const TEST_FAILURE_EXIT_CODE: i32 = 1;
+/// Exit code used when `hlwnpa fmt --check` finds a file that isn't formatted.
+const FORMAT_CHECK_FAILURE_EXIT_CODE: i32 = 1;
+/// Exit code used when `hlwnpa lint` has warnings.
+const LINT_WARNING_EXIT_CODE: i32 = 1;
 
 fn main() {
 
@@ -51,6 +51,28 @@ fn main() {
                 )
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("args")
+                .value_name("Args")
+                .help(
+                    "Arguments passed to `fn main(args: [String])`. Put them after `--` if they start with a dash.",
+                )
+                .requires("file")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("allow-fs")
+                .value_name("Directory")
+                .long("allow-fs")
+                .help(
+                    "Lets the program use the file builtins. With `--allow-fs=<dir>`, only files inside the directory can be used."
+                )
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .empty_values(true)
+                .global(true)
+        )
         .arg(
             Arg::with_name("repl")
                 .value_name("REPL")
@@ -61,81 +83,277 @@ fn main() {
                 .requires("file")
                 .takes_value(false)
         )
-        .get_matches();
+        .arg(
+            Arg::with_name("debug")
+                .long("debug")
+                .help(
+                    "Runs the file in a debugger that pauses before the first statement. Enter `help` when it pauses to see its commands."
+                )
+                .requires("file")
+                .conflicts_with("repl")
+        )
+        .subcommand(
+            SubCommand::with_name("test")
+                .about("Runs the functions named `test_*` that don't take any parameters, each in a fresh interpreter.")
+                .arg(
+                    Arg::with_name("path")
+                        .value_name("Path")
+                        .help("A .hlw file, or a directory to search for .hlw files.")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .about("Rewrites .hlw files in the standard formatting.")
+                .arg(
+                    Arg::with_name("path")
+                        .value_name("Path")
+                        .help("A .hlw file, or a directory to search for .hlw files.")
+                        .required(true)
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Lists the files that aren't formatted instead of rewriting them, failing if there are any.")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("Warns about likely mistakes in .hlw files, like unused variables and calls to undefined functions.")
+                .arg(
+                    Arg::with_name("path")
+                        .value_name("Path")
+                        .help("A .hlw file, or a directory to search for .hlw files.")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("lsp")
+                .about("Runs a Language Server Protocol server over stdin and stdout, for editors to check .hlw files with.")
+        )
+        .get_matches_from(allow_fs_without_value());
+
+    if let Some(test_matches) = matches.subcommand_matches("test") {
+        let path: &str = test_matches.value_of("path").expect("The path is required");
+        // clap doesn't pass global arguments given before the subcommand down to it.
+        let access_matches: &ArgMatches = if test_matches.is_present("allow-fs") { test_matches } else { &matches };
+        run_tests(path, file_system_access(access_matches));
+    }
+
+    if let Some(fmt_matches) = matches.subcommand_matches("fmt") {
+        let path: &str = fmt_matches.value_of("path").expect("The path is required");
+        format_files(path, fmt_matches.is_present("check"));
+    }
+
+    if let Some(lint_matches) = matches.subcommand_matches("lint") {
+        let path: &str = lint_matches.value_of("path").expect("The path is required");
+        lint_files(path);
+    }
+
+    if matches.subcommand_matches("lsp").is_some() {
+        let stdin = io::stdin();
+        match lsp::serve(stdin.lock(), io::stdout()) {
+            Ok(status) => process::exit(status),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(FILE_ERROR_EXIT_CODE);
+            }
+        }
+    }
 
     let repl_after_parse: bool = matches.is_present("repl");
+    let file_system: FileSystemAccess = file_system_access(&matches);
 
     match matches.value_of("file") {
         Some(filename) => {
-            // read the file into a string, parse it, and execute the resulting AST
-
-            match OpenOptions::new().read(true).open(&filename) {
-                Ok(file) => {
-                    let mut file_contents: String = String::new();
-                    let mut buf_reader = BufReader::new(&file);
-                    match buf_reader.read_to_string(&mut file_contents) {
-                        Ok(_) => {}
-                        Err(e) => eprintln!("Couldn't read the file {} because: {}", filename, e),
-                    }
+            let mut interpreter: Interpreter = Interpreter::new();
+            interpreter.set_file_system_access(file_system);
+            if repl_after_parse {
+                // Drop the user into a repl
+                match interpreter.load_file(filename) {
+                    Ok(_) => repl(&mut interpreter), // Start the REPL if the program evaluates correctly
+                    Err(e) => println!( "Couldn't load program into REPL, due to error: {:?}", e )
+                };
+            } else {
+                let arguments: Vec<String> = matches.values_of("args")
+                    .map(|values| values.map(String::from).collect())
+                    .unwrap_or_default();
+                if matches.is_present("debug") {
+                    interpreter.set_debugger(Debugger::standard());
+                }
+                let result = interpreter.eval_file_with_arguments(filename, &arguments);
+                process::exit(exit_code(&result));
+            }
+        }
+        None => create_repl(file_system), // If a file to run wasn't provided, drop the user into a REPL
+    }
+}
+
+fn file_system_access(matches: &ArgMatches) -> FileSystemAccess {
+    match matches.value_of("allow-fs") {
+        Some("") => FileSystemAccess::Unrestricted,
+        Some(directory) => FileSystemAccess::Within(PathBuf::from(directory)),
+        None => FileSystemAccess::Denied,
+    }
+}
 
-                    let preprocessed_program = preprocessor::preprocess(file_contents.as_str()); // run the preprocessor
-
-                    match program(preprocessed_program.as_bytes()) {
-                        IResult::Done(_, ast) => {
-                            let mut map: VariableStore = VariableStore::new();
-                            let mut mutability_map: MutabilityMap = MutabilityMap::new();
-                            let mut type_store: TypeStore = TypeStore::new();
-                            std_functions::add_std_functions(&mut map);
-                            let ast = ast.hoist_functions_and_structs();
-
-                            // Drop the user into a repl
-                            if repl_after_parse {
-                                match ast.evaluate(&mut map) {
-                                    Ok(_) => repl(&mut map, &mut mutability_map, &mut type_store), // Start the REPL if the program evaluates correctly
-                                    Err(e) => println!( "Couldn't load program into REPL, due to error: {:?}", e )
-                                };
-                            } else {
-                                let mut program_return_value: LangResult = Err(LangError::InitState);
-                                // Check the type info for the AST.
-                                match ast.check_types(&mut type_store) {
-                                    // If the types check out, execute the program.
-                                    Ok(_) => {
-                                        if ast.main_fn_exists() {
-                                            match ast.evaluate(&mut map) {
-                                                Ok(_) => program_return_value = ast.execute_main(&mut map),
-                                                Err(e) => {
-                                                    println!( "Couldn't call main because program failed to evaluate, due to error: {:?}", e )
-                                                }
-                                            }
-                                        } else {
-                                            // main() isn't found, just execute the statements found in the program.
-                                            program_return_value = ast.evaluate(&mut map);
-                                        }
-                                    },
-                                    // Otherwise, print the type error
-                                    Err(type_error) => {
-                                        println!("Type Error Encountered: {:?}", type_error )
-                                    }
-                                }
-
-                                match program_return_value {
-                                    Ok(ok_value) => println!("{:?}", ok_value),
-                                    Err(e) => println!("{:?}", e),
-                                }
-                            }
-
-                        }
-                        IResult::Error(e) => {
-                            eprintln!("encountered an error while parsing the file: {:?}", e)
-                        }
-                        IResult::Incomplete(i) => {
-                            eprintln!("Couldn't parse all of the file: {:?}", i)
-                        }
+/// Runs the tests and prints the report, exiting with a failure if any test failed.
+fn run_tests(path: &str, file_system: FileSystemAccess) -> ! {
+    match test_runner::run_tests(Path::new(path), &file_system) {
+        Ok(report) => {
+            print!("{}", report);
+            process::exit(if report.failed() == 0 { 0 } else { TEST_FAILURE_EXIT_CODE });
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(FILE_ERROR_EXIT_CODE);
+        }
+    }
+}
+
+/// Formats the files, printing the ones that changed, or with `check` the ones that would change.
+/// Exits with a failure if a file couldn't be formatted, or if `check` found an unformatted file.
+fn format_files(path: &str, check: bool) -> ! {
+    let files: Vec<PathBuf> = match test_runner::source_files(Path::new(path)) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(FILE_ERROR_EXIT_CODE);
+        }
+    };
+    let mut unformatted: bool = false;
+    let mut failed: bool = false;
+    for file in files {
+        match formatter::format_file(&file, check) {
+            Ok(true) if check => {
+                println!("{} isn't formatted", file.display());
+                unformatted = true;
+            }
+            Ok(true) => println!("Formatted {}", file.display()),
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("{}", e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(FILE_ERROR_EXIT_CODE);
+    }
+    process::exit(if unformatted { FORMAT_CHECK_FAILURE_EXIT_CODE } else { 0 });
+}
+
+/// Prints the warnings for each file, exiting with a failure if there were any.
+fn lint_files(path: &str) -> ! {
+    let files: Vec<PathBuf> = match test_runner::source_files(Path::new(path)) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(FILE_ERROR_EXIT_CODE);
+        }
+    };
+    let mut warned: bool = false;
+    let mut failed: bool = false;
+    for file in files {
+        match linter::lint_file(&file) {
+            Ok(warnings) => {
+                for warning in &warnings {
+                    match warning.line {
+                        Some(line) => println!("{}:{}: {}", file.display(), line, warning),
+                        None => println!("{}: {}", file.display(), warning),
                     }
                 }
-                Err(e) => eprintln!("Couldn't open file because: {}", e),
+                warned |= !warnings.is_empty();
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(FILE_ERROR_EXIT_CODE);
+    }
+    process::exit(if warned { LINT_WARNING_EXIT_CODE } else { 0 });
+}
+
+/// Gets the command line arguments, with a bare `--allow-fs` written as `--allow-fs=`.
+/// Otherwise clap would take the argument after the flag, usually the file, as its directory.
+/// Arguments after `--` belong to the program, so they are left alone.
+fn allow_fs_without_value() -> Vec<String> {
+    let mut passed_separator: bool = false;
+    env::args()
+        .map(|argument| {
+            passed_separator |= argument == "--";
+            if !passed_separator && argument == "--allow-fs" {
+                "--allow-fs=".to_string()
+            } else {
+                argument
             }
+        })
+        .collect()
+}
+
+/// Reports the result of running a file, along with whether `main` was called, and determines the exit code for it.
+/// A Number returned from `main` becomes the exit code, other values exit successfully.
+/// The OS only keeps the lowest 8 bits of the code, so Numbers outside 0 to 255 exit with 255 instead of being
+/// silently truncated, where 256 would look like success.
+fn exit_code(result: &Result<(RcDatatype, bool), LangError>) -> i32 {
+    match *result {
+        Ok((ref ok_value, main_called)) => {
+            println!("{}", ok_value.representation());
+            match **ok_value {
+                Datatype::Number(code) if main_called => if (0..=MAX_EXIT_CODE).contains(&code) { code } else { MAX_EXIT_CODE },
+                _ => 0,
+            }
+        }
+        Err(LangError::StoppedByDebugger) => RUNTIME_ERROR_EXIT_CODE, // The user already knows why it stopped.
+        Err(LangError::NewTypeError(ref type_error)) => {
+            eprintln!("Type Error Encountered: {:?}", type_error);
+            CHECK_ERROR_EXIT_CODE
+        }
+        Err(ref e @ LangError::MutabilityRulesViolated(_)) => {
+            eprintln!("{:?}", e);
+            CHECK_ERROR_EXIT_CODE
+        }
+        Err(ref e @ LangError::CouldNotParseFile { .. }) |
+        Err(ref e @ LangError::InvalidSyntax(_)) => {
+            eprintln!("{:?}", e);
+            PARSE_ERROR_EXIT_CODE
         }
-        None => create_repl(), // If a file to run wasn't provided, drop the user into a REPL
+        Err(ref e @ LangError::CouldNotReadFile { .. }) => {
+            eprintln!("{:?}", e);
+            FILE_ERROR_EXIT_CODE
+        }
+        Err(ref e) => {
+            eprintln!("{:?}", e);
+            RUNTIME_ERROR_EXIT_CODE
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use std::rc::Rc;
+
+    #[test]
+    fn numbers_from_main_become_the_exit_code() {
+        assert_eq!(3, exit_code(&Ok((Rc::new(Datatype::Number(3)), true))));
+        assert_eq!(MAX_EXIT_CODE, exit_code(&Ok((Rc::new(Datatype::Number(256)), true))));
+        assert_eq!(MAX_EXIT_CODE, exit_code(&Ok((Rc::new(Datatype::Number(-1)), true))));
+        // Without main, the last value of the file is only printed.
+        assert_eq!(0, exit_code(&Ok((Rc::new(Datatype::Number(3)), false))));
+    }
+
+    #[test]
+    fn files_that_only_partly_parse_exit_with_a_parse_error() {
+        let filename: PathBuf = env::temp_dir().join(format!("hlwnpa_partial_parse_test_{}.hlw", process::id()));
+        fs::write(&filename, "let a := 4\na (((").unwrap();
+        let result = Interpreter::new().eval_file_with_arguments(filename.to_str().unwrap(), &[]);
+        fs::remove_file(&filename).unwrap();
+        assert_eq!(PARSE_ERROR_EXIT_CODE, exit_code(&result));
     }
 }
@@ -5,14 +5,16 @@ use s_expression::SExpression;
 use parser::identifier::identifier;
 use parser::type_signature::type_signature;
 use parser::expressions::sexpr;
+use ast::TypeInfo;
 
 named!(let_declaration<Ast>,
     do_parse!(
         ws!(tag!("let")) >>
         id: ws!(identifier) >>
+        type_info: opt!(type_annotation) >>
         ws!(tag!(":="))>>
         value: sexpr >>
-        (Ast::SExpr(SExpression::VariableDeclaration{identifier: Box::new(id), ast: Box::new(value) }))
+        (Ast::SExpr(SExpression::VariableDeclaration{identifier: Box::new(annotate_identifier(id, type_info)), ast: Box::new(value) }))
     )
 );
 
@@ -20,12 +22,32 @@ named!(const_declaration<Ast>,
     do_parse!(
         ws!(tag!("const")) >>
         id: ws!(identifier) >>
+        type_info: opt!(type_annotation) >>
         ws!(tag!(":="))>>
         value: sexpr >>
-        (Ast::SExpr(SExpression::ConstDeclaration{identifier: Box::new(id), ast: Box::new(value) }))
+        (Ast::SExpr(SExpression::ConstDeclaration{identifier: Box::new(annotate_identifier(id, type_info)), ast: Box::new(value) }))
     )
 );
 
+/// The optional type in a declaration like `let a: Number := 5`
+named!(type_annotation<TypeInfo>,
+    complete!(preceded!(
+        ws!(tag!(":")),
+        ws!(type_signature)
+    ))
+);
+
+/// If a type is specified for a declaration, the identifier is wrapped in a TypeAssignment.
+fn annotate_identifier(identifier: Ast, type_info: Option<TypeInfo>) -> Ast {
+    match type_info {
+        Some(type_info) => Ast::SExpr(SExpression::TypeAssignment {
+            identifier: Box::new(identifier),
+            type_info: Box::new(Ast::Type(type_info))
+        }),
+        None => identifier
+    }
+}
+
 named!(pub declaration<Ast>,
     alt!(let_declaration | const_declaration)
 );
@@ -46,7 +68,7 @@ named!(pub struct_value_assignment<Ast>,
     do_parse!(
         id: identifier >>
         tag!(":") >>
-        value: sexpr >>
+        value: ws!(sexpr) >>
         (Ast::SExpr(SExpression::FieldAssignment{identifier: Box::new(id), ast: Box::new(value) }))
     )
 );
@@ -91,4 +113,41 @@ mod test {
             value
         )
     }
+
+    #[test]
+    fn parse_annotated_declaration_of_empty_array_test() {
+        let input_string = "let xs: [Number] := []";
+        let (_, value) = match declaration(input_string.as_bytes()) {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::VariableDeclaration {
+                identifier: Box::new(Ast::SExpr(SExpression::TypeAssignment {
+                    identifier: Box::new(Ast::ValueIdentifier("xs".to_string())),
+                    type_info: Box::new(Ast::Type(TypeInfo::Array(Box::new(TypeInfo::Number)))),
+                })),
+                ast: Box::new(Ast::Literal(Datatype::Array { value: vec![], type_: TypeInfo::Any })),
+            }),
+            value
+        )
+    }
+
+    #[test]
+    fn parse_struct_value_assignment_of_negated_value_test() {
+        let input_string = "y: -4";
+        let (_, value) = match struct_value_assignment(input_string.as_bytes()) {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::FieldAssignment {
+                identifier: Box::new(Ast::ValueIdentifier("y".to_string())),
+                ast: Box::new(Ast::SExpr(SExpression::Negate(Box::new(Ast::Literal(Datatype::Number(4)))))),
+            }),
+            value
+        )
+    }
 }
@@ -4,13 +4,19 @@ use ast::Ast;
 use parser::assignment::{type_assignment, struct_value_assignment, declaration};
 use parser::control_flow::control_flow;
 use parser::expressions::sexpr;
+use parser::located::located;
+
+// One of the statements in a block.
+named!(block_statement<Ast>,
+    alt_complete!(sexpr | control_flow | declaration)
+);
 
 #[cfg(not(feature = "polite"))]
 named!(pub body<Ast>,
     do_parse!(
         statements : delimited!(
             ws!(char!('{')),
-            many0!(ws!(alt_complete!(sexpr| control_flow | declaration ))), // consider making a ; terminate an expression // Also, multiple ast types are valuable here. define a matcher for those. //todo: should be many1
+            many0!(ws!(call!(located, block_statement))), // consider making a ; terminate an expression // Also, multiple ast types are valuable here. define a matcher for those. //todo: should be many1
             ws!(char!('}'))
         ) >>
         (Ast::ExpressionList( statements ))
@@ -23,7 +29,7 @@ named!(pub body<Ast>,
     do_parse!(
         statements : delimited!(
             ws!(alt!(tag!("please") | tag!("{"))),
-            many0!(ws!(alt_complete!( sexpr | control_flow | declaration ))), // consider making a ; terminate an expression // Also, multiple ast types are valuable here. define a matcher for those. //todo: should be many1
+            many0!(ws!(call!(located, block_statement))), // consider making a ; terminate an expression // Also, multiple ast types are valuable here. define a matcher for those. //todo: should be many1
             ws!(alt!(tag!("thankyou") | tag!("}")))
         ) >>
 
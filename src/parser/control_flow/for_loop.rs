@@ -22,6 +22,9 @@ named!(pub for_loop<Ast>,
     )
 );
 
+/// The loop is desugared into a while loop that walks an index over the array.
+/// The index, length, and array are stored in variables declared with unique names,
+/// and the loop variable is declared on each iteration, so they can be type and mutability checked like any other variable.
 fn create_for_loop(identifier: Ast, array: Ast, for_body: Ast) -> Ast {
     // Create a unique value to hold the index that should never collide if this is called repeatedly.
     let index_uuid: String = Uuid::new(UuidVersion::Random).unwrap().hyphenated().to_string();
@@ -31,13 +34,13 @@ fn create_for_loop(identifier: Ast, array: Ast, for_body: Ast) -> Ast {
     match array {
         Ast::ValueIdentifier(array_id) => {
             Ast::ExpressionList(vec![
-                Ast::SExpr(SExpression::Assignment {
+                Ast::SExpr(SExpression::VariableDeclaration {
                     identifier: Box::new(Ast::ValueIdentifier(index_uuid.clone())),
                     ast: Box::new(Ast::Literal(Datatype::Number(0))) // 0 index
                 }),
 
 
-                Ast::SExpr(SExpression::Assignment {
+                Ast::SExpr(SExpression::VariableDeclaration {
                     identifier: Box::new(Ast::ValueIdentifier(length_uuid.clone())),
                     ast: Box::new(Ast::SExpr(SExpression::GetArrayLength(Box::new(Ast::ValueIdentifier(array_id.clone())))))
                 }),
@@ -47,7 +50,7 @@ fn create_for_loop(identifier: Ast, array: Ast, for_body: Ast) -> Ast {
                         Box::new( Ast::ValueIdentifier(length_uuid))
                     ) )),
                     body: Box::new(Ast::ExpressionList(vec![
-                        Ast::SExpr(SExpression::Assignment {
+                        Ast::SExpr(SExpression::VariableDeclaration {
                             identifier: Box::new(identifier),
                             ast: Box::new(Ast::SExpr(SExpression::AccessArray {
                                 identifier: Box::new(Ast::ValueIdentifier(array_id)), // Because we already have an identifier, we can just use it here. No need to declare another variable earlier
@@ -71,7 +74,7 @@ fn create_for_loop(identifier: Ast, array: Ast, for_body: Ast) -> Ast {
 
             let length_uuid: String = Uuid::new(UuidVersion::Random).unwrap().hyphenated().to_string();
             Ast::ExpressionList(vec![
-                Ast::SExpr(SExpression::Assignment {
+                Ast::SExpr(SExpression::VariableDeclaration {
                     identifier: Box::new(Ast::ValueIdentifier(index_uuid.clone())),
                     ast: Box::new(Ast::Literal(Datatype::Number(0))) // 0 index
                 }),
@@ -79,12 +82,12 @@ fn create_for_loop(identifier: Ast, array: Ast, for_body: Ast) -> Ast {
                 // (if we initialize an array then iterate through it, we only create it once,
                 // instead of creating a new array for every loop iteration like a prior implementation in all cases except
                 // you guessed it, by accessing it through an id.)
-                Ast::SExpr(SExpression::Assignment {
+                Ast::SExpr(SExpression::VariableDeclaration {
                     identifier: Box::new(Ast::ValueIdentifier(array_uuid.clone())),
                     ast: Box::new(array)
                 }),
 
-                Ast::SExpr(SExpression::Assignment {
+                Ast::SExpr(SExpression::VariableDeclaration {
                     identifier: Box::new(Ast::ValueIdentifier(length_uuid.clone())),
                     ast: Box::new(Ast::SExpr(SExpression::GetArrayLength(Box::new(Ast::ValueIdentifier(array_uuid.clone())))))
                 }),
@@ -95,7 +98,7 @@ fn create_for_loop(identifier: Ast, array: Ast, for_body: Ast) -> Ast {
                         Box::new(Ast::ValueIdentifier(length_uuid))
                     ) )),
                     body: Box::new(Ast::ExpressionList(vec![
-                        Ast::SExpr(SExpression::Assignment {
+                        Ast::SExpr(SExpression::VariableDeclaration {
                             identifier: Box::new(identifier),
                             ast: Box::new(Ast::SExpr(SExpression::AccessArray {
                                 identifier: Box::new(Ast::ValueIdentifier(array_uuid)), // Use the identifier to the initialized array that was created earlier
@@ -7,10 +7,13 @@ pub use self::while_loop::while_loop;
 mod for_loop;
 use self::for_loop::for_loop;
 
+mod try_catch;
+use self::try_catch::try_catch;
+
 #[allow(unused_imports)]
 use nom::*;
 use ast::Ast;
 
 named!(pub control_flow<Ast>,
-    ws!(alt!(while_loop | if_expression | for_loop))
+    ws!(alt!(while_loop | if_expression | for_loop | try_catch))
 );
@@ -0,0 +1,48 @@
+#[allow(unused_imports)]
+use nom::*;
+use ast::Ast;
+use s_expression::SExpression;
+use parser::body::body;
+use parser::identifier::identifier;
+use std::boxed::Box;
+
+named!(pub try_catch<Ast>,
+    do_parse!(
+        ws!(tag!("try")) >>
+        try_body: ws!(body) >>
+        ws!(tag!("catch")) >>
+        error_identifier: ws!(identifier) >>
+        catch_body: ws!(body) >>
+
+        (Ast::SExpr(SExpression::TryCatch {
+            body: Box::new(try_body),
+            error_identifier: Box::new(error_identifier),
+            handler: Box::new(catch_body)
+        }))
+    )
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use datatype::Datatype;
+
+    #[test]
+    fn parse_try_catch_test() {
+        let input_string = "try { 1 } catch e { 2 }";
+        let (_, value) = match try_catch(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("Error in parsing: {}", e),
+            IResult::Incomplete(i) => panic!("Incomplete parse: {:?}", i),
+        };
+
+        assert_eq!(
+            Ast::SExpr(SExpression::TryCatch {
+                body: Box::new(Ast::ExpressionList(vec![Ast::Literal(Datatype::Number(1))])),
+                error_identifier: Box::new(Ast::ValueIdentifier("e".to_string())),
+                handler: Box::new(Ast::ExpressionList(vec![Ast::Literal(Datatype::Number(2))])),
+            }),
+            value
+        )
+    }
+}
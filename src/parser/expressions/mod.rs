@@ -22,23 +22,24 @@ named!(pub sexpr<Ast>,
 );
 
 /// Grab the righthand side
+/// `++` and `--` are tried first, otherwise `x--` followed by a line starting with `y` would be read as `x - -y`.
 named!(op_and_rhs<(Operator, Option<Ast>)>,
     alt_complete!(
+        do_parse!(
+            op: arithmetic_unary_operator >>
+            ((op, None))
+        ) |
         do_parse!(
             op: arithmetic_binary_operator >>
             rhs: no_keyword_token_group >>
             ((op, Some(rhs)))
-        ) |
-        do_parse!(
-            op: arithmetic_unary_operator >>
-            ((op, None))
         )
     )
 );
 
 named!(pub unary_operator_and_operand<Ast>,
     do_parse!(
-        operator: alt!(negate | invert) >>
+        operator: alt!(negate | invert | bitwise_not) >>
         lhs: no_keyword_token_group >>
         (create_sexpr(operator, lhs, None))
     )
@@ -107,10 +108,11 @@ fn create_sexpr(operator: Operator, lhs: Ast, rhs: Option<Ast>) -> Ast {
             parameters: Box::new(rhs.expect("rhs should be present"))
         }),
         //Unary
-        Operator::Increment => Ast::SExpr(SExpression::Increment(Box::new(lhs))),
-        Operator::Decrement => Ast::SExpr(SExpression::Decrement(Box::new(lhs))),
+        Operator::Increment => assign_in_place(lhs.clone(), Ast::SExpr(SExpression::Increment(Box::new(lhs)))),
+        Operator::Decrement => assign_in_place(lhs.clone(), Ast::SExpr(SExpression::Decrement(Box::new(lhs)))),
         Operator::Invert => Ast::SExpr(SExpression::Invert(Box::new(lhs))),
         Operator::Negate => Ast::SExpr(SExpression::Negate(Box::new(lhs))),
+        Operator::BitwiseNot => Ast::SExpr(SExpression::BitwiseNot(Box::new(lhs))),
         //Binary
         Operator::Plus => Ast::SExpr(SExpression::Add(
             Box::new(lhs),
@@ -164,163 +166,110 @@ fn create_sexpr(operator: Operator, lhs: Ast, rhs: Option<Ast>) -> Ast {
             Box::new(lhs),
             Box::new(rhs.expect("rhs should be present")),
         )),
+        Operator::Power => Ast::SExpr(SExpression::Power(
+            Box::new(lhs),
+            Box::new(rhs.expect("rhs should be present")),
+        )),
+        Operator::BitwiseAnd => Ast::SExpr(SExpression::BitwiseAnd(
+            Box::new(lhs),
+            Box::new(rhs.expect("rhs should be present")),
+        )),
+        Operator::BitwiseOr => Ast::SExpr(SExpression::BitwiseOr(
+            Box::new(lhs),
+            Box::new(rhs.expect("rhs should be present")),
+        )),
+        Operator::BitwiseXor => Ast::SExpr(SExpression::BitwiseXor(
+            Box::new(lhs),
+            Box::new(rhs.expect("rhs should be present")),
+        )),
+        Operator::ShiftLeft => Ast::SExpr(SExpression::ShiftLeft(
+            Box::new(lhs),
+            Box::new(rhs.expect("rhs should be present")),
+        )),
+        Operator::ShiftRight => Ast::SExpr(SExpression::ShiftRight(
+            Box::new(lhs),
+            Box::new(rhs.expect("rhs should be present")),
+        )),
         Operator::Assignment => Ast::SExpr(SExpression::Assignment {
             identifier: Box::new(lhs),
             ast: Box::new(rhs.expect("rhs should be present")),
-        })
+        }),
+        // Compound assignments desugar to an assignment of the operation, so `x += 1` becomes `x := x + 1`.
+        Operator::AddAssign => compound_assignment(Operator::Plus, lhs, rhs),
+        Operator::SubtractAssign => compound_assignment(Operator::Minus, lhs, rhs),
+        Operator::MultiplyAssign => compound_assignment(Operator::Times, lhs, rhs),
+        Operator::DivideAssign => compound_assignment(Operator::Divide, lhs, rhs),
+        Operator::ModuloAssign => compound_assignment(Operator::Modulo, lhs, rhs),
     }
 }
 
-/// When creating left-aligned groups, it is necessary to reuse the most recent state of the LHS,
-/// so the RHS of that old LHS can be replaced.
-/// This method if given that LHS, will deconstruct it into its component parts so they can be used construct a new grouping.
-fn retrieve_operator_and_operands(
-    ast: &Ast,
-) -> Result<(Option<Operator>, Ast, Option<Ast>), String> {
-    match *ast {
-        Ast::SExpr(ref sexpr) => {
-            match *sexpr {
-                SExpression::AccessArray {ref identifier, ref index } => Ok((
-                    Some(Operator::ArrayAccess),
-                    *identifier.clone(),
-                    Some(*index.clone())
-                )),
-                SExpression::AccessStructField {ref identifier, ref field_identifier } => Ok((
-                    Some(Operator::StructAccess),
-                    *identifier.clone(),
-                    Some(*field_identifier.clone())
-                )),
-                SExpression::ExecuteFn {ref identifier, ref parameters } => Ok((
-                    Some(Operator::ExecuteFunction),
-                    *identifier.clone(),
-                    Some(*parameters.clone())
-                )),
-                SExpression::Multiply(ref lhs, ref rhs) => Ok((
-                    Some(Operator::Times),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::Divide(ref lhs, ref rhs) => Ok((
-                    Some(Operator::Divide),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::Modulo(ref lhs, ref rhs) => Ok((
-                    Some(Operator::Modulo),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::Add(ref lhs, ref rhs) => Ok((
-                    Some(Operator::Plus),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::Subtract(ref lhs, ref rhs) => Ok((
-                    Some(Operator::Minus),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::Equals(ref lhs, ref rhs) => Ok((
-                    Some(Operator::Equals),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::NotEquals(ref lhs, ref rhs) => Ok((
-                    Some(Operator::NotEquals),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::GreaterThan(ref lhs, ref rhs) => Ok((
-                    Some(Operator::GreaterThan),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::LessThan(ref lhs, ref rhs) => Ok((
-                    Some(Operator::LessThan),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::GreaterThanOrEqual(ref lhs, ref rhs) => Ok((
-                    Some(
-                        Operator::GreaterThanOrEqual,
-                    ),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::LessThanOrEqual(ref lhs, ref rhs) => Ok((
-                    Some(
-                        Operator::LessThanOrEqual,
-                    ),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::LogicalAnd(ref lhs, ref rhs) => Ok((
-                    Some(Operator::LogicalAnd),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::LogicalOr(ref lhs, ref rhs) => Ok((
-                    Some(Operator::LogicalOr),
-                    *lhs.clone(),
-                    Some(*rhs.clone()),
-                )),
-                SExpression::Invert(ref lhs) => {
-                    (Ok((Some(Operator::Invert), *lhs.clone(), None)))
-                }
-                SExpression::Negate(ref lhs) => {
-                    (Ok((Some(Operator::Negate), *lhs.clone(), None)))
-                },
-                SExpression::Increment(ref lhs) => {
-                    (Ok((Some(Operator::Increment), *lhs.clone(), None)))
-                }
-                SExpression::Decrement(ref lhs) => {
-                    (Ok((Some(Operator::Decrement), *lhs.clone(), None)))
-                }
-                SExpression::Assignment {ref identifier, ref ast } => {
-                    Ok((Some(Operator::Assignment), *identifier.clone(), Some(*ast.clone())))
-                }
-                _ => (Err("Unsupported SExpression".to_string())),
-            }
-        }
-//        Ast::Literal(ref literal_dt) => Ok((None, Ast::Literal(literal_dt.clone()), None)),
-        _ => {
-            (Err(
-                "Ast isn't an supported when assigning precedence".to_string(),
-            ))
-        }
-    }
+fn compound_assignment(operator: Operator, lhs: Ast, rhs: Option<Ast>) -> Ast {
+    Ast::SExpr(SExpression::Assignment {
+        identifier: Box::new(lhs.clone()),
+        ast: Box::new(create_sexpr(operator, lhs, rhs)),
+    })
 }
 
+/// `++` and `--` write their result back to the variable they were applied to.
+/// Array elements and struct fields become assignments too, so the mutability checker rejects them like it does `a[0] += 1`,
+/// instead of them silently leaving the element unchanged.
+/// If they are applied to a value, like `3++`, the result is just returned.
+fn assign_in_place(lhs: Ast, operation: Ast) -> Ast {
+    match lhs {
+        Ast::ValueIdentifier(_) |
+        Ast::SExpr(SExpression::AccessArray { .. }) |
+        Ast::SExpr(SExpression::AccessStructField { .. }) => Ast::SExpr(SExpression::Assignment {
+            identifier: Box::new(lhs),
+            ast: Box::new(operation),
+        }),
+        _ => operation
+    }
+}
 
+/// Builds a tree out of the flat list of operators and operands parsed by `sexpr`.
+///
+/// Postfix operators (array access, struct access, function calls, `++` and `--`) are applied to
+/// the operand immediately preceding them first.
+/// The remaining binary operators are then grouped according to their precedence using the shunting-yard algorithm,
+/// where a lower precedence value indicates that the operator binds more tightly.
+/// Operators of equal precedence group towards the left, unless they are right associative.
 fn group_sexpr_by_precedence(lhs: Ast, rhss: Vec<(Operator, Option<Ast>)>) -> Ast {
-    let mut lhs = lhs;
-    let mut previous_op_value: u32 = 0;
-    for op_and_rhs in rhss {
-        let (op, rhs): (Operator, Option<Ast>) = op_and_rhs;
+    let mut operands: Vec<Ast> = vec![lhs];
+    let mut operators: Vec<Operator> = vec![];
+
+    for (op, rhs) in rhss {
+        if op.is_postfix() {
+            let operand = operands.pop().expect("There should always be an operand preceding a postfix operator");
+            operands.push(create_sexpr(op, operand, rhs));
+            continue;
+        }
+
         let op_value: u32 = op.clone().into();
-        // the a lower value indicates it has more precedence.
-        if op_value < previous_op_value {
-            let (old_operator, old_lhs, old_rhs) = retrieve_operator_and_operands(&lhs).expect(format!("Couldn't retrieve operator and operands for a given lhs: {:?}", lhs).as_str()); // TODO possibly bad unwrap
-            match old_operator {
-                Some(old_operator) => {
-                    lhs = create_sexpr(
-                        old_operator,
-                        old_lhs,
-                        Some(create_sexpr(op, old_rhs.unwrap(), rhs)),
-                    ) // Group left
-                }
-                None => {
-                    // The lack of an operator in the old RHS indicates that this is the first loop, and we can't group left.
-                    // So just group right, because there is no difference for the first term.
-                    lhs = create_sexpr(op, lhs, rhs) // expand, grouping towards the right.
-                }
+        while let Some(stacked_op) = operators.pop() {
+            let stacked_op_value: u32 = stacked_op.clone().into();
+            if stacked_op_value < op_value || (stacked_op_value == op_value && !op.is_right_associative()) {
+                reduce(stacked_op, &mut operands);
+            } else {
+                operators.push(stacked_op);
+                break;
             }
-        } else {
-            lhs = create_sexpr(op, lhs, rhs) // expand, grouping towards the right.
         }
-        previous_op_value = op_value;
+        operators.push(op);
+        operands.push(rhs.expect("rhs should be present"));
     }
-    return lhs;
+
+    while let Some(op) = operators.pop() {
+        reduce(op, &mut operands);
+    }
+
+    operands.pop().expect("All operators should have been reduced into a single operand")
+}
+
+/// Replaces the top two operands with a single S-Expression joining them with the operator.
+fn reduce(op: Operator, operands: &mut Vec<Ast>) {
+    let rhs = operands.pop().expect("rhs should be present");
+    let lhs = operands.pop().expect("lhs should be present");
+    operands.push(create_sexpr(op, lhs, Some(rhs)));
 }
 
 
@@ -1028,4 +977,150 @@ mod test {
         assert_eq!(expected_ast, value)
     }
 
+
+    #[test]
+    fn sexpr_precedence_across_many_levels() {
+        let (_, value) = match sexpr(b"1 + 2 * 3 == 7 && true") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::LogicalAnd(
+                Box::new(Ast::SExpr(SExpression::Equals(
+                    Box::new(Ast::SExpr(SExpression::Add(
+                        Box::new(Ast::Literal(Datatype::Number(1))),
+                        Box::new(Ast::SExpr(SExpression::Multiply(
+                            Box::new(Ast::Literal(Datatype::Number(2))),
+                            Box::new(Ast::Literal(Datatype::Number(3))),
+                        ))),
+                    ))),
+                    Box::new(Ast::Literal(Datatype::Number(7))),
+                ))),
+                Box::new(Ast::Literal(Datatype::Bool(true))),
+            )),
+            value
+        );
+    }
+
+    #[test]
+    fn sexpr_power_is_right_associative() {
+        let (_, value) = match sexpr(b"2 ** 3 ** 2") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::Power(
+                Box::new(Ast::Literal(Datatype::Number(2))),
+                Box::new(Ast::SExpr(SExpression::Power(
+                    Box::new(Ast::Literal(Datatype::Number(3))),
+                    Box::new(Ast::Literal(Datatype::Number(2))),
+                ))),
+            )),
+            value
+        );
+    }
+
+    #[test]
+    fn sexpr_bitwise_precedence() {
+        let (_, value) = match sexpr(b"a | b & 1 << 2") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::BitwiseOr(
+                Box::new(Ast::ValueIdentifier("a".to_string())),
+                Box::new(Ast::SExpr(SExpression::BitwiseAnd(
+                    Box::new(Ast::ValueIdentifier("b".to_string())),
+                    Box::new(Ast::SExpr(SExpression::ShiftLeft(
+                        Box::new(Ast::Literal(Datatype::Number(1))),
+                        Box::new(Ast::Literal(Datatype::Number(2))),
+                    ))),
+                ))),
+            )),
+            value
+        );
+    }
+
+    #[test]
+    fn sexpr_bitwise_not() {
+        let (_, value) = match sexpr(b"~x ^ 3") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::BitwiseXor(
+                Box::new(Ast::SExpr(SExpression::BitwiseNot(
+                    Box::new(Ast::ValueIdentifier("x".to_string()))
+                ))),
+                Box::new(Ast::Literal(Datatype::Number(3))),
+            )),
+            value
+        );
+    }
+
+
+    #[test]
+    fn sexpr_compound_assignment() {
+        let (_, value) = match sexpr(b"x *= 2 + 1") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::Assignment {
+                identifier: Box::new(Ast::ValueIdentifier("x".to_string())),
+                ast: Box::new(Ast::SExpr(SExpression::Multiply(
+                    Box::new(Ast::ValueIdentifier("x".to_string())),
+                    Box::new(Ast::SExpr(SExpression::Add(
+                        Box::new(Ast::Literal(Datatype::Number(2))),
+                        Box::new(Ast::Literal(Datatype::Number(1))),
+                    ))),
+                ))),
+            }),
+            value
+        );
+    }
+
+    #[test]
+    fn sexpr_increment_identifier_assigns() {
+        let (_, value) = match sexpr(b"x++") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        assert_eq!(
+            Ast::SExpr(SExpression::Assignment {
+                identifier: Box::new(Ast::ValueIdentifier("x".to_string())),
+                ast: Box::new(Ast::SExpr(SExpression::Increment(
+                    Box::new(Ast::ValueIdentifier("x".to_string()))
+                ))),
+            }),
+            value
+        );
+    }
+
+    #[test]
+    fn sexpr_increment_array_element_is_an_assignment() {
+        let (_, value) = match sexpr(b"a[0]++") {
+            IResult::Done(r, v) => (r, v),
+            IResult::Error(e) => panic!("{:?}", e),
+            _ => panic!(),
+        };
+        let element = Ast::SExpr(SExpression::AccessArray {
+            identifier: Box::new(Ast::ValueIdentifier("a".to_string())),
+            index: Box::new(Ast::Literal(Datatype::Number(0))),
+        });
+        assert_eq!(
+            Ast::SExpr(SExpression::Assignment {
+                identifier: Box::new(element.clone()),
+                ast: Box::new(Ast::SExpr(SExpression::Increment(Box::new(element)))),
+            }),
+            value
+        );
+    }
+
 }
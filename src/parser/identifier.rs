@@ -4,15 +4,27 @@ use nom::*;
 use std::str;
 
 
-named!(normal_reserved_words,
-    alt!(
-        tag!("let") | tag!("const") | tag!("fn") | tag!("if") | tag!("else") |
-        tag!("for") |
-        tag!("while") | tag!("true") | tag!("false") |
-        tag!("struct") |
-        tag!("new") | tag!("include")
-    )
-);
+/// The words that can't be used as identifiers, which tools like the REPL's tab completion also offer.
+/// `normal_reserved_words` matches them in this order, so a word must come before any other word it is a prefix of.
+pub const KEYWORDS: [&str; 14] = [
+    "let", "const", "fn", "if", "else", "for", "while", "true", "false", "struct", "new", "include", "try", "catch",
+];
+
+/// Matches any of the `KEYWORDS`, like an `alt!` of a `tag!` for each of them would.
+fn normal_reserved_words(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    for keyword in KEYWORDS.iter() {
+        let keyword: &[u8] = keyword.as_bytes();
+        if input.starts_with(keyword) {
+            return IResult::Done(&input[keyword.len()..], &input[..keyword.len()]);
+        }
+        if keyword.starts_with(input) {
+            // Like `tag!`, the input could still become the keyword.
+            return IResult::Incomplete(Needed::Size(keyword.len()));
+        }
+    }
+    IResult::Error(error_position!(ErrorKind::Alt, input))
+}
+
 #[cfg(not(feature = "polite"))]
 named!( reserved_words,
     alt!(
@@ -31,7 +43,7 @@ named!( reserved_words,
 
 named!(accepted_identifier_characters<&str>,
     map_res!(
-        is_not!(" \n\t\r.(){}<>[],:;+-*/%!=\"&|"),
+        is_not!(" \n\t\r.(){}<>[],:;+-*/%!=\"&|^~"),
         str::from_utf8
     )
 );
@@ -51,6 +63,22 @@ named!(pub identifier<Ast>,
 mod test {
     use super::*;
 
+    #[test]
+    fn keywords_are_reserved_test() {
+        for keyword in KEYWORDS.iter() {
+            let input_string = format!("{} ", keyword);
+            match normal_reserved_words(input_string.as_bytes()) {
+                IResult::Done(rest, _) => assert_eq!(b" ", rest),
+                other => panic!("{} isn't reserved: {:?}", keyword, other),
+            }
+            assert!(identifier(input_string.as_bytes()).is_err(), "{} can be used as an identifier", keyword);
+        }
+        // Words that only start with a keyword are still identifiers.
+        for word in &["letter ", "format ", "trying "] {
+            assert!(identifier(word.as_bytes()).is_done(), "{} isn't an identifier", word);
+        }
+    }
+
     #[test]
     fn parse_identifier_characters_test() {
         let input_string = "name ";
@@ -1,15 +1,14 @@
 use ast::{Ast, Datatype, TypeInfo};
 #[allow(unused_imports)]
 use nom::*;
-use super::literal;
 use s_expression::SExpression;
 use parser::expressions::sexpr;
 use std::rc::Rc;
 
-pub const TYPE_MISMATCH_ERROR: u32 = 10001;
 
-
-/// Grab a list of literals delimited by [ ] and then check if their datatypes are the same
+/// Grab a list of expressions delimited by [ ].
+/// If every element is a literal of the same type, the array is created here as a literal.
+/// Otherwise the elements will be evaluated, and have their types checked, when the array is created at runtime.
 named!(pub array_literal<Ast>,
     do_parse!(
         array: delimited!(
@@ -17,55 +16,59 @@ named!(pub array_literal<Ast>,
             array_values,
             ws!(char!(']'))
         ) >>
-        (
-            Ast::Literal( Datatype::Array {
-                value: array.iter().map(|arr_member| match arr_member {
-                    &Ast::Literal(ref datatype) => Rc::new(datatype.clone()), // get the datatype out of a borrowed context
-                    _ => unreachable!() // Because the literal function defined in literal/mod.rs only returns literals, we know that this is unreachable.
-                }).collect(),
-
-                type_: match array.iter().map(|arr_member| match arr_member {
-                    &Ast::Literal(ref datatype) => return TypeInfo::from(datatype.clone()),// get the datatype out of a borrowed context return IResult::Error(ErrorKind::Custom(420)),
-                    _ => unreachable!()
-                }).fold(
-                    // initial value, this will become 'acc'
-                    Ok( TypeInfo::from(match array[0].clone()   {
-                        Ast::Literal(ref datatype) => datatype.clone(), // get the datatype out of a borrowed context
-                        _ => unreachable!() //
-                    })),
-                    // check if each element is the same
-                    |acc, x| if Ok(x) == acc {
-                        acc
-                    } else {
-                        Err("types don't match")
-                    }
-                ) {
-                    Ok(k) => k,
-                    Err(_) => return IResult::Error(ErrorKind::Custom(TYPE_MISMATCH_ERROR))
-                }
-            })
-        )
+        (create_array(array))
     )
 );
 
 named!(array_values<Vec<Ast> >,
     separated_list_complete!(
         ws!(tag!(",")),
-        literal
+        sexpr
     )
 );
 
+fn create_array(elements: Vec<Ast>) -> Ast {
+    match literal_array(&elements) {
+        Some(array) => Ast::Literal(array),
+        None => Ast::SExpr(SExpression::CreateArray(Box::new(Ast::ExpressionList(elements))))
+    }
+}
+
+/// Produces an array Datatype if all of the elements are literals that share a type.
+/// An empty array is also a literal.
+fn literal_array(elements: &[Ast]) -> Option<Datatype> {
+    let mut values: Vec<Rc<Datatype>> = vec![];
+    let mut type_: Option<TypeInfo> = None;
+    for element in elements {
+        match *element {
+            Ast::Literal(ref datatype) => {
+                let element_type = TypeInfo::from(datatype.clone());
+                type_ = match type_ {
+                    Some(type_) => Some(type_.unify(&element_type)?),
+                    None => Some(element_type),
+                };
+                values.push(Rc::new(datatype.clone()));
+            }
+            _ => return None
+        }
+    }
+    Some(Datatype::Array {
+        value: values,
+        type_: type_.unwrap_or(TypeInfo::Any) // The element type of an empty array will be inferred from how it is used.
+    })
+}
+
 /// Matches syntax like [0..10] to create an array with the first value of 0, and the last value of 10.
 named!(pub array_range<Ast>,
     delimited!(
-        char!('['),
+        ws!(char!('[')),
         do_parse!(
             start: sexpr >> // should be either a number, or function, or sexpr or an identifier that resolves to a number
             ws!(tag!("..")) >>
             end: sexpr >>
             ( Ast::SExpr( SExpression::Range{start: Box::new(start), end: Box::new(end) } ) )
         ),
-        char!(']')
+        ws!(char!(']'))
     )
 );
 
@@ -111,10 +114,56 @@ fn parse_array_bool_test() {
 }
 
 
+/// Mismatched types are rejected when the array is type checked or evaluated, rather than by the parser.
 #[test]
-fn fail_parse_array_mismatched_literal_test() {
-    let error = array_literal(b"[true, 8]");
-    assert_eq!(IResult::Error(ErrorKind::Custom(TYPE_MISMATCH_ERROR)), error );
+fn parse_array_mismatched_literal_test() {
+    let (_, value) = match array_literal(b"[true, 8]") {
+        IResult::Done(r, v) => (r, v),
+        IResult::Error(e) => panic!("{:?}", e),
+        _ => panic!(),
+    };
+    assert_eq!(Ast::SExpr(SExpression::CreateArray(Box::new(Ast::ExpressionList(vec![
+        Ast::Literal(Datatype::Bool(true)),
+        Ast::Literal(Datatype::Number(8))
+    ])))), value)
+}
+
+#[test]
+fn parse_array_of_expressions_test() {
+    let (_, value) = match array_literal(b"[a, b * 2]") {
+        IResult::Done(r, v) => (r, v),
+        IResult::Error(e) => panic!("{:?}", e),
+        _ => panic!(),
+    };
+    assert_eq!(Ast::SExpr(SExpression::CreateArray(Box::new(Ast::ExpressionList(vec![
+        Ast::ValueIdentifier("a".to_string()),
+        Ast::SExpr(SExpression::Multiply(
+            Box::new(Ast::ValueIdentifier("b".to_string())),
+            Box::new(Ast::Literal(Datatype::Number(2)))
+        ))
+    ])))), value)
+}
+
+#[test]
+fn parse_nested_array_literal_test() {
+    let (_, value) = match array_literal(b"[[1, 2], [3]]") {
+        IResult::Done(r, v) => (r, v),
+        IResult::Error(e) => panic!("{:?}", e),
+        _ => panic!(),
+    };
+    assert_eq!(Ast::Literal(Datatype::Array {
+        value: vec![
+            Rc::new(Datatype::Array {
+                value: vec![Rc::new(Datatype::Number(1)), Rc::new(Datatype::Number(2))],
+                type_: TypeInfo::Number
+            }),
+            Rc::new(Datatype::Array {
+                value: vec![Rc::new(Datatype::Number(3))],
+                type_: TypeInfo::Number
+            }),
+        ],
+        type_: TypeInfo::Array(Box::new(TypeInfo::Number))
+    }), value)
 }
 
 #[test]
@@ -148,4 +197,19 @@ fn parse_array_range() {
 
     assert_eq!(expected, value)
 
-}
\ No newline at end of file
+}
+#[test]
+fn parse_array_range_surrounded_by_whitespace() {
+    let (rest, value) = match array_range(b" [10..20]\n") {
+        IResult::Done(r, v) => (r, v),
+        IResult::Error(e) => panic!("{:?}", e),
+        _ => panic!(),
+    };
+    let expected = Ast::SExpr(SExpression::Range {
+        start: Box::new(Ast::Literal(Datatype::Number(10))),
+        end: Box::new(Ast::Literal(Datatype::Number(20)))
+    });
+
+    assert_eq!(expected, value);
+    assert!(rest.is_empty()) // The trailing whitespace is consumed like it is after other literals, so the expression can end here.
+}
@@ -0,0 +1,70 @@
+use ast::Ast;
+use locations::statement_start_lines;
+use nom::IResult;
+
+use std::cell::RefCell;
+
+/// The preprocessed source being parsed with the lines of its statements.
+struct LocatedSource {
+    /// The address of the preprocessed source, so the offset of the input to a parser can be found.
+    /// The source is borrowed for as long as this is installed, so it can't be moved or freed in the meantime.
+    start: usize,
+    /// The line at each offset of the preprocessed source, where a statement starting there would start a line.
+    lines: Vec<Option<usize>>,
+}
+
+thread_local! {
+    static LOCATED_SOURCE: RefCell<Option<LocatedSource>> = const { RefCell::new(None) };
+}
+
+/// Runs the function, which parses the preprocessed source, so each statement that starts a line of the source
+/// is wrapped in an `Ast::Located` that records the line.
+/// Statements that share a line with the one before them aren't located, so tools treat each line as a single step.
+pub fn with_lines<F, R>(source: &str, preprocessed: &str, function: F) -> R
+    where F: FnOnce() -> R
+{
+    let located: LocatedSource = LocatedSource {
+        start: preprocessed.as_ptr() as usize,
+        lines: statement_start_lines(source, preprocessed),
+    };
+    let previous: Option<LocatedSource> = LOCATED_SOURCE.with(|current| current.replace(Some(located)));
+    let _restore = RestoreLocatedSource(Some(previous));
+    function()
+}
+
+/// Puts back what was being parsed before when it is dropped, so it is restored even if parsing panics.
+struct RestoreLocatedSource(Option<Option<LocatedSource>>);
+
+impl Drop for RestoreLocatedSource {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            // The thread local may already be gone if this is dropped while the thread exits.
+            let _ = LOCATED_SOURCE.try_with(|current| *current.borrow_mut() = previous);
+        }
+    }
+}
+
+/// Parses a statement, recording the line it starts on if the source is being parsed with the lines of its statements.
+pub fn located<F>(input: &[u8], parser: F) -> IResult<&[u8], Ast>
+    where F: Fn(&[u8]) -> IResult<&[u8], Ast>
+{
+    match parser(input) {
+        IResult::Done(rest, ast) => {
+            let leading_whitespace: usize = input.iter().take_while(|byte| byte.is_ascii_whitespace()).count();
+            match line_at(input.as_ptr() as usize + leading_whitespace) {
+                Some(line) => IResult::Done(rest, Ast::Located { line, ast: Box::new(ast) }),
+                None => IResult::Done(rest, ast),
+            }
+        }
+        other => other,
+    }
+}
+
+/// The line a statement starting at the address would be on, if the address is in the source being parsed.
+fn line_at(address: usize) -> Option<usize> {
+    LOCATED_SOURCE.with(|current| {
+        current.borrow().as_ref().and_then(|located| {
+            address.checked_sub(located.start).and_then(|offset| located.lines.get(offset)).and_then(|&line| line)
+        })
+    })
+}
@@ -10,6 +10,7 @@ mod expressions;
 use self::expressions::sexpr;
 
 mod identifier;
+pub use self::identifier::KEYWORDS;
 
 mod literal;
 
@@ -25,6 +26,10 @@ use self::function::*;
 
 mod body;
 
+mod located;
+pub use self::located::with_lines;
+use self::located::located;
+
 mod control_flow;
 use self::control_flow::control_flow;
 
@@ -49,9 +54,14 @@ named!(any_ast<Ast>,
 
 
 
+// One of the statements that make up a program, for tools that need to know where each statement is.
+named!(pub statement<Ast>,
+    ws!(any_ast)
+);
+
 named!(pub program<Ast>,
     do_parse!(
-        e: many1!(ws!(any_ast)) >>
+        e: many1!(ws!(call!(located, any_ast))) >>
         (Ast::ExpressionList( e ))
     )
 );
@@ -15,6 +15,12 @@ named!(minus<Operator>,
     )
 );
 
+named!(power<Operator>,
+    value!(
+        Operator::Power,
+        tag!("**")
+    )
+);
 named!(multiply<Operator>,
      value!(
         Operator::Times,
@@ -81,6 +87,36 @@ named!(logical_or<Operator>,
         tag!("||")
     )
 );
+named!(bitwise_and<Operator>,
+    value!(
+        Operator::BitwiseAnd,
+        tag!("&")
+    )
+);
+named!(bitwise_or<Operator>,
+    value!(
+        Operator::BitwiseOr,
+        tag!("|")
+    )
+);
+named!(bitwise_xor<Operator>,
+    value!(
+        Operator::BitwiseXor,
+        tag!("^")
+    )
+);
+named!(shift_left<Operator>,
+    value!(
+        Operator::ShiftLeft,
+        tag!("<<")
+    )
+);
+named!(shift_right<Operator>,
+    value!(
+        Operator::ShiftRight,
+        tag!(">>")
+    )
+);
 
 
 named!(pub invert<Operator>,
@@ -97,6 +133,13 @@ named!(pub negate<Operator>,
     )
 );
 
+named!(pub bitwise_not<Operator>,
+    value!(
+        Operator::BitwiseNot,
+        tag!("~")
+    )
+);
+
 named!(increment<Operator>,
     value!(
         Operator::Increment,
@@ -117,11 +160,48 @@ named!(assignment<Operator>,
         tag!(":=")
     )
 );
+named!(add_assign<Operator>,
+    value!(
+        Operator::AddAssign,
+        tag!("+=")
+    )
+);
+named!(subtract_assign<Operator>,
+    value!(
+        Operator::SubtractAssign,
+        tag!("-=")
+    )
+);
+named!(multiply_assign<Operator>,
+    value!(
+        Operator::MultiplyAssign,
+        tag!("*=")
+    )
+);
+named!(divide_assign<Operator>,
+    value!(
+        Operator::DivideAssign,
+        tag!("/=")
+    )
+);
+named!(modulo_assign<Operator>,
+    value!(
+        Operator::ModuloAssign,
+        tag!("%=")
+    )
+);
 
 
 named!( pub arithmetic_binary_operator<Operator>,
     ws!(alt!(
-
+        // Operators that share a prefix with another operator must be tried first.
+        add_assign |
+        subtract_assign |
+        multiply_assign |
+        divide_assign |
+        modulo_assign |
+
+        power |
         multiply |
         divide |
         modulo |
@@ -129,6 +209,9 @@ named!( pub arithmetic_binary_operator<Operator>,
         plus |
         minus |
 
+        shift_left |
+        shift_right |
+
         greater_than_or_eq |
         less_than_or_eq |
         greater_than |
@@ -140,6 +223,10 @@ named!( pub arithmetic_binary_operator<Operator>,
         logical_and |
         logical_or |
 
+        bitwise_and |
+        bitwise_or |
+        bitwise_xor |
+
         assignment
     ))
 );
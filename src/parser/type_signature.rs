@@ -5,7 +5,7 @@ use parser::identifier::identifier;
 
 /// _ts indicates that the parser combinator is a getting a type signature
 named!(pub type_signature<TypeInfo>,
-   ws!(alt!(number_ts | string_ts | bool_ts | array_ts | custom_ts ))
+   ws!(alt!(number_ts | float_ts | string_ts | bool_ts | array_ts | custom_ts ))
 );
 
 named!(number_ts<TypeInfo>,
@@ -14,6 +14,12 @@ named!(number_ts<TypeInfo>,
        tag!("Number")
     )
 );
+named!(float_ts<TypeInfo>,
+    value!(
+       TypeInfo::Float,
+       tag!("Float")
+    )
+);
 named!(string_ts<TypeInfo>,
     value!(
         TypeInfo::String,
@@ -1,122 +1,367 @@
-
+use hlwnpa::{Interpreter, parse};
+use hlwnpa::io_context::FileSystemAccess;
+use hlwnpa::ast::{Datatype, LangResult, TypeInfo};
+use hlwnpa::parser::{program, KEYWORDS};
+use hlwnpa::preprocessor::preprocess;
 use nom::IResult;
-use parser::program;
-use ast::{Ast, LangResult, LangError};
-use ast::datatype::{Datatype, VariableStore};
+use rustyline::{Context, Editor, Helper};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+
 use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
 
-use std::io;
-use std::io::Write;
+const PROMPT: &str = "user>";
+/// Shown instead of the prompt while the input so far is incomplete.
+const CONTINUATION_PROMPT: &str = "....>";
+/// The file in the home directory that the REPL's history is kept in.
+const HISTORY_FILE_NAME: &str = ".hlwnpa_history";
+/// Lines that start with this are commands for the REPL, rather than input for the interpreter.
+const COMMAND_PREFIX: char = ':';
+const COMMAND_HELP: &str = "\
+:env             List the globals and their types
+:type <expr>     Show the type of the expression without evaluating it
+:ast <expr>      Show the parsed expression
+:load <file>     Evaluate the file without calling main()
+:mut <name>      Show if the global can be reassigned
+:save <file>     Write the declared structs, functions and variables to a file
+:restore <file>  Replace everything that has been declared with a saved file
+:reset           Forget everything that has been declared
+:quit            Leave the REPL
+:help            Show this message
+";
 
-use std_functions;
-use std::rc::Rc;
-use preprocessor::preprocess;
-use ast::mutability::MutabilityMap;
-use ast::type_checking::TypeStore;
 
+/// Prints the result of the AST
+fn print(possibly_evaluated_program: LangResult) {
 
-/// Because the program parser will put all top level expressions in a list,
-/// the mutability checker will be given a new scope for every command entered in at
-/// the command line due to the way it will copy the mutability map for every new scope.
+    match possibly_evaluated_program {
+        Ok(datatype) => println!("{}", datatype.representation()),
+        Err(err) => println!("{:?}", err),
+    }
+}
+
+/// Reads lines from stdin, evaluating them with the interpreter and printing the result.
 ///
-/// This moves the singular element out of the list and allows the mutability checker to work in
-/// the REPL.
-fn replace_top_level_list_with_its_constituent_element( ast: &Ast ) -> &Ast {
-    if let Ast::ExpressionList(ref expressions) = *ast {
-        if expressions.len() == 1 {
-            expressions.get(0).unwrap()
-        } else {
-            ast
+/// Lines are accumulated until they form a complete program, so functions and structs can span several lines.
+/// An empty line evaluates incomplete input anyway, so mistakes like an extra `{` don't leave the user stuck.
+/// Lines can be edited, previous lines are kept in a history file, and tab completes identifiers, fields and keywords.
+pub fn repl(interpreter: &mut Interpreter) {
+    let mut editor: Editor<ReplHelper> = Editor::new();
+    editor.set_helper(Some(ReplHelper::new(interpreter)));
+    let history_file: Option<PathBuf> = history_file();
+    if let Some(ref history_file) = history_file {
+        let _ = editor.load_history(history_file); // There is no history the first time the REPL is used.
+    }
+
+    let mut input: String = String::new();
+    loop {
+        let prompt: &str = if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT };
+        let line: String = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                input.clear(); // Ctrl-C abandons the current input.
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("Couldn't read input: {}", e);
+                break;
+            }
+        };
+        editor.add_history_entry(line.as_str());
+        if input.is_empty() && line.starts_with(COMMAND_PREFIX) {
+            match run_command(&line, interpreter) {
+                Some(output) => print!("{}", output),
+                None => break,
+            }
+            editor.set_helper(Some(ReplHelper::new(interpreter)));
+            continue;
+        }
+        let forced: bool = !input.is_empty() && line.trim().is_empty();
+        input.push_str(&line);
+        input.push('\n');
+        if !forced && is_incomplete(&input) {
+            continue;
         }
-    } else {
-        ast
+        print(interpreter.eval_str(&input));
+        input.clear();
+        editor.set_helper(Some(ReplHelper::new(interpreter))); // Complete what the input declared.
     }
 
+    if let Some(ref history_file) = history_file {
+        if let Err(e) = editor.save_history(history_file) {
+            eprintln!("Couldn't save the history to {}: {}", history_file.display(), e);
+        }
+    }
 }
 
+/// Runs a REPL command, producing what it prints, or None if the REPL should exit.
+fn run_command(line: &str, interpreter: &mut Interpreter) -> Option<String> {
+    let line: &str = line.trim();
+    let (command, argument): (&str, &str) = match line.find(char::is_whitespace) {
+        Some(index) => (&line[..index], line[index..].trim()),
+        None => (line, ""),
+    };
+    let output: String = match command {
+        ":env" => {
+            let mut output: String = String::new();
+            for name in interpreter.global_names() {
+                let value = interpreter.get_global(&name).expect("The name was just listed");
+                match *value {
+                    Datatype::BuiltinFunction(_) => continue, // The standard library would drown out what was declared.
+                    Datatype::StructType { .. } => {
+                        output.push_str(&format!("{}\n", value));
+                        continue;
+                    }
+                    _ => {}
+                }
+                let type_info: TypeInfo = interpreter.global_type(&name)
+                    .unwrap_or_else(|| TypeInfo::from(value.as_ref().clone()));
+                output.push_str(&format!("{}: {}\n", name, type_info));
+            }
+            output
+        }
+        ":type" => {
+            match interpreter.type_of(argument) {
+                Ok(type_info) => format!("{}\n", type_info),
+                Err(e) => format!("{:?}\n", e),
+            }
+        }
+        ":ast" => {
+            match parse(argument) {
+                Ok(ast) => format!("{:#?}\n", ast),
+                Err(e) => format!("{:?}\n", e),
+            }
+        }
+        ":load" => {
+            match interpreter.load_file(argument) {
+                Ok(value) => format!("{}\n", value.representation()),
+                Err(e) => format!("{:?}\n", e),
+            }
+        }
+        ":mut" => {
+            match interpreter.mutability(argument) {
+                Some(mutability) => format!("{:?}\n", mutability),
+                None => format!("`{}` hasn't been declared\n", argument),
+            }
+        }
+        ":save" => {
+            match interpreter.save_session(argument) {
+                Ok(ref unsaved) if unsaved.is_empty() => format!("Saved to {}\n", argument),
+                Ok(unsaved) => format!("Saved to {}, except for {}\n", argument, unsaved.join(", ")),
+                Err(e) => format!("{}\n", e),
+            }
+        }
+        ":restore" => {
+            match interpreter.restore_session(argument) {
+                Ok(_) => format!("Restored {}\n", argument),
+                Err(e) => format!("{}\n", e),
+            }
+        }
+        ":reset" => {
+            interpreter.reset();
+            String::new()
+        }
+        ":quit" => return None,
+        ":help" => COMMAND_HELP.to_string(),
+        other => format!("Unknown command `{}`, use :help to list the commands\n", other),
+    };
+    Some(output)
+}
 
-/// Reads and parses
-fn read<'a>(read_string: &'a str) -> IResult<&'a [u8], Ast> {
-    if read_string == "" {
-        return IResult::Done(b"", Ast::Literal(Datatype::None))
-    }
-    return program(read_string.as_bytes());
+fn history_file() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE_NAME))
 }
 
-// Evaluates the AST
-fn evaluate(
-    ast: Ast,
-    map: &mut VariableStore,
-    mutability_map: &mut MutabilityMap,
-    type_store: &mut TypeStore
-) -> LangResult {
-
-    let ast = replace_top_level_list_with_its_constituent_element(&ast);
-    if let Err(error) = ast.check_mutability_semantics(mutability_map) {
-        println!("{:?}", error);
-        Err(LangError::MutabilityRulesViolated)
-    } else {
-        match ast.check_types(type_store) {
-            Ok(_) => ast.evaluate(map),
-            Err(type_error) => Err(LangError::NewTypeError(type_error))
+/// Determines if more lines are needed to finish the input, because it has unclosed
+/// braces, brackets, parentheses or strings, or because the parser ran out of input.
+fn is_incomplete(input: &str) -> bool {
+    let mut depth: i32 = 0;
+    let mut in_string: bool = false;
+    let mut escaped: bool = false;
+    for character in input.chars() {
+        if in_string {
+            match character {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else {
+            match character {
+                '"' => in_string = true,
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth -= 1,
+                _ => {}
+            }
         }
     }
+    if in_string || depth > 0 {
+        return true;
+    }
+    matches!(program(preprocess(input).as_bytes()), IResult::Incomplete(_))
 }
 
-/// Prints the result of the AST
-fn print(possibly_evaluated_program: LangResult) {
 
-    match possibly_evaluated_program {
-        Ok(datatype) => print!("{:?}\nuser>", datatype),
-        Err(err) => print!("{:?}\nuser>", err),
+/// Completes the word before the cursor.
+struct ReplHelper {
+    /// The globals and keywords, in alphabetical order.
+    names: Vec<String>,
+    /// The fields of each global struct, by the global's name.
+    struct_fields: HashMap<String, Vec<String>>,
+    /// The fields of every struct type, which are offered when the struct being accessed isn't a global.
+    all_fields: Vec<String>,
+}
+
+impl ReplHelper {
+    fn new(interpreter: &Interpreter) -> ReplHelper {
+        let mut names: Vec<String> = KEYWORDS.iter().map(|keyword| keyword.to_string()).collect();
+        let mut struct_fields: HashMap<String, Vec<String>> = HashMap::new();
+        let mut all_fields: Vec<String> = vec![];
+        for name in interpreter.global_names() {
+            let fields: Vec<String> = match interpreter.get_global(&name).as_ref().map(|value| value.as_ref()) {
+                Some(Datatype::Struct { map, .. }) => map.keys().cloned().collect(),
+                Some(Datatype::StructType { type_information: TypeInfo::Struct { map }, .. }) => {
+                    all_fields.extend(map.keys().cloned());
+                    vec![]
+                }
+                _ => vec![],
+            };
+            if !fields.is_empty() {
+                struct_fields.insert(name.clone(), fields);
+            }
+            names.push(name);
+        }
+        names.sort();
+        all_fields.sort();
+        all_fields.dedup();
+        ReplHelper { names, struct_fields, all_fields }
+    }
+
+    /// Finds where the word before the cursor starts, and the candidates that could finish it.
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before_cursor: &str = &line[..pos];
+        let start: usize = word_start(before_cursor);
+        let word: &str = &before_cursor[start..];
+        let options: &[String] = if before_cursor[..start].ends_with('.') {
+            let receiver: &str = &before_cursor[word_start(&before_cursor[..start - 1])..start - 1];
+            self.struct_fields.get(receiver).unwrap_or(&self.all_fields)
+        } else {
+            &self.names
+        };
+        let mut matching: Vec<String> = options.iter().filter(|option| option.starts_with(word)).cloned().collect();
+        matching.sort();
+        (start, matching)
     }
+}
 
-    let _ = io::stdout().flush(); // print immediately
+/// The start of the identifier that the text ends with.
+fn word_start(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .take_while(|&(_, character)| character.is_alphanumeric() || character == '_')
+        .last()
+        .map_or(text.len(), |(index, _)| index)
 }
 
-/// It is expected that the incoming map already has the std_functions added.
-pub fn repl(mut map: &mut VariableStore, mut mutability_map: &mut MutabilityMap, mut type_store: &mut TypeStore) {
-    use std::io;
-    use std::io::prelude::*;
-    let stdin = io::stdin();
+impl Completer for ReplHelper {
+    type Candidate = String;
 
-    print!("user>");
-    let _ = io::stdout().flush();
-    for line in stdin.lock().lines() {
-        prep(&mut line.unwrap().as_str(), &mut map, &mut mutability_map, type_store)
+    fn complete(&self, line: &str, pos: usize, _: &Context) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
     }
 }
 
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
 
+impl Helper for ReplHelper {}
 
-/// Creates the map, adds standard functions to it and runs the repl with it.
-pub fn create_repl() {
-    let mut map: VariableStore = VariableStore::new();
-    let mut mutability_map: MutabilityMap = MutabilityMap::new();
-    let mut type_store: TypeStore = TypeStore::new();
-    std_functions::add_std_functions(&mut map);
 
-    repl(&mut map, &mut mutability_map, &mut type_store)
+/// Creates an interpreter with the standard functions and runs the repl with it.
+pub fn create_repl(file_system: FileSystemAccess) {
+    let mut interpreter: Interpreter = Interpreter::new();
+    interpreter.set_file_system_access(file_system);
+    repl(&mut interpreter)
 }
 
 
-/// Preprocess, Parse, Evaluate, Print.
-fn prep(a: &mut &str, map: &mut VariableStore, mutability_map: &mut MutabilityMap, type_store: &mut TypeStore ) {
-    let preprocessed = preprocess(a);
-    let parsed = read(preprocessed.as_str());
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
 
-    match parsed {
-        IResult::Done(_, ast) => {
-            let evaled = evaluate(ast, map, mutability_map, type_store);
-            print(evaled)
-        },
-        IResult::Error(e) => {
-            print!("Invalid syntax: {}\nuser>", e);
-        }
-        IResult::Incomplete(i) => {
-            print!("Invalid syntax. Parser returned incomplete: {:?}\nuser>", i);
-        }
+    #[test]
+    fn unclosed_delimiters_are_incomplete() {
+        assert!(is_incomplete("fn add(a: Number, b: Number) -> Number {\n"));
+        assert!(is_incomplete("let xs := [1,\n"));
+        assert!(is_incomplete("println(\"first line\n"));
+        assert!(is_incomplete("fn f() -> Number\n"));
+    }
+
+    #[test]
+    fn finished_input_is_complete() {
+        assert!(!is_incomplete("fn add(a: Number, b: Number) -> Number {\n a + b\n}\n"));
+        assert!(!is_incomplete("println(\"{ \\\" (\")\n"));
+        assert!(!is_incomplete("let x := 4\n"));
+        // Extra closing delimiters can't be fixed by more input, so the parser reports them.
+        assert!(!is_incomplete("}\n"));
     }
 
+    #[test]
+    fn commands_inspect_the_interpreter() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("const limit := 4").unwrap();
+        let env: String = run_command(":env", &mut interpreter).unwrap();
+        assert!(env.contains("limit: Number\n"));
+        assert!(!env.contains("len:"));
+        assert_eq!(Some("Float\n".to_string()), run_command(":type to_float(limit) ", &mut interpreter));
+        assert_eq!(Some("Immutable\n".to_string()), run_command(":mut limit", &mut interpreter));
+        assert_eq!(Some(String::new()), run_command(":reset", &mut interpreter));
+        assert_eq!(Some("`limit` hasn't been declared\n".to_string()), run_command(":mut limit", &mut interpreter));
+        assert_eq!(None, run_command(":quit", &mut interpreter));
+    }
+
+    #[test]
+    fn types_are_shown_as_they_are_written() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("struct Point { x: Number y: Number }\nlet points := [new Point { x: 1 y: 2 }]").unwrap();
+        let env: String = run_command(":env", &mut interpreter).unwrap();
+        assert!(env.contains("points: [Point]\n"));
+        assert!(env.contains("struct Point { x: Number, y: Number }\n"));
+        assert_eq!(Some("String\n".to_string()), run_command(":type \"a\" + 1", &mut interpreter));
+    }
 
+    #[test]
+    fn commands_save_and_restore_sessions() {
+        let filename = env::temp_dir().join(format!("hlwnpa_repl_session_test_{}.hlw", ::std::process::id()));
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("let width := 3").unwrap();
+        assert_eq!(Some(format!("Saved to {}\n", filename.display())), run_command(&format!(":save {}", filename.display()), &mut interpreter));
+        interpreter.eval_str("width := 4").unwrap();
+        assert_eq!(Some(format!("Restored {}\n", filename.display())), run_command(&format!(":restore {}", filename.display()), &mut interpreter));
+        fs::remove_file(&filename).unwrap();
+        assert_eq!(Datatype::Number(3), *interpreter.eval_str("width").unwrap());
+    }
+
+    #[test]
+    fn completes_globals_keywords_and_fields() {
+        let mut interpreter = Interpreter::new();
+        interpreter.eval_str("struct Point { x: Number y: Number }\nlet origin := new Point { x: 0 y: 0 }\nlet width := 3").unwrap();
+        let helper = ReplHelper::new(&interpreter);
+        assert_eq!((4, vec!["while".to_string(), "width".to_string(), "write_file".to_string()]), helper.candidates("1 + w", 5));
+        assert_eq!((7, vec!["y".to_string()]), helper.candidates("origin.y", 8));
+        // The struct isn't a global, so the fields of every struct type are offered.
+        assert_eq!((2, vec!["x".to_string(), "y".to_string()]), helper.candidates("p.", 2));
+    }
 }
@@ -0,0 +1,173 @@
+use ast::{Ast, SExpression, Datatype, RcDatatype, TypeInfo, VariableStore};
+use ast::mutability::{MutabilityMap, Mutability};
+use ast::type_checking::TypeStore;
+use parser::statement;
+use preprocessor::preprocess;
+use std_functions;
+use nom::IResult;
+
+use std::collections::HashMap;
+
+/// The source a struct or function was declared with, and the value declaring it created.
+#[derive(Clone, Debug)]
+pub struct Declaration {
+    source: String,
+    value: RcDatatype,
+}
+
+/// The declarations of the structs and functions, by name.
+pub type Declarations = HashMap<String, Declaration>;
+
+/// Remembers the source of the structs and functions that were declared by the top level statements of the source,
+/// once it has been evaluated, so they can be written out again.
+pub fn record_declarations(source: &str, map: &VariableStore, declarations: &mut Declarations) {
+    for (name, source) in declaration_sources(source) {
+        if let Some(value) = map.get(&name) {
+            declarations.insert(name, Declaration { source, value: value.clone() });
+        }
+    }
+}
+
+/// Finds the struct and function declarations among the top level statements, along with the name they declare.
+/// Escapes are only replaced within each statement, so the source is kept as it was written.
+fn declaration_sources(source: &str) -> Vec<(String, String)> {
+    let mut sources: Vec<(String, String)> = vec![];
+    let mut rest: &str = source;
+    while let IResult::Done(remaining, _) = statement(rest.as_bytes()) {
+        if remaining.len() == rest.len() {
+            break;
+        }
+        let text: &str = rest[..rest.len() - remaining.len()].trim();
+        rest = &rest[rest.len() - remaining.len()..];
+        let identifier: Option<Ast> = match statement(preprocess(text).as_bytes()) {
+            IResult::Done(remaining, Ast::SExpr(SExpression::DeclareFunction { identifier, .. })) |
+            IResult::Done(remaining, Ast::SExpr(SExpression::StructDeclaration { identifier, .. })) if remaining.is_empty() => Some(*identifier),
+            _ => None,
+        };
+        if let Some(Ast::ValueIdentifier(name)) = identifier {
+            sources.push((name, text.to_string()));
+        }
+    }
+    sources
+}
+
+/// Writes source that declares the structs, functions and variables in the stores, so evaluating it recreates them.
+/// Structs come first, then functions, then variables, each in alphabetical order.
+/// Structs and functions are written the way they were declared, and variables are written as literals.
+///
+/// Also returns the names of the globals that can't be written as source,
+/// like native functions and strings containing quotes, which are left out.
+pub fn session_source(map: &VariableStore, type_store: &TypeStore, mutability_map: &MutabilityMap, declarations: &Declarations) -> (String, Vec<String>) {
+    let mut standard_functions: VariableStore = VariableStore::new();
+    std_functions::add_std_functions(&mut standard_functions);
+
+    let mut names: Vec<&String> = map.keys().collect();
+    names.sort();
+
+    let mut structs: Vec<String> = vec![];
+    let mut functions: Vec<String> = vec![];
+    let mut variables: Vec<String> = vec![];
+    let mut unsaved: Vec<String> = vec![];
+    for name in names {
+        let value: &Datatype = &map[name];
+        if standard_functions.get(name).map(|standard| standard.as_ref()) == Some(value) {
+            continue;
+        }
+        // A declaration only describes the global if nothing has replaced what it declared since.
+        let declared: Option<String> = declarations.get(name)
+            .filter(|declaration| *declaration.value == *value)
+            .map(|declaration| declaration.source.clone());
+        match *value {
+            Datatype::StructType { .. } => match declared {
+                Some(source) => structs.push(source),
+                None => unsaved.push(name.clone()),
+            },
+            Datatype::Function { .. } => match declared {
+                Some(source) => functions.push(source),
+                None => unsaved.push(name.clone()),
+            },
+            _ => {
+                let type_info: Option<TypeInfo> = type_store.get(name).cloned().map(|mutability| mutability.get_type());
+                match value_source(value, type_info.as_ref(), map) {
+                    Some(source) => {
+                        let keyword: &str = match mutability_map.get(name) {
+                            Some(&Mutability::Immutable) => "const",
+                            _ => "let",
+                        };
+                        // Arrays are annotated, so the element type of an empty array isn't lost.
+                        let annotation: Option<String> = match type_info {
+                            Some(ref array_type @ TypeInfo::Array(_)) => type_source(array_type),
+                            _ => None,
+                        };
+                        variables.push(match annotation {
+                            Some(annotation) => format!("{} {}: {} := {}", keyword, name, annotation, source),
+                            None => format!("{} {} := {}", keyword, name, source),
+                        });
+                    }
+                    None => unsaved.push(name.clone()),
+                }
+            }
+        }
+    }
+
+    let mut source: String = String::new();
+    for declaration in structs.into_iter().chain(functions).chain(variables) {
+        source.push_str(&declaration);
+        source.push('\n');
+    }
+    (source, unsaved)
+}
+
+/// How a type is written in an annotation, if it can be.
+fn type_source(type_info: &TypeInfo) -> Option<String> {
+    match *type_info {
+        TypeInfo::Number => Some("Number".to_string()),
+        TypeInfo::Float => Some("Float".to_string()),
+        TypeInfo::String => Some("String".to_string()),
+        TypeInfo::Bool => Some("Bool".to_string()),
+        TypeInfo::Array(ref element_type) => type_source(element_type).map(|element_type| format!("[{}]", element_type)),
+        TypeInfo::StructType { ref identifier } => Some(identifier.clone()),
+        _ => None,
+    }
+}
+
+/// Writes an expression that evaluates to the value, looking up the types of struct fields in the struct types.
+/// The structs returned by builtins aren't an instance of any struct type, so they can't be created.
+fn value_source(value: &Datatype, type_info: Option<&TypeInfo>, struct_types: &VariableStore) -> Option<String> {
+    match *value {
+        Datatype::Number(value) if value == i32::MIN => Some(format!("(-{} - 1)", i32::MAX)), // The positive value doesn't fit.
+        Datatype::Number(value) => Some(value.to_string()),
+        Datatype::Float(value) if !value.is_finite() => None,
+        Datatype::Float(value) => {
+            let mut source: String = value.to_string();
+            if !source.contains('.') {
+                source.push_str(".0"); // Floats need a decimal point, or they would be parsed as Numbers.
+            }
+            Some(source)
+        }
+        Datatype::String(ref value) if value.contains('"') || value.contains('\\') => None,
+        Datatype::String(ref value) => Some(format!("\"{}\"", value.replace('\n', "\\n").replace('\t', "\\t").replace('\r', "\\r"))),
+        Datatype::Bool(value) => Some(value.to_string()),
+        Datatype::Array { value: ref elements, ref type_ } => {
+            let element_type: &TypeInfo = match type_info {
+                Some(TypeInfo::Array(element_type)) => element_type,
+                _ => type_,
+            };
+            let elements: Vec<String> = elements.iter()
+                .map(|element| value_source(element, Some(element_type), struct_types))
+                .collect::<Option<Vec<String>>>()?;
+            Some(format!("[{}]", elements.join(", ")))
+        }
+        Datatype::Struct { identifier: Some(ref identifier), ref field_order, ref map } => {
+            let field_types = match struct_types.get(identifier).map(|struct_type| struct_type.as_ref()) {
+                Some(Datatype::StructType { type_information: TypeInfo::Struct { map: field_types }, .. }) => field_types,
+                _ => return None,
+            };
+            let assignments: Vec<String> = field_order.iter()
+                .map(|field| value_source(&map[field], field_types.get(field), struct_types).map(|source| format!("{}: {}", field, source)))
+                .collect::<Option<Vec<String>>>()?;
+            Some(format!("new {} {{ {} }}", identifier, assignments.join(" ")))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,463 @@
+use ast::{Datatype, RcDatatype, TypeInfo, LangResult, LangError};
+use ast::type_checking::{TypeResult, TypeError};
+use super::{check_argument_count, expect_argument_count, expect_index, index_argument, StringFunction};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Functions for working with arrays.
+/// Arrays are immutable values, so functions that alter an array return a new array, leaving the original untouched.
+/// To change the array stored in a variable, assign the result back to it: `xs := push(xs, 4)`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ArrayFunction {
+    Len, // len(xs) -> Number
+    Push, // push(xs, value) -> xs with the value at the end
+    Pop, // pop(xs) -> xs without its last element
+    Insert, // insert(xs, index, value) -> xs with the value at the index
+    Remove, // remove(xs, index) -> xs without the element at the index
+    Slice, // slice(xs, start, end) -> the elements from start up to, but not including, end
+    Concat, // concat(xs, ys) -> the elements of xs followed by the elements of ys
+    Reverse, // reverse(xs) -> xs in reverse order
+    Sort, // sort(xs) -> xs in ascending order, only for arrays of Numbers, Floats, Strings or Bools
+    Contains, // contains(xs, value) -> Bool
+    IndexOf, // index_of(xs, value) -> the index of the first equal element, or -1
+    Zip, // zip(xs, ys) -> an array of structs with the fields `first` and `second`, as long as the shorter array
+    Enumerate, // enumerate(xs) -> an array of structs with the fields `index` and `value`
+}
+
+impl ArrayFunction {
+    pub const ALL: [ArrayFunction; 13] = [
+        ArrayFunction::Len,
+        ArrayFunction::Push,
+        ArrayFunction::Pop,
+        ArrayFunction::Insert,
+        ArrayFunction::Remove,
+        ArrayFunction::Slice,
+        ArrayFunction::Concat,
+        ArrayFunction::Reverse,
+        ArrayFunction::Sort,
+        ArrayFunction::Contains,
+        ArrayFunction::IndexOf,
+        ArrayFunction::Zip,
+        ArrayFunction::Enumerate,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ArrayFunction::Len => "len",
+            ArrayFunction::Push => "push",
+            ArrayFunction::Pop => "pop",
+            ArrayFunction::Insert => "insert",
+            ArrayFunction::Remove => "remove",
+            ArrayFunction::Slice => "slice",
+            ArrayFunction::Concat => "concat",
+            ArrayFunction::Reverse => "reverse",
+            ArrayFunction::Sort => "sort",
+            ArrayFunction::Contains => "contains",
+            ArrayFunction::IndexOf => "index_of",
+            ArrayFunction::Zip => "zip",
+            ArrayFunction::Enumerate => "enumerate",
+        }
+    }
+
+    fn argument_count(&self) -> usize {
+        match *self {
+            ArrayFunction::Len |
+            ArrayFunction::Pop |
+            ArrayFunction::Reverse |
+            ArrayFunction::Sort |
+            ArrayFunction::Enumerate => 1,
+            ArrayFunction::Push |
+            ArrayFunction::Remove |
+            ArrayFunction::Concat |
+            ArrayFunction::Contains |
+            ArrayFunction::IndexOf |
+            ArrayFunction::Zip => 2,
+            ArrayFunction::Insert |
+            ArrayFunction::Slice => 3,
+        }
+    }
+
+    /// `len` and `contains` also accept strings, which are handled by the matching string function.
+    fn string_function(&self) -> Option<StringFunction> {
+        match *self {
+            ArrayFunction::Len => Some(StringFunction::Len),
+            ArrayFunction::Contains => Some(StringFunction::Contains),
+            _ => None,
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        if let (Some(string_function), Some(&TypeInfo::String)) = (self.string_function(), arguments.first()) {
+            return string_function.check_types(arguments);
+        }
+        check_argument_count(arguments, self.argument_count())?;
+        let element_type: TypeInfo = element_type(&arguments[0])?;
+        let array_type: TypeInfo = TypeInfo::Array(Box::new(element_type.clone()));
+        match *self {
+            ArrayFunction::Len => Ok(TypeInfo::Number),
+            ArrayFunction::Pop |
+            ArrayFunction::Reverse => Ok(array_type),
+            ArrayFunction::Sort => {
+                match element_type {
+                    TypeInfo::Number | TypeInfo::Float | TypeInfo::String | TypeInfo::Bool | TypeInfo::Any => Ok(array_type),
+                    other => Err(TypeError::NotSortable(other)),
+                }
+            }
+            ArrayFunction::Push => {
+                let element_type = unify_element(&element_type, &arguments[1])?;
+                Ok(TypeInfo::Array(Box::new(element_type)))
+            }
+            ArrayFunction::Insert => {
+                expect_index(&arguments[1])?;
+                let element_type = unify_element(&element_type, &arguments[2])?;
+                Ok(TypeInfo::Array(Box::new(element_type)))
+            }
+            ArrayFunction::Remove => {
+                expect_index(&arguments[1])?;
+                Ok(array_type)
+            }
+            ArrayFunction::Slice => {
+                expect_index(&arguments[1])?;
+                expect_index(&arguments[2])?;
+                Ok(array_type)
+            }
+            ArrayFunction::Concat => {
+                match array_type.unify(&arguments[1]) {
+                    Some(unified_type) => Ok(unified_type),
+                    None => Err(TypeError::TypeMismatch(arguments[1].clone(), array_type)),
+                }
+            }
+            ArrayFunction::Contains => {
+                unify_element(&element_type, &arguments[1])?;
+                Ok(TypeInfo::Bool)
+            }
+            ArrayFunction::IndexOf => {
+                unify_element(&element_type, &arguments[1])?;
+                Ok(TypeInfo::Number)
+            }
+            ArrayFunction::Zip => {
+                let other_element_type: TypeInfo = self::element_type(&arguments[1])?;
+                Ok(TypeInfo::Array(Box::new(pair_type(("first", element_type), ("second", other_element_type)))))
+            }
+            ArrayFunction::Enumerate => {
+                Ok(TypeInfo::Array(Box::new(pair_type(("index", TypeInfo::Number), ("value", element_type)))))
+            }
+        }
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        if let Some(string_function) = self.string_function() {
+            if let Some(&Datatype::String(_)) = arguments.first().map(|argument| argument.as_ref()) {
+                return string_function.call(arguments);
+            }
+        }
+        expect_argument_count(arguments, self.argument_count())?;
+        let (elements, element_type) = array_argument(&arguments[0])?;
+        match *self {
+            ArrayFunction::Len => Ok(Rc::new(Datatype::Number(elements.len() as i32))),
+            ArrayFunction::Push => {
+                let element_type = unify_value(element_type, &arguments[1])?;
+                let mut elements = elements.clone();
+                elements.push(arguments[1].clone());
+                create_array(elements, element_type)
+            }
+            ArrayFunction::Pop => {
+                if elements.is_empty() {
+                    return Err(LangError::OutOfBoundsArrayAccess);
+                }
+                create_array(elements[..elements.len() - 1].to_vec(), element_type.clone())
+            }
+            ArrayFunction::Insert => {
+                let index = index_argument(&arguments[1], elements.len())?;
+                let element_type = unify_value(element_type, &arguments[2])?;
+                let mut elements = elements.clone();
+                elements.insert(index, arguments[2].clone());
+                create_array(elements, element_type)
+            }
+            ArrayFunction::Remove => {
+                let index = index_argument(&arguments[1], elements.len())?;
+                if index == elements.len() {
+                    return Err(LangError::OutOfBoundsArrayAccess);
+                }
+                let mut elements = elements.clone();
+                elements.remove(index);
+                create_array(elements, element_type.clone())
+            }
+            ArrayFunction::Slice => {
+                let start = index_argument(&arguments[1], elements.len())?;
+                let end = index_argument(&arguments[2], elements.len())?;
+                if start > end {
+                    return Err(LangError::OutOfBoundsArrayAccess);
+                }
+                create_array(elements[start..end].to_vec(), element_type.clone())
+            }
+            ArrayFunction::Concat => {
+                let (other_elements, other_element_type) = array_argument(&arguments[1])?;
+                let element_type = match element_type.unify(other_element_type) {
+                    Some(unified_type) => unified_type,
+                    None => {
+                        return Err(LangError::MismatchedArrayElementTypes {
+                            expected: element_type.clone(),
+                            found: other_element_type.clone(),
+                        })
+                    }
+                };
+                let mut elements = elements.clone();
+                elements.extend(other_elements.iter().cloned());
+                create_array(elements, element_type)
+            }
+            ArrayFunction::Reverse => {
+                let mut elements = elements.clone();
+                elements.reverse();
+                create_array(elements, element_type.clone())
+            }
+            ArrayFunction::Sort => {
+                match *element_type {
+                    TypeInfo::Number | TypeInfo::Float | TypeInfo::String | TypeInfo::Bool | TypeInfo::Any => {}
+                    ref other => return Err(LangError::NotSortable(other.clone())),
+                }
+                let mut elements = elements.clone();
+                elements.sort_by(|lhs, rhs| sort_order(lhs, rhs));
+                create_array(elements, element_type.clone())
+            }
+            ArrayFunction::Contains => {
+                let found = elements.iter().any(|element| element.lang_eq(&arguments[1]));
+                Ok(Rc::new(Datatype::Bool(found)))
+            }
+            ArrayFunction::IndexOf => {
+                let index = match elements.iter().position(|element| element.lang_eq(&arguments[1])) {
+                    Some(index) => index as i32,
+                    None => -1,
+                };
+                Ok(Rc::new(Datatype::Number(index)))
+            }
+            ArrayFunction::Zip => {
+                let (other_elements, other_element_type) = array_argument(&arguments[1])?;
+                let pairs: Vec<RcDatatype> = elements
+                    .iter()
+                    .zip(other_elements.iter())
+                    .map(|(first, second)| pair(("first", first), ("second", second)))
+                    .collect();
+                create_array(pairs, pair_type(("first", element_type.clone()), ("second", other_element_type.clone())))
+            }
+            ArrayFunction::Enumerate => {
+                let pairs: Vec<RcDatatype> = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| pair(("index", &Rc::new(Datatype::Number(index as i32))), ("value", value)))
+                    .collect();
+                create_array(pairs, pair_type(("index", TypeInfo::Number), ("value", element_type.clone())))
+            }
+        }
+    }
+}
+
+/// The type of the anonymous structs produced by `zip` and `enumerate`.
+fn pair_type(first: (&str, TypeInfo), second: (&str, TypeInfo)) -> TypeInfo {
+    let mut map: HashMap<String, TypeInfo> = HashMap::new();
+    map.insert(first.0.to_string(), first.1);
+    map.insert(second.0.to_string(), second.1);
+    TypeInfo::Struct { map }
+}
+
+fn pair(first: (&str, &RcDatatype), second: (&str, &RcDatatype)) -> RcDatatype {
+    let mut map: HashMap<String, Datatype> = HashMap::new();
+    map.insert(first.0.to_string(), first.1.as_ref().clone());
+    map.insert(second.0.to_string(), second.1.as_ref().clone());
+    Rc::new(Datatype::Struct {
+        identifier: None,
+        field_order: vec![first.0.to_string(), second.0.to_string()],
+        map,
+    })
+}
+
+/// Gets the type of the elements of an array type.
+fn element_type(array_type: &TypeInfo) -> TypeResult {
+    match *array_type {
+        TypeInfo::Array(ref element_type) => Ok(*element_type.clone()),
+        TypeInfo::Any => Ok(TypeInfo::Any),
+        ref other => Err(TypeError::TypeMismatch(other.clone(), TypeInfo::Array(Box::new(TypeInfo::Any)))),
+    }
+}
+
+/// Checks that a value can be stored in an array with the given element type,
+/// producing the element type of the resulting array.
+fn unify_element(element_type: &TypeInfo, value_type: &TypeInfo) -> TypeResult {
+    match element_type.unify(value_type) {
+        Some(unified_type) => Ok(unified_type),
+        None => Err(TypeError::TypeMismatch(value_type.clone(), element_type.clone())),
+    }
+}
+
+fn array_argument(argument: &RcDatatype) -> Result<(&Vec<RcDatatype>, &TypeInfo), LangError> {
+    match **argument {
+        Datatype::Array { ref value, ref type_ } => Ok((value, type_)),
+        ref other => {
+            Err(LangError::TypeError {
+                expected: TypeInfo::Array(Box::new(TypeInfo::Any)),
+                found: TypeInfo::from(other.clone()),
+            })
+        }
+    }
+}
+
+fn unify_value(element_type: &TypeInfo, value: &RcDatatype) -> Result<TypeInfo, LangError> {
+    let value_type = TypeInfo::from(value.as_ref().clone());
+    match element_type.unify(&value_type) {
+        Some(unified_type) => Ok(unified_type),
+        None => {
+            Err(LangError::MismatchedArrayElementTypes {
+                expected: element_type.clone(),
+                found: value_type,
+            })
+        }
+    }
+}
+
+/// Orders the elements for `sort`. NaN isn't less or greater than anything, so it is put after every other value,
+/// as leaving it where it is would stop the values around it from being sorted.
+fn sort_order(lhs: &Datatype, rhs: &Datatype) -> Ordering {
+    let is_nan = |value: &Datatype| matches!(*value, Datatype::Float(value) if value.is_nan());
+    match (is_nan(lhs), is_nan(rhs)) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal),
+    }
+}
+
+fn create_array(elements: Vec<RcDatatype>, element_type: TypeInfo) -> LangResult {
+    Ok(Rc::new(Datatype::Array {
+        value: elements,
+        type_: element_type,
+    }))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::slice;
+
+    fn numbers(values: &[i32]) -> RcDatatype {
+        Rc::new(Datatype::Array {
+            value: values.iter().map(|n| Rc::new(Datatype::Number(*n))).collect(),
+            type_: TypeInfo::Number,
+        })
+    }
+
+    fn number(value: i32) -> RcDatatype {
+        Rc::new(Datatype::Number(value))
+    }
+
+    #[test]
+    fn functions_return_new_arrays() {
+        let xs = numbers(&[3, 1, 2]);
+        assert_eq!(numbers(&[3, 1, 2, 4]), ArrayFunction::Push.call(&[xs.clone(), number(4)]).unwrap());
+        assert_eq!(numbers(&[3, 1]), ArrayFunction::Pop.call(slice::from_ref(&xs)).unwrap());
+        assert_eq!(numbers(&[3, 5, 1, 2]), ArrayFunction::Insert.call(&[xs.clone(), number(1), number(5)]).unwrap());
+        assert_eq!(numbers(&[3, 2]), ArrayFunction::Remove.call(&[xs.clone(), number(1)]).unwrap());
+        assert_eq!(numbers(&[1, 2]), ArrayFunction::Slice.call(&[xs.clone(), number(1), number(3)]).unwrap());
+        assert_eq!(numbers(&[3, 1, 2, 7]), ArrayFunction::Concat.call(&[xs.clone(), numbers(&[7])]).unwrap());
+        assert_eq!(numbers(&[2, 1, 3]), ArrayFunction::Reverse.call(slice::from_ref(&xs)).unwrap());
+        assert_eq!(numbers(&[1, 2, 3]), ArrayFunction::Sort.call(slice::from_ref(&xs)).unwrap());
+        assert_eq!(numbers(&[3, 1, 2]), xs);
+    }
+
+    #[test]
+    fn sort_puts_nan_last() {
+        let floats = |values: &[f64]| Rc::new(Datatype::Array {
+            value: values.iter().map(|value| Rc::new(Datatype::Float(*value))).collect(),
+            type_: TypeInfo::Float,
+        });
+        let sorted = ArrayFunction::Sort.call(&[floats(&[1.0, f64::NAN, 0.5, f64::NAN, -2.0])]).unwrap();
+        let values: Vec<f64> = match *sorted {
+            Datatype::Array { ref value, .. } => value.iter().map(|element| match **element {
+                Datatype::Float(value) => value,
+                ref other => panic!("Expected a Float, got: {:?}", other),
+            }).collect(),
+            ref other => panic!("Expected an array, got: {:?}", other),
+        };
+        assert_eq!(vec![-2.0, 0.5, 1.0], values[..3].to_vec());
+        assert!(values[3].is_nan() && values[4].is_nan());
+    }
+
+    #[test]
+    fn search_functions() {
+        let xs = numbers(&[3, 1, 2]);
+        assert_eq!(number(3), ArrayFunction::Len.call(slice::from_ref(&xs)).unwrap());
+        assert_eq!(Datatype::Bool(true), *ArrayFunction::Contains.call(&[xs.clone(), number(2)]).unwrap());
+        assert_eq!(Datatype::Bool(false), *ArrayFunction::Contains.call(&[xs.clone(), number(9)]).unwrap());
+        assert_eq!(number(1), ArrayFunction::IndexOf.call(&[xs.clone(), number(1)]).unwrap());
+        assert_eq!(number(-1), ArrayFunction::IndexOf.call(&[xs.clone(), number(9)]).unwrap());
+    }
+
+    #[test]
+    fn zip_and_enumerate_produce_structs() {
+        let zipped = ArrayFunction::Zip.call(&[numbers(&[3, 1, 2]), numbers(&[7])]).unwrap();
+        let mut first_pair: HashMap<String, Datatype> = HashMap::new();
+        first_pair.insert("first".to_string(), Datatype::Number(3));
+        first_pair.insert("second".to_string(), Datatype::Number(7));
+        assert_eq!(
+            Datatype::Array {
+                value: vec![Rc::new(Datatype::Struct {
+                    identifier: None,
+                    field_order: vec!["first".to_string(), "second".to_string()],
+                    map: first_pair,
+                })],
+                type_: pair_type(("first", TypeInfo::Number), ("second", TypeInfo::Number)),
+            },
+            *zipped
+        );
+        let enumerated = ArrayFunction::Enumerate.call(&[numbers(&[3, 1])]).unwrap();
+        if let Datatype::Array { ref value, .. } = *enumerated {
+            if let Datatype::Struct { ref map, .. } = *value[1] {
+                assert_eq!(Some(&Datatype::Number(1)), map.get("index"));
+                assert_eq!(Some(&Datatype::Number(1)), map.get("value"));
+            } else {
+                panic!("Expected a struct");
+            }
+        } else {
+            panic!("Expected an array");
+        }
+    }
+
+    #[test]
+    fn out_of_range_indices_are_rejected() {
+        let xs = numbers(&[3, 1, 2]);
+        assert_eq!(LangError::OutOfBoundsArrayAccess, ArrayFunction::Remove.call(&[xs.clone(), number(3)]).unwrap_err());
+        assert_eq!(LangError::NegativeIndex(-1), ArrayFunction::Insert.call(&[xs.clone(), number(-1), number(0)]).unwrap_err());
+        assert_eq!(LangError::OutOfBoundsArrayAccess, ArrayFunction::Slice.call(&[xs.clone(), number(2), number(1)]).unwrap_err());
+        assert_eq!(LangError::OutOfBoundsArrayAccess, ArrayFunction::Pop.call(&[numbers(&[])]).unwrap_err());
+    }
+
+    #[test]
+    fn pushing_onto_an_empty_array_sets_its_type() {
+        let empty = Rc::new(Datatype::Array { value: vec![], type_: TypeInfo::Any });
+        assert_eq!(numbers(&[1]), ArrayFunction::Push.call(&[empty, number(1)]).unwrap());
+        assert_eq!(
+            LangError::MismatchedArrayElementTypes { expected: TypeInfo::Number, found: TypeInfo::Bool },
+            ArrayFunction::Push.call(&[numbers(&[1]), Rc::new(Datatype::Bool(true))]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn type_checking() {
+        let number_array = TypeInfo::Array(Box::new(TypeInfo::Number));
+        assert_eq!(Ok(number_array.clone()), ArrayFunction::Push.check_types(&[TypeInfo::Array(Box::new(TypeInfo::Any)), TypeInfo::Number]));
+        assert_eq!(Ok(TypeInfo::Number), ArrayFunction::Len.check_types(slice::from_ref(&number_array)));
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::String, TypeInfo::Number)),
+            ArrayFunction::Contains.check_types(&[number_array.clone(), TypeInfo::String])
+        );
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::Bool, TypeInfo::Array(Box::new(TypeInfo::Any)))),
+            ArrayFunction::Reverse.check_types(&[TypeInfo::Bool])
+        );
+        assert_eq!(
+            Err(TypeError::NotSortable(TypeInfo::Array(Box::new(TypeInfo::Number)))),
+            ArrayFunction::Sort.check_types(&[TypeInfo::Array(Box::new(number_array.clone()))])
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use ast::{Datatype, RcDatatype, TypeInfo, LangResult, LangError};
+use ast::type_checking::{TypeResult, TypeError};
+use super::{check_argument_count, expect_argument_count};
+use std::rc::Rc;
+
+/// Functions for checking that a program behaves as expected.
+/// A failed assertion is a runtime error that describes what went wrong.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum AssertFunction {
+    Assert, // assert(condition, message) -> true, or fails with the message if the condition is false
+    AssertEq, // assert_eq(left, right) -> true, or fails showing both values if they aren't equal
+}
+
+impl AssertFunction {
+    pub const ALL: [AssertFunction; 2] = [
+        AssertFunction::Assert,
+        AssertFunction::AssertEq,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            AssertFunction::Assert => "assert",
+            AssertFunction::AssertEq => "assert_eq",
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        check_argument_count(arguments, 2)?;
+        match *self {
+            AssertFunction::Assert => {
+                expect_type(&arguments[0], TypeInfo::Bool)?;
+                expect_type(&arguments[1], TypeInfo::String)?;
+            }
+            // Like `==`, any two values can be compared.
+            AssertFunction::AssertEq => {}
+        }
+        Ok(TypeInfo::Bool)
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        expect_argument_count(arguments, 2)?;
+        match *self {
+            AssertFunction::Assert => {
+                match (&*arguments[0], &*arguments[1]) {
+                    (Datatype::Bool(true), _) => Ok(Rc::new(Datatype::Bool(true))),
+                    (Datatype::Bool(false), Datatype::String(message)) => {
+                        Err(LangError::AssertionFailed(format!("Assertion failed: {}", message)))
+                    }
+                    (Datatype::Bool(_), other) => {
+                        Err(LangError::TypeError { expected: TypeInfo::String, found: TypeInfo::from(other.clone()) })
+                    }
+                    (other, _) => {
+                        Err(LangError::TypeError { expected: TypeInfo::Bool, found: TypeInfo::from(other.clone()) })
+                    }
+                }
+            }
+            AssertFunction::AssertEq => {
+                if arguments[0].lang_eq(&arguments[1]) {
+                    Ok(Rc::new(Datatype::Bool(true)))
+                } else {
+                    Err(LangError::AssertionFailed(format!(
+                        "Assertion failed: left == right\n  left: {}\n right: {}",
+                        arguments[0].representation(),
+                        arguments[1].representation()
+                    )))
+                }
+            }
+        }
+    }
+}
+
+fn expect_type(found: &TypeInfo, expected: TypeInfo) -> Result<(), TypeError> {
+    match *found {
+        TypeInfo::Any => Ok(()),
+        ref other if *other == expected => Ok(()),
+        ref other => Err(TypeError::TypeMismatch(other.clone(), expected)),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn assert_fails_with_the_message() {
+        let passing = [Rc::new(Datatype::Bool(true)), Rc::new(Datatype::from("unused"))];
+        assert_eq!(Datatype::Bool(true), *AssertFunction::Assert.call(&passing).unwrap());
+        let failing = [Rc::new(Datatype::Bool(false)), Rc::new(Datatype::from("x should be positive"))];
+        assert_eq!(
+            LangError::AssertionFailed("Assertion failed: x should be positive".to_string()),
+            AssertFunction::Assert.call(&failing).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn assert_eq_shows_both_values() {
+        let equal = [Rc::new(Datatype::Number(1)), Rc::new(Datatype::Float(1.0))];
+        assert_eq!(Datatype::Bool(true), *AssertFunction::AssertEq.call(&equal).unwrap());
+        let different = [Rc::new(Datatype::from("a")), Rc::new(Datatype::from("b"))];
+        assert_eq!(
+            LangError::AssertionFailed("Assertion failed: left == right\n  left: \"a\"\n right: \"b\"".to_string()),
+            AssertFunction::AssertEq.call(&different).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn assert_types() {
+        assert_eq!(Ok(TypeInfo::Bool), AssertFunction::Assert.check_types(&[TypeInfo::Bool, TypeInfo::String]));
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::Number, TypeInfo::Bool)),
+            AssertFunction::Assert.check_types(&[TypeInfo::Number, TypeInfo::String])
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use ast::{Datatype, RcDatatype, TypeInfo, LangResult};
+use ast::type_checking::{TypeResult, TypeError};
+use super::{check_argument_count, expect_argument_count, string_argument};
+use io_context;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Functions that use the file system, if the interpreter allows it.
+///
+/// Failures, including not being allowed to use the file, don't stop the program.
+/// Instead, each function produces a struct with the fields `ok`, `value` and `error`.
+/// If `ok` is false, `error` describes what went wrong, and `value` is empty.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum FileFunction {
+    ReadFile, // read_file(path) -> the contents of the file
+    WriteFile, // write_file(path, contents) -> contents, replacing the file's contents
+    AppendFile, // append_file(path, contents) -> contents, added to the end of the file
+    FileExists, // file_exists(path) -> if a file or directory exists at the path
+    ListDir, // list_dir(path) -> the sorted names of the entries in the directory
+}
+
+impl FileFunction {
+    pub const ALL: [FileFunction; 5] = [
+        FileFunction::ReadFile,
+        FileFunction::WriteFile,
+        FileFunction::AppendFile,
+        FileFunction::FileExists,
+        FileFunction::ListDir,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            FileFunction::ReadFile => "read_file",
+            FileFunction::WriteFile => "write_file",
+            FileFunction::AppendFile => "append_file",
+            FileFunction::FileExists => "file_exists",
+            FileFunction::ListDir => "list_dir",
+        }
+    }
+
+    fn argument_count(&self) -> usize {
+        match *self {
+            FileFunction::WriteFile |
+            FileFunction::AppendFile => 2,
+            FileFunction::ReadFile |
+            FileFunction::FileExists |
+            FileFunction::ListDir => 1,
+        }
+    }
+
+    /// The type of the `value` field of the result.
+    fn value_type(&self) -> TypeInfo {
+        match *self {
+            FileFunction::ReadFile |
+            FileFunction::WriteFile |
+            FileFunction::AppendFile => TypeInfo::String,
+            FileFunction::FileExists => TypeInfo::Bool,
+            FileFunction::ListDir => TypeInfo::Array(Box::new(TypeInfo::String)),
+        }
+    }
+
+    /// The value used when the function fails.
+    fn empty_value(&self) -> Datatype {
+        match *self {
+            FileFunction::ReadFile |
+            FileFunction::WriteFile |
+            FileFunction::AppendFile => Datatype::String(String::new()),
+            FileFunction::FileExists => Datatype::Bool(false),
+            FileFunction::ListDir => Datatype::Array { value: vec![], type_: TypeInfo::String },
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        check_argument_count(arguments, self.argument_count())?;
+        for argument in arguments {
+            match *argument {
+                TypeInfo::String | TypeInfo::Any => {}
+                ref other => return Err(TypeError::TypeMismatch(other.clone(), TypeInfo::String)),
+            }
+        }
+        Ok(result_type(self.value_type()))
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        expect_argument_count(arguments, self.argument_count())?;
+        let path: &String = string_argument(&arguments[0])?;
+        let outcome: Result<Datatype, String> = match io_context::allowed_path(path) {
+            Ok(path) => self.use_path(path, arguments),
+            Err(reason) => Err(reason),
+        };
+        let result: Datatype = match outcome {
+            Ok(value) => result(true, value, String::new()),
+            Err(error) => result(false, self.empty_value(), error),
+        };
+        Ok(Rc::new(result))
+    }
+
+    fn use_path(&self, path: PathBuf, arguments: &[RcDatatype]) -> Result<Datatype, String> {
+        match *self {
+            FileFunction::ReadFile => {
+                fs::read_to_string(&path)
+                    .map(Datatype::String)
+                    .map_err(|e| e.to_string())
+            }
+            FileFunction::WriteFile => {
+                let contents: &String = string_argument(&arguments[1]).map_err(|e| format!("{:?}", e))?;
+                fs::write(&path, contents)
+                    .map(|_| Datatype::String(contents.clone()))
+                    .map_err(|e| e.to_string())
+            }
+            FileFunction::AppendFile => {
+                let contents: &String = string_argument(&arguments[1]).map_err(|e| format!("{:?}", e))?;
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(contents.as_bytes()))
+                    .map(|_| Datatype::String(contents.clone()))
+                    .map_err(|e| e.to_string())
+            }
+            FileFunction::FileExists => Ok(Datatype::Bool(path.exists())),
+            FileFunction::ListDir => {
+                let entries = fs::read_dir(&path).map_err(|e| e.to_string())?;
+                let mut names: Vec<String> = vec![];
+                for entry in entries {
+                    let entry = entry.map_err(|e| e.to_string())?;
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+                names.sort();
+                Ok(Datatype::Array {
+                    value: names.into_iter().map(|name| Rc::new(Datatype::String(name))).collect(),
+                    type_: TypeInfo::String,
+                })
+            }
+        }
+    }
+}
+
+/// The type of the struct produced by the file functions.
+fn result_type(value_type: TypeInfo) -> TypeInfo {
+    let mut map: HashMap<String, TypeInfo> = HashMap::new();
+    map.insert("ok".to_string(), TypeInfo::Bool);
+    map.insert("value".to_string(), value_type);
+    map.insert("error".to_string(), TypeInfo::String);
+    TypeInfo::Struct { map }
+}
+
+fn result(ok: bool, value: Datatype, error: String) -> Datatype {
+    let mut map: HashMap<String, Datatype> = HashMap::new();
+    map.insert("ok".to_string(), Datatype::Bool(ok));
+    map.insert("value".to_string(), value);
+    map.insert("error".to_string(), Datatype::String(error));
+    Datatype::Struct {
+        identifier: None,
+        field_order: vec!["ok".to_string(), "value".to_string(), "error".to_string()],
+        map,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use io_context::{IoContext, FileSystemAccess, with_context};
+    use std::cell::RefCell;
+    use std::env;
+    use std::io;
+
+    fn context_with_access(file_system: FileSystemAccess) -> IoContext {
+        IoContext {
+            stdin: Rc::new(RefCell::new(io::empty())),
+            stdout: Rc::new(RefCell::new(io::sink())),
+            stderr: Rc::new(RefCell::new(io::sink())),
+            file_system,
+        }
+    }
+
+    fn string(text: &str) -> RcDatatype {
+        Rc::new(Datatype::String(text.to_string()))
+    }
+
+    fn field(result: &RcDatatype, name: &str) -> Datatype {
+        match **result {
+            Datatype::Struct { ref map, .. } => map[name].clone(),
+            ref other => panic!("Expected a result struct, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn file_functions_are_denied_by_default() {
+        let result = FileFunction::ReadFile.call(&[string("Cargo.toml")]).unwrap();
+        assert_eq!(Datatype::Bool(false), field(&result, "ok"));
+        assert_eq!(Datatype::String(String::new()), field(&result, "value"));
+        assert_ne!(Datatype::String(String::new()), field(&result, "error"));
+    }
+
+    #[test]
+    fn write_append_and_read_file() {
+        let directory: PathBuf = env::temp_dir().join(format!("hlwnpa_file_test_{}", ::std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path: String = directory.join("notes.txt").to_string_lossy().into_owned();
+        with_context(&context_with_access(FileSystemAccess::Within(directory.clone())), || {
+            let written = FileFunction::WriteFile.call(&[string(&path), string("first\n")]).unwrap();
+            assert_eq!(Datatype::Bool(true), field(&written, "ok"));
+            FileFunction::AppendFile.call(&[string(&path), string("second\n")]).unwrap();
+            let read = FileFunction::ReadFile.call(&[string(&path)]).unwrap();
+            assert_eq!(Datatype::String("first\nsecond\n".to_string()), field(&read, "value"));
+
+            let exists = FileFunction::FileExists.call(&[string(&path)]).unwrap();
+            assert_eq!(Datatype::Bool(true), field(&exists, "value"));
+            let listed = FileFunction::ListDir.call(&[string(&directory.to_string_lossy())]).unwrap();
+            assert_eq!(
+                Datatype::Array { value: vec![string("notes.txt")], type_: TypeInfo::String },
+                field(&listed, "value")
+            );
+
+            let outside = FileFunction::ReadFile.call(&[string("Cargo.toml")]).unwrap();
+            assert_eq!(Datatype::Bool(false), field(&outside, "ok"));
+        });
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn missing_file_is_an_error_value() {
+        with_context(&context_with_access(FileSystemAccess::Unrestricted), || {
+            let result = FileFunction::ReadFile.call(&[string("examples/does_not_exist.txt")]).unwrap();
+            assert_eq!(Datatype::Bool(false), field(&result, "ok"));
+        });
+    }
+
+    #[test]
+    fn file_function_types() {
+        assert_eq!(
+            Ok(result_type(TypeInfo::Array(Box::new(TypeInfo::String)))),
+            FileFunction::ListDir.check_types(&[TypeInfo::String])
+        );
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::Number, TypeInfo::String)),
+            FileFunction::WriteFile.check_types(&[TypeInfo::String, TypeInfo::Number])
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use ast::{Datatype, RcDatatype, TypeInfo, VariableStore, LangResult, LangError, call_function};
+use ast::type_checking::{TypeResult, TypeError};
+use super::{check_argument_count, expect_argument_count};
+use std::rc::Rc;
+
+/// Functions that take another function as an argument and call it on the elements of an array.
+/// The function may be declared in the language or be another builtin, like `map(xs, to_float)`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum HigherOrderFunction {
+    Map, // map(xs, f) -> an array of the results of calling f on each element
+    Filter, // filter(xs, predicate) -> the elements for which the predicate returned true
+    Reduce, // reduce(xs, initial, f) -> the result of calling f(accumulator, element) on each element, starting with initial
+    Any, // any(xs, predicate) -> Bool, stops at the first element for which the predicate returned true
+    All, // all(xs, predicate) -> Bool, stops at the first element for which the predicate returned false
+}
+
+impl HigherOrderFunction {
+    pub const ALL: [HigherOrderFunction; 5] = [
+        HigherOrderFunction::Map,
+        HigherOrderFunction::Filter,
+        HigherOrderFunction::Reduce,
+        HigherOrderFunction::Any,
+        HigherOrderFunction::All,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            HigherOrderFunction::Map => "map",
+            HigherOrderFunction::Filter => "filter",
+            HigherOrderFunction::Reduce => "reduce",
+            HigherOrderFunction::Any => "any",
+            HigherOrderFunction::All => "all",
+        }
+    }
+
+    fn argument_count(&self) -> usize {
+        match *self {
+            HigherOrderFunction::Reduce => 3,
+            _ => 2,
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        check_argument_count(arguments, self.argument_count())?;
+        let element_type: TypeInfo = match arguments[0] {
+            TypeInfo::Array(ref element_type) => *element_type.clone(),
+            TypeInfo::Any => TypeInfo::Any,
+            ref other => return Err(TypeError::TypeMismatch(other.clone(), TypeInfo::Array(Box::new(TypeInfo::Any)))),
+        };
+        match *self {
+            HigherOrderFunction::Map => {
+                let result_type: TypeInfo = check_call(&arguments[1], &[element_type])?;
+                Ok(TypeInfo::Array(Box::new(result_type)))
+            }
+            HigherOrderFunction::Filter => {
+                check_predicate(&arguments[1], element_type.clone())?;
+                Ok(TypeInfo::Array(Box::new(element_type)))
+            }
+            HigherOrderFunction::Reduce => {
+                let accumulator_type: TypeInfo = arguments[1].clone();
+                let result_type: TypeInfo = check_call(&arguments[2], &[accumulator_type.clone(), element_type])?;
+                match accumulator_type.unify(&result_type) {
+                    Some(unified_type) => Ok(unified_type),
+                    None => Err(TypeError::TypeMismatch(result_type, accumulator_type)),
+                }
+            }
+            HigherOrderFunction::Any |
+            HigherOrderFunction::All => {
+                check_predicate(&arguments[1], element_type)?;
+                Ok(TypeInfo::Bool)
+            }
+        }
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype], map: &VariableStore) -> LangResult {
+        expect_argument_count(arguments, self.argument_count())?;
+        let (elements, element_type) = match *arguments[0] {
+            Datatype::Array { ref value, ref type_ } => (value, type_),
+            ref other => {
+                return Err(LangError::TypeError {
+                    expected: TypeInfo::Array(Box::new(TypeInfo::Any)),
+                    found: TypeInfo::from(other.clone()),
+                })
+            }
+        };
+        match *self {
+            HigherOrderFunction::Map => {
+                let function: &RcDatatype = &arguments[1];
+                // An empty array can still be given a type if the function declares what it returns.
+                let mut result_type: TypeInfo = match **function {
+                    Datatype::Function { ref return_type, .. } => return_type.clone(),
+                    _ => TypeInfo::Any,
+                };
+                let mut results: Vec<RcDatatype> = vec![];
+                for element in elements {
+                    let result: RcDatatype = call(function, vec![element.clone()], map)?;
+                    let found: TypeInfo = TypeInfo::from(result.as_ref().clone());
+                    result_type = match result_type.unify(&found) {
+                        Some(unified_type) => unified_type,
+                        None => return Err(LangError::MismatchedArrayElementTypes { expected: result_type, found }),
+                    };
+                    results.push(result);
+                }
+                Ok(Rc::new(Datatype::Array { value: results, type_: result_type }))
+            }
+            HigherOrderFunction::Filter => {
+                let mut kept: Vec<RcDatatype> = vec![];
+                for element in elements {
+                    if call_predicate(&arguments[1], element, map)? {
+                        kept.push(element.clone());
+                    }
+                }
+                Ok(Rc::new(Datatype::Array { value: kept, type_: element_type.clone() }))
+            }
+            HigherOrderFunction::Reduce => {
+                let mut accumulator: RcDatatype = arguments[1].clone();
+                for element in elements {
+                    accumulator = call(&arguments[2], vec![accumulator, element.clone()], map)?;
+                }
+                Ok(accumulator)
+            }
+            HigherOrderFunction::Any => {
+                for element in elements {
+                    if call_predicate(&arguments[1], element, map)? {
+                        return Ok(Rc::new(Datatype::Bool(true)));
+                    }
+                }
+                Ok(Rc::new(Datatype::Bool(false)))
+            }
+            HigherOrderFunction::All => {
+                for element in elements {
+                    if !call_predicate(&arguments[1], element, map)? {
+                        return Ok(Rc::new(Datatype::Bool(false)));
+                    }
+                }
+                Ok(Rc::new(Datatype::Bool(true)))
+            }
+        }
+    }
+}
+
+/// Given the type of a function and the types of the arguments it will be called with,
+/// produce the type the function will return.
+fn check_call(function_type: &TypeInfo, arguments: &[TypeInfo]) -> TypeResult {
+    match *function_type {
+        TypeInfo::Function { ref parameters, ref return_type } => {
+            check_argument_count(arguments, parameters.len())?;
+            for (argument, parameter) in arguments.iter().zip(parameters.iter()) {
+                if argument.unify(parameter).is_none() {
+                    return Err(TypeError::TypeMismatch(argument.clone(), parameter.clone()));
+                }
+            }
+            Ok(*return_type.clone())
+        }
+        TypeInfo::BuiltinFunction(ref builtin) => builtin.check_types(arguments),
+        TypeInfo::Any => Ok(TypeInfo::Any),
+        ref other => Err(TypeError::NotAFunction(other.clone())),
+    }
+}
+
+fn check_predicate(function_type: &TypeInfo, element_type: TypeInfo) -> Result<(), TypeError> {
+    let result_type: TypeInfo = check_call(function_type, &[element_type])?;
+    match result_type {
+        TypeInfo::Bool | TypeInfo::Any => Ok(()),
+        other => Err(TypeError::TypeMismatch(other, TypeInfo::Bool)),
+    }
+}
+
+/// Calls the function on a copy of the stack, so the function can't alter the caller's variables.
+fn call(function: &RcDatatype, arguments: Vec<RcDatatype>, map: &VariableStore) -> LangResult {
+    let mut cloned_map: VariableStore = map.clone();
+    call_function(function, arguments, &mut cloned_map)
+}
+
+fn call_predicate(predicate: &RcDatatype, element: &RcDatatype, map: &VariableStore) -> Result<bool, LangError> {
+    match *call(predicate, vec![element.clone()], map)? {
+        Datatype::Bool(result) => Ok(result),
+        _ => Err(LangError::ConditionOnNonBoolean),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std_functions::{Builtin, NumericFunction};
+    use parser::program;
+    use nom::IResult;
+
+    fn numbers(values: &[i32]) -> RcDatatype {
+        Rc::new(Datatype::Array {
+            value: values.iter().map(|value| Rc::new(Datatype::Number(*value))).collect(),
+            type_: TypeInfo::Number,
+        })
+    }
+
+    /// Declares the functions in the source, returning the stack they were declared in.
+    fn declare(source: &str) -> VariableStore {
+        let mut map: VariableStore = VariableStore::new();
+        match program(source.as_bytes()) {
+            IResult::Done(_, ast) => {
+                ast.evaluate(&mut map).unwrap();
+            }
+            _ => panic!("Couldn't parse the test functions"),
+        }
+        map
+    }
+
+    #[test]
+    fn calls_declared_functions() {
+        let map = declare("
+            fn double(x: Number) -> Number { x * 2 }
+            fn is_odd(x: Number) -> Bool { x % 2 == 1 }
+            fn add(a: Number, b: Number) -> Number { a + b }
+        ");
+        let xs = numbers(&[1, 2, 3]);
+        let double = map.get("double").unwrap().clone();
+        let is_odd = map.get("is_odd").unwrap().clone();
+        let add = map.get("add").unwrap().clone();
+
+        assert_eq!(numbers(&[2, 4, 6]), HigherOrderFunction::Map.call(&[xs.clone(), double], &map).unwrap());
+        assert_eq!(numbers(&[1, 3]), HigherOrderFunction::Filter.call(&[xs.clone(), is_odd.clone()], &map).unwrap());
+        assert_eq!(Datatype::Number(6), *HigherOrderFunction::Reduce.call(&[xs.clone(), Rc::new(Datatype::Number(0)), add], &map).unwrap());
+        assert_eq!(Datatype::Bool(true), *HigherOrderFunction::Any.call(&[xs.clone(), is_odd.clone()], &map).unwrap());
+        assert_eq!(Datatype::Bool(false), *HigherOrderFunction::All.call(&[xs, is_odd], &map).unwrap());
+    }
+
+    #[test]
+    fn calls_builtin_functions() {
+        let to_float = Rc::new(Datatype::BuiltinFunction(Builtin::Numeric(NumericFunction::ToFloat)));
+        let floats = HigherOrderFunction::Map.call(&[numbers(&[1]), to_float], &VariableStore::new()).unwrap();
+        assert_eq!(
+            Datatype::Array { value: vec![Rc::new(Datatype::Float(1.0))], type_: TypeInfo::Float },
+            *floats
+        );
+    }
+
+    #[test]
+    fn type_checking() {
+        let number_array = TypeInfo::Array(Box::new(TypeInfo::Number));
+        let number_to_string = TypeInfo::Function { parameters: vec![TypeInfo::Number], return_type: Box::new(TypeInfo::String) };
+        assert_eq!(
+            Ok(TypeInfo::Array(Box::new(TypeInfo::String))),
+            HigherOrderFunction::Map.check_types(&[number_array.clone(), number_to_string.clone()])
+        );
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::String, TypeInfo::Bool)),
+            HigherOrderFunction::Filter.check_types(&[number_array.clone(), number_to_string.clone()])
+        );
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::String, TypeInfo::Number)),
+            HigherOrderFunction::Map.check_types(&[TypeInfo::Array(Box::new(TypeInfo::String)), number_to_string])
+        );
+        assert_eq!(
+            Err(TypeError::NotAFunction(TypeInfo::Number)),
+            HigherOrderFunction::Any.check_types(&[number_array, TypeInfo::Number])
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use ast::{Datatype, RcDatatype, TypeInfo, LangResult};
+use ast::type_checking::TypeResult;
+use super::{check_argument_count, expect_argument_count};
+use io_context;
+use std::rc::Rc;
+
+/// Functions that read from or write to the streams owned by the interpreter.
+/// `print` and `println` are declared in the language, so they aren't included here.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum IoFunction {
+    EPrint, // eprint(value) -> the text of value, writes it to stderr
+    EPrintln, // eprintln(value) -> the text of value with a newline, writes it to stderr
+    ReadLine, // read_line() -> the next line of stdin without its line ending, or "" once stdin is exhausted
+    ReadAll, // read_all() -> the rest of stdin
+}
+
+impl IoFunction {
+    pub const ALL: [IoFunction; 4] = [
+        IoFunction::EPrint,
+        IoFunction::EPrintln,
+        IoFunction::ReadLine,
+        IoFunction::ReadAll,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            IoFunction::EPrint => "eprint",
+            IoFunction::EPrintln => "eprintln",
+            IoFunction::ReadLine => "read_line",
+            IoFunction::ReadAll => "read_all",
+        }
+    }
+
+    fn argument_count(&self) -> usize {
+        match *self {
+            IoFunction::EPrint |
+            IoFunction::EPrintln => 1,
+            IoFunction::ReadLine |
+            IoFunction::ReadAll => 0,
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        // eprint and eprintln accept any value, and every function produces text.
+        check_argument_count(arguments, self.argument_count())?;
+        Ok(TypeInfo::String)
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        expect_argument_count(arguments, self.argument_count())?;
+        match *self {
+            IoFunction::EPrint => eprint(format!("{}", arguments[0])),
+            // Like println, eprintln returns the string with the newline that was printed.
+            IoFunction::EPrintln => eprint(format!("{}\n", arguments[0])),
+            IoFunction::ReadLine => Ok(Rc::new(Datatype::String(io_context::read_line()?))),
+            IoFunction::ReadAll => Ok(Rc::new(Datatype::String(io_context::read_all()?))),
+        }
+    }
+}
+
+fn eprint(text: String) -> LangResult {
+    io_context::write_stderr(&text)?;
+    Ok(Rc::new(Datatype::String(text)))
+}
+
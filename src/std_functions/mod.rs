@@ -0,0 +1,227 @@
+
+use std::collections::HashMap;
+use ast::{Ast, SExpression, Datatype, TypeInfo, VariableStore, RcDatatype, LangResult, LangError};
+use ast::type_checking::{TypeStore, TypeResult, TypeError, Mutability};
+use std::rc::Rc;
+
+mod numeric;
+pub use self::numeric::NumericFunction;
+mod array;
+pub use self::array::ArrayFunction;
+mod higher_order;
+pub use self::higher_order::HigherOrderFunction;
+mod string;
+pub use self::string::StringFunction;
+mod io;
+pub use self::io::IoFunction;
+mod file;
+pub use self::file::FileFunction;
+mod assert;
+pub use self::assert::AssertFunction;
+mod native;
+pub use self::native::{NativeFunction, add_native_function};
+
+pub fn add_std_functions(map: &mut HashMap<String, Rc<Datatype>>) {
+    add_print_function(map);
+    add_println_function(map);
+    add_builtin_functions(map);
+}
+
+/// Registers the types of the standard functions, so programs that call them can be type checked.
+pub fn add_std_function_types(type_store: &mut TypeStore) {
+    let mut map: VariableStore = VariableStore::new();
+    add_std_functions(&mut map);
+    for (identifier, datatype) in map {
+        type_store.insert(identifier, Mutability::Immutable(TypeInfo::from(datatype.as_ref().clone())));
+    }
+}
+
+
+/// Functions that are implemented in Rust instead of HLWNPA.
+/// Unlike functions declared in the language, each builtin determines its own return type from
+/// the types of its arguments, which allows them to accept more than one type.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Builtin {
+    Numeric(NumericFunction),
+    Array(ArrayFunction),
+    HigherOrder(HigherOrderFunction),
+    String(StringFunction),
+    Io(IoFunction),
+    File(FileFunction),
+    Assert(AssertFunction),
+}
+
+impl Builtin {
+    /// Every builtin that is added to the global map.
+    fn all() -> Vec<Builtin> {
+        NumericFunction::ALL.iter().map(|f| Builtin::Numeric(*f))
+            .chain(ArrayFunction::ALL.iter().map(|f| Builtin::Array(*f)))
+            .chain(HigherOrderFunction::ALL.iter().map(|f| Builtin::HigherOrder(*f)))
+            .chain(StringFunction::ALL.iter().map(|f| Builtin::String(*f)))
+            .chain(IoFunction::ALL.iter().map(|f| Builtin::Io(*f)))
+            .chain(FileFunction::ALL.iter().map(|f| Builtin::File(*f)))
+            .chain(AssertFunction::ALL.iter().map(|f| Builtin::Assert(*f)))
+            .collect()
+    }
+
+    /// The identifier the builtin is stored under.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Builtin::Numeric(ref f) => f.name(),
+            Builtin::Array(ref f) => f.name(),
+            Builtin::HigherOrder(ref f) => f.name(),
+            Builtin::String(ref f) => f.name(),
+            Builtin::Io(ref f) => f.name(),
+            Builtin::File(ref f) => f.name(),
+            Builtin::Assert(ref f) => f.name(),
+        }
+    }
+
+    /// Given the types of the arguments, produce the type the builtin will return,
+    /// or an error if the builtin can't accept those arguments.
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        match *self {
+            Builtin::Numeric(ref f) => f.check_types(arguments),
+            Builtin::Array(ref f) => f.check_types(arguments),
+            Builtin::HigherOrder(ref f) => f.check_types(arguments),
+            Builtin::String(ref f) => f.check_types(arguments),
+            Builtin::Io(ref f) => f.check_types(arguments),
+            Builtin::File(ref f) => f.check_types(arguments),
+            Builtin::Assert(ref f) => f.check_types(arguments),
+        }
+    }
+
+    /// Run the builtin with already evaluated arguments.
+    /// The stack is needed by builtins that call functions passed to them.
+    pub fn call(&self, arguments: Vec<RcDatatype>, map: &mut VariableStore) -> LangResult {
+        match *self {
+            Builtin::Numeric(ref f) => f.call(&arguments),
+            Builtin::Array(ref f) => f.call(&arguments),
+            Builtin::HigherOrder(ref f) => f.call(&arguments, map),
+            Builtin::String(ref f) => f.call(&arguments),
+            Builtin::Io(ref f) => f.call(&arguments),
+            Builtin::File(ref f) => f.call(&arguments),
+            Builtin::Assert(ref f) => f.call(&arguments),
+        }
+    }
+}
+
+fn add_builtin_functions(map: &mut VariableStore) {
+    for builtin in Builtin::all() {
+        map.insert(builtin.name().to_string(), Rc::new(Datatype::BuiltinFunction(builtin)));
+    }
+}
+
+/// Checks that a builtin was given the number of arguments it expects while type checking.
+fn check_argument_count(arguments: &[TypeInfo], expected: usize) -> Result<(), TypeError> {
+    if arguments.len() == expected {
+        Ok(())
+    } else {
+        Err(TypeError::ParameterLengthMismatch { expected, found: arguments.len() })
+    }
+}
+
+/// Checks that a builtin was given the number of arguments it expects at runtime.
+fn expect_argument_count(arguments: &[RcDatatype], expected: usize) -> Result<(), LangError> {
+    if arguments.len() == expected {
+        Ok(())
+    } else {
+        Err(LangError::ParameterLengthMismatch)
+    }
+}
+
+/// Gets the String a builtin was given, producing a type error for any other value.
+fn string_argument(argument: &RcDatatype) -> Result<&String, LangError> {
+    match **argument {
+        Datatype::String(ref text) => Ok(text),
+        ref other => {
+            Err(LangError::TypeError {
+                expected: TypeInfo::String,
+                found: TypeInfo::from(other.clone()),
+            })
+        }
+    }
+}
+
+/// Checks that an index into an array or string is a Number while type checking.
+fn expect_index(index_type: &TypeInfo) -> Result<(), TypeError> {
+    match *index_type {
+        TypeInfo::Number | TypeInfo::Any => Ok(()),
+        ref other => Err(TypeError::TypeMismatch(other.clone(), TypeInfo::Number)),
+    }
+}
+
+/// Gets an index that can be used to insert into an array or string of the given length,
+/// so it may be one past the last element.
+fn index_argument(argument: &RcDatatype, length: usize) -> Result<usize, LangError> {
+    match **argument {
+        Datatype::Number(index) if index < 0 => Err(LangError::NegativeIndex(index)),
+        Datatype::Number(index) if index as usize > length => Err(LangError::OutOfBoundsArrayAccess),
+        Datatype::Number(index) => Ok(index as usize),
+        ref other => Err(LangError::InvalidIndexType(other.clone())),
+    }
+}
+
+
+fn add_print_function(map: &mut VariableStore) {
+    let to_print = Ast::ValueIdentifier("to_print".to_string());
+    declare_print_function(map, "print", to_print);
+}
+
+/// println shows its argument with `to_string`, so the newline can be added to any value.
+fn add_println_function(map: &mut VariableStore) {
+    let to_print = Ast::SExpr(SExpression::Add(
+        Box::new(Ast::SExpr(SExpression::ExecuteFn {
+            identifier: Box::new(Ast::ValueIdentifier(StringFunction::ToString.name().to_string())),
+            parameters: Box::new(Ast::ExpressionList(vec![Ast::ValueIdentifier("to_print".to_string())])),
+        })),
+        Box::new(Ast::Literal(Datatype::String("\n".to_string()))),
+    ));
+    declare_print_function(map, "println", to_print);
+}
+
+/// Declares a function that takes a value of any type as `to_print`, and prints the given expression.
+fn declare_print_function(map: &mut VariableStore, name: &str, to_print: Ast) {
+    let ast: Ast = Ast::SExpr(SExpression::DeclareFunction {
+        identifier: Box::new(Ast::ValueIdentifier(name.to_string())),
+        function_datatype: Box::new(Ast::Literal(Datatype::Function {
+            parameters: Box::new(Ast::ExpressionList(vec![
+                Ast::SExpr(SExpression::TypeAssignment {
+                    identifier: Box::new(Ast::ValueIdentifier("to_print".to_string())),
+                    type_info: Box::new(Ast::Type(TypeInfo::Any)),
+                }),
+            ])),
+            body: Box::new(Ast::ExpressionList(vec![
+                Ast::SExpr(SExpression::Print(Box::new(to_print))),
+            ])),
+            return_type: TypeInfo::String,
+        })),
+    });
+    if let Err(error) = ast.evaluate(map) {
+        panic!("Couldn't add {}(): {:?}", name, error);
+    }
+}
+
+
+#[test]
+fn expect_print_function_to_be_added_to_global_map() {
+    let mut map: VariableStore = VariableStore::new();
+    add_print_function(&mut map);
+    let mut expected_map: VariableStore = VariableStore::new();
+    let print_fn: Datatype = Datatype::Function {
+        parameters: (Box::new(Ast::ExpressionList(vec![
+            Ast::SExpr(SExpression::TypeAssignment {
+                identifier: Box::new(Ast::ValueIdentifier("to_print".to_string())),
+                type_info: Box::new(Ast::Type(TypeInfo::Any)),
+            }),
+        ]))),
+        body: (Box::new(Ast::ExpressionList(vec![
+            Ast::SExpr(SExpression::Print(
+                Box::new(Ast::ValueIdentifier("to_print".to_string())),
+            )),
+        ]))),
+        return_type: TypeInfo::String,
+    };
+    expected_map.insert("print".to_string(), Rc::new(print_fn));
+    assert_eq!(expected_map, map);
+}
@@ -0,0 +1,131 @@
+use ast::{Datatype, RcDatatype, TypeInfo, VariableStore, LangResult, LangError};
+use ast::type_checking::{TypeStore, Mutability};
+use std::fmt;
+use std::rc::Rc;
+
+pub type NativeClosure = Rc<dyn Fn(&[RcDatatype]) -> LangResult>;
+
+/// A function implemented by the Rust program that embeds the interpreter.
+/// Unlike a `Builtin`, it has a fixed signature, so it is type checked like a function declared in the language.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub parameters: Vec<TypeInfo>,
+    pub return_type: TypeInfo,
+    pub function: NativeClosure,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, parameters: Vec<TypeInfo>, return_type: TypeInfo, function: F) -> NativeFunction
+        where F: Fn(&[RcDatatype]) -> LangResult + 'static
+    {
+        NativeFunction {
+            name: name.to_string(),
+            parameters,
+            return_type,
+            function: Rc::new(function),
+        }
+    }
+
+    /// Checks the arguments against the declared parameters before running the closure,
+    /// then checks the value it produced against the declared return type.
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        if arguments.len() != self.parameters.len() {
+            return Err(LangError::ParameterLengthMismatch);
+        }
+        for (argument, parameter) in arguments.iter().zip(self.parameters.iter()) {
+            let found: TypeInfo = TypeInfo::from(argument.as_ref().clone());
+            if parameter.unify(&found).is_none() {
+                return Err(LangError::TypeError { expected: parameter.clone(), found });
+            }
+        }
+        let output: RcDatatype = (self.function)(arguments)?;
+        if self.return_type.unify(&TypeInfo::from(output.as_ref().clone())).is_some() {
+            Ok(output)
+        } else {
+            Err(LangError::ReturnTypeDoesNotMatchReturnValue)
+        }
+    }
+
+    /// The type the checker uses for calls to this function.
+    pub fn type_info(&self) -> TypeInfo {
+        TypeInfo::Function {
+            parameters: self.parameters.clone(),
+            return_type: Box::new(self.return_type.clone()),
+        }
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NativeFunction({}: {:?} -> {:?})", self.name, self.parameters, self.return_type)
+    }
+}
+
+/// Closures can't be compared, so two native functions are only equal if they share the same closure.
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &NativeFunction) -> bool {
+        self.name == other.name && Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+/// Makes a native function callable from programs, under its name.
+/// It is added to both the map used for evaluation and the store used for type checking,
+/// and like the other standard functions, it can't be reassigned.
+pub fn add_native_function(map: &mut VariableStore, type_store: &mut TypeStore, function: NativeFunction) {
+    type_store.insert(function.name.clone(), Mutability::Immutable(function.type_info()));
+    map.insert(function.name.clone(), Rc::new(Datatype::NativeFunction(function)));
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ast::{Ast, SExpression};
+
+    fn add_double(map: &mut VariableStore, type_store: &mut TypeStore) {
+        let double = NativeFunction::new("double", vec![TypeInfo::Number], TypeInfo::Number, |arguments| {
+            match *arguments[0] {
+                Datatype::Number(value) => Ok(Rc::new(Datatype::Number(value * 2))),
+                _ => Err(LangError::ParserShouldHaveRejected),
+            }
+        });
+        add_native_function(map, type_store, double);
+    }
+
+    fn call_double(argument: Datatype) -> Ast {
+        Ast::SExpr(SExpression::ExecuteFn {
+            identifier: Box::new(Ast::ValueIdentifier("double".to_string())),
+            parameters: Box::new(Ast::ExpressionList(vec![Ast::Literal(argument)])),
+        })
+    }
+
+    #[test]
+    fn native_function_is_callable() {
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        add_double(&mut map, &mut type_store);
+        let ast = call_double(Datatype::Number(21));
+        assert_eq!(Ok(Mutability::Mutable(TypeInfo::Number)), ast.check_types(&mut type_store));
+        assert_eq!(Datatype::Number(42), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn native_function_arguments_are_checked() {
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        add_double(&mut map, &mut type_store);
+        let ast = call_double(Datatype::String("21".to_string()));
+        assert!(ast.check_types(&mut type_store).is_err());
+        assert_eq!(
+            LangError::TypeError { expected: TypeInfo::Number, found: TypeInfo::String },
+            ast.evaluate(&mut map).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn native_function_return_type_is_checked() {
+        let liar = NativeFunction::new("liar", vec![], TypeInfo::Number, |_| Ok(Rc::new(Datatype::Bool(true))));
+        assert_eq!(LangError::ReturnTypeDoesNotMatchReturnValue, liar.call(&[]).unwrap_err());
+    }
+}
@@ -0,0 +1,130 @@
+use ast::{Datatype, RcDatatype, TypeInfo, LangResult, LangError};
+use ast::type_checking::{TypeResult, TypeError};
+use super::{check_argument_count, expect_argument_count};
+use std::rc::Rc;
+
+/// Explicit conversions between Numbers and Floats.
+/// Every conversion accepts either a Number or a Float.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum NumericFunction {
+    ToFloat, // Widens the value to a Float.
+    ToNumber, // Truncates the value towards zero.
+    Round, // Rounds half-way cases away from zero.
+    Floor, // Rounds towards negative infinity.
+}
+
+impl NumericFunction {
+    pub const ALL: [NumericFunction; 4] = [
+        NumericFunction::ToFloat,
+        NumericFunction::ToNumber,
+        NumericFunction::Round,
+        NumericFunction::Floor,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            NumericFunction::ToFloat => "to_float",
+            NumericFunction::ToNumber => "to_number",
+            NumericFunction::Round => "round",
+            NumericFunction::Floor => "floor",
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        check_argument_count(arguments, 1)?;
+        match arguments[0] {
+            TypeInfo::Number | TypeInfo::Float | TypeInfo::Any => {}
+            ref other => return Err(TypeError::TypeMismatch(other.clone(), TypeInfo::Float)),
+        }
+        match *self {
+            NumericFunction::ToFloat => Ok(TypeInfo::Float),
+            _ => Ok(TypeInfo::Number),
+        }
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        expect_argument_count(arguments, 1)?;
+        // Every i32 can be represented exactly by a f64, so Numbers can share the Float logic.
+        let value: f64 = match *arguments[0] {
+            Datatype::Number(number) => number as f64,
+            Datatype::Float(float) => float,
+            ref other => {
+                return Err(LangError::TypeError {
+                    expected: TypeInfo::Float,
+                    found: TypeInfo::from(other.clone()),
+                })
+            }
+        };
+        match *self {
+            NumericFunction::ToFloat => Ok(Rc::new(Datatype::Float(value))),
+            NumericFunction::ToNumber => float_to_number(value.trunc()),
+            NumericFunction::Round => float_to_number(value.round()),
+            NumericFunction::Floor => float_to_number(value.floor()),
+        }
+    }
+}
+
+/// Converts an already integral Float into a Number.
+/// NaN, infinities and values outside of the range of a Number can't be converted.
+fn float_to_number(value: f64) -> LangResult {
+    if value.is_nan() || value < i32::MIN as f64 || value > i32::MAX as f64 {
+        Err(LangError::FloatNotRepresentableAsNumber(value))
+    } else {
+        Ok(Rc::new(Datatype::Number(value as i32)))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(function: NumericFunction, datatype: Datatype) -> LangResult {
+        function.call(&[Rc::new(datatype)])
+    }
+
+    #[test]
+    fn to_float_widens_numbers() {
+        assert_eq!(Datatype::Float(3.0), *call(NumericFunction::ToFloat, Datatype::Number(3)).unwrap());
+        assert_eq!(Datatype::Float(2.5), *call(NumericFunction::ToFloat, Datatype::Float(2.5)).unwrap());
+    }
+
+    #[test]
+    fn to_number_truncates_towards_zero() {
+        assert_eq!(Datatype::Number(2), *call(NumericFunction::ToNumber, Datatype::Float(2.9)).unwrap());
+        assert_eq!(Datatype::Number(-2), *call(NumericFunction::ToNumber, Datatype::Float(-2.9)).unwrap());
+        assert_eq!(Datatype::Number(7), *call(NumericFunction::ToNumber, Datatype::Number(7)).unwrap());
+    }
+
+    #[test]
+    fn round_and_floor() {
+        assert_eq!(Datatype::Number(3), *call(NumericFunction::Round, Datatype::Float(2.5)).unwrap());
+        assert_eq!(Datatype::Number(-3), *call(NumericFunction::Round, Datatype::Float(-2.5)).unwrap());
+        assert_eq!(Datatype::Number(2), *call(NumericFunction::Floor, Datatype::Float(2.5)).unwrap());
+        assert_eq!(Datatype::Number(-3), *call(NumericFunction::Floor, Datatype::Float(-2.5)).unwrap());
+    }
+
+    #[test]
+    fn unrepresentable_floats_are_rejected() {
+        assert!(call(NumericFunction::ToNumber, Datatype::Float(f64::NAN)).is_err());
+        assert_eq!(
+            LangError::FloatNotRepresentableAsNumber(f64::INFINITY),
+            call(NumericFunction::Floor, Datatype::Float(f64::INFINITY)).unwrap_err()
+        );
+        assert!(call(NumericFunction::Round, Datatype::Float(3000000000.0)).is_err());
+    }
+
+    #[test]
+    fn conversions_reject_non_numbers() {
+        assert_eq!(
+            LangError::TypeError { expected: TypeInfo::Float, found: TypeInfo::String },
+            call(NumericFunction::ToFloat, Datatype::String("3".to_string())).unwrap_err()
+        );
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::Bool, TypeInfo::Float)),
+            NumericFunction::Round.check_types(&[TypeInfo::Bool])
+        );
+        assert_eq!(Ok(TypeInfo::Float), NumericFunction::ToFloat.check_types(&[TypeInfo::Number]));
+        assert_eq!(Ok(TypeInfo::Number), NumericFunction::Floor.check_types(&[TypeInfo::Float]));
+    }
+}
@@ -0,0 +1,329 @@
+use ast::{Datatype, RcDatatype, TypeInfo, LangResult, LangError};
+use ast::type_checking::{TypeResult, TypeError};
+use super::{check_argument_count, expect_argument_count, string_argument, expect_index, index_argument};
+use std::rc::Rc;
+
+/// Functions for working with strings.
+/// Positions and lengths count characters rather than bytes, matching how strings are indexed with `s[i]`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum StringFunction {
+    Len, // len(s) -> the number of characters
+    Substring, // substring(s, start, end) -> the characters from start up to, but not including, end
+    Split, // split(s, separator) -> [String], an empty separator splits the string into its characters
+    Join, // join(strings, separator) -> String
+    Trim, // trim(s) -> s without leading or trailing whitespace
+    ToUpper, // to_upper(s) -> String
+    ToLower, // to_lower(s) -> String
+    Replace, // replace(s, from, to) -> s with every occurrence of from replaced by to
+    StartsWith, // starts_with(s, prefix) -> Bool
+    EndsWith, // ends_with(s, suffix) -> Bool
+    Contains, // contains(s, substring) -> Bool
+    Find, // find(s, substring) -> the index of the first occurrence, or -1
+    Chars, // chars(s) -> [String], holding one character each
+    ParseNumber, // parse_number(s) -> Number, ignoring surrounding whitespace
+    ParseFloat, // parse_float(s) -> Float, ignoring surrounding whitespace
+    ToString, // to_string(value) -> String, showing any value the way print would
+}
+
+impl StringFunction {
+    /// `len` and `contains` aren't included, because they share their names with array functions.
+    /// The array functions forward to these when they are given a String.
+    pub const ALL: [StringFunction; 14] = [
+        StringFunction::Substring,
+        StringFunction::Split,
+        StringFunction::Join,
+        StringFunction::Trim,
+        StringFunction::ToUpper,
+        StringFunction::ToLower,
+        StringFunction::Replace,
+        StringFunction::StartsWith,
+        StringFunction::EndsWith,
+        StringFunction::Find,
+        StringFunction::Chars,
+        StringFunction::ParseNumber,
+        StringFunction::ParseFloat,
+        StringFunction::ToString,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            StringFunction::Len => "len",
+            StringFunction::Substring => "substring",
+            StringFunction::Split => "split",
+            StringFunction::Join => "join",
+            StringFunction::Trim => "trim",
+            StringFunction::ToUpper => "to_upper",
+            StringFunction::ToLower => "to_lower",
+            StringFunction::Replace => "replace",
+            StringFunction::StartsWith => "starts_with",
+            StringFunction::EndsWith => "ends_with",
+            StringFunction::Contains => "contains",
+            StringFunction::Find => "find",
+            StringFunction::Chars => "chars",
+            StringFunction::ParseNumber => "parse_number",
+            StringFunction::ParseFloat => "parse_float",
+            StringFunction::ToString => "to_string",
+        }
+    }
+
+    fn argument_count(&self) -> usize {
+        match *self {
+            StringFunction::Len |
+            StringFunction::Trim |
+            StringFunction::ToUpper |
+            StringFunction::ToLower |
+            StringFunction::Chars |
+            StringFunction::ParseNumber |
+            StringFunction::ParseFloat |
+            StringFunction::ToString => 1,
+            StringFunction::Split |
+            StringFunction::Join |
+            StringFunction::StartsWith |
+            StringFunction::EndsWith |
+            StringFunction::Contains |
+            StringFunction::Find => 2,
+            StringFunction::Substring |
+            StringFunction::Replace => 3,
+        }
+    }
+
+    pub fn check_types(&self, arguments: &[TypeInfo]) -> TypeResult {
+        check_argument_count(arguments, self.argument_count())?;
+        match *self {
+            StringFunction::Join => {
+                let string_array = TypeInfo::Array(Box::new(TypeInfo::String));
+                if string_array.unify(&arguments[0]).is_none() {
+                    return Err(TypeError::TypeMismatch(arguments[0].clone(), string_array));
+                }
+            }
+            StringFunction::ToString => {}
+            _ => expect_string(&arguments[0])?,
+        }
+        match *self {
+            StringFunction::Len => Ok(TypeInfo::Number),
+            StringFunction::Substring => {
+                expect_index(&arguments[1])?;
+                expect_index(&arguments[2])?;
+                Ok(TypeInfo::String)
+            }
+            StringFunction::Split => {
+                expect_string(&arguments[1])?;
+                Ok(TypeInfo::Array(Box::new(TypeInfo::String)))
+            }
+            StringFunction::Join => {
+                expect_string(&arguments[1])?;
+                Ok(TypeInfo::String)
+            }
+            StringFunction::Trim |
+            StringFunction::ToUpper |
+            StringFunction::ToLower => Ok(TypeInfo::String),
+            StringFunction::Replace => {
+                expect_string(&arguments[1])?;
+                expect_string(&arguments[2])?;
+                Ok(TypeInfo::String)
+            }
+            StringFunction::StartsWith |
+            StringFunction::EndsWith |
+            StringFunction::Contains => {
+                expect_string(&arguments[1])?;
+                Ok(TypeInfo::Bool)
+            }
+            StringFunction::Find => {
+                expect_string(&arguments[1])?;
+                Ok(TypeInfo::Number)
+            }
+            StringFunction::Chars => Ok(TypeInfo::Array(Box::new(TypeInfo::String))),
+            StringFunction::ParseNumber => Ok(TypeInfo::Number),
+            StringFunction::ParseFloat => Ok(TypeInfo::Float),
+            StringFunction::ToString => Ok(TypeInfo::String),
+        }
+    }
+
+    pub fn call(&self, arguments: &[RcDatatype]) -> LangResult {
+        expect_argument_count(arguments, self.argument_count())?;
+        // Every function other than join and to_string takes the string to work on first.
+        let string = || string_argument(&arguments[0]);
+        match *self {
+            StringFunction::Len => Ok(Rc::new(Datatype::Number(string()?.chars().count() as i32))),
+            StringFunction::Substring => {
+                let characters: Vec<char> = string()?.chars().collect();
+                let start = index_argument(&arguments[1], characters.len())?;
+                let end = index_argument(&arguments[2], characters.len())?;
+                if start > end {
+                    return Err(LangError::OutOfBoundsArrayAccess);
+                }
+                Ok(Rc::new(Datatype::String(characters[start..end].iter().collect())))
+            }
+            StringFunction::Split => {
+                let separator: &String = string_argument(&arguments[1])?;
+                if separator.is_empty() {
+                    return StringFunction::Chars.call(&arguments[..1]);
+                }
+                Ok(create_string_array(string()?.split(separator.as_str()).map(String::from).collect()))
+            }
+            StringFunction::Join => {
+                let separator: &String = string_argument(&arguments[1])?;
+                match *arguments[0] {
+                    Datatype::Array { ref value, .. } => {
+                        let strings: Vec<&str> = value
+                            .iter()
+                            .map(|element| string_argument(element).map(String::as_str))
+                            .collect::<Result<Vec<&str>, LangError>>()?;
+                        Ok(Rc::new(Datatype::String(strings.join(separator))))
+                    }
+                    ref other => {
+                        Err(LangError::TypeError {
+                            expected: TypeInfo::Array(Box::new(TypeInfo::String)),
+                            found: TypeInfo::from(other.clone()),
+                        })
+                    }
+                }
+            }
+            StringFunction::Trim => Ok(Rc::new(Datatype::String(string()?.trim().to_string()))),
+            StringFunction::ToUpper => Ok(Rc::new(Datatype::String(string()?.to_uppercase()))),
+            StringFunction::ToLower => Ok(Rc::new(Datatype::String(string()?.to_lowercase()))),
+            StringFunction::Replace => {
+                let from: &String = string_argument(&arguments[1])?;
+                let to: &String = string_argument(&arguments[2])?;
+                Ok(Rc::new(Datatype::String(string()?.replace(from.as_str(), to))))
+            }
+            StringFunction::StartsWith => {
+                let prefix: &String = string_argument(&arguments[1])?;
+                Ok(Rc::new(Datatype::Bool(string()?.starts_with(prefix.as_str()))))
+            }
+            StringFunction::EndsWith => {
+                let suffix: &String = string_argument(&arguments[1])?;
+                Ok(Rc::new(Datatype::Bool(string()?.ends_with(suffix.as_str()))))
+            }
+            StringFunction::Contains => {
+                let substring: &String = string_argument(&arguments[1])?;
+                Ok(Rc::new(Datatype::Bool(string()?.contains(substring.as_str()))))
+            }
+            StringFunction::Find => {
+                let substring: &String = string_argument(&arguments[1])?;
+                let string: &String = string()?;
+                let index: i32 = match string.find(substring.as_str()) {
+                    // Convert the byte offset into a character index.
+                    Some(byte_index) => string[..byte_index].chars().count() as i32,
+                    None => -1,
+                };
+                Ok(Rc::new(Datatype::Number(index)))
+            }
+            StringFunction::Chars => Ok(create_string_array(string()?.chars().map(|c| c.to_string()).collect())),
+            StringFunction::ParseNumber => {
+                let string: &String = string()?;
+                match string.trim().parse::<i32>() {
+                    Ok(number) => Ok(Rc::new(Datatype::Number(number))),
+                    Err(_) => Err(LangError::CouldNotParseNumber(string.clone())),
+                }
+            }
+            StringFunction::ParseFloat => {
+                let string: &String = string()?;
+                match string.trim().parse::<f64>() {
+                    Ok(float) => Ok(Rc::new(Datatype::Float(float))),
+                    Err(_) => Err(LangError::CouldNotParseNumber(string.clone())),
+                }
+            }
+            StringFunction::ToString => Ok(Rc::new(Datatype::String(format!("{}", arguments[0])))),
+        }
+    }
+}
+
+fn expect_string(argument_type: &TypeInfo) -> Result<(), TypeError> {
+    match *argument_type {
+        TypeInfo::String | TypeInfo::Any => Ok(()),
+        ref other => Err(TypeError::TypeMismatch(other.clone(), TypeInfo::String)),
+    }
+}
+
+fn create_string_array(strings: Vec<String>) -> RcDatatype {
+    Rc::new(Datatype::Array {
+        value: strings.into_iter().map(|string| Rc::new(Datatype::String(string))).collect(),
+        type_: TypeInfo::String,
+    })
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn string(value: &str) -> RcDatatype {
+        Rc::new(Datatype::String(value.to_string()))
+    }
+
+    fn strings(values: &[&str]) -> RcDatatype {
+        create_string_array(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    fn number(value: i32) -> RcDatatype {
+        Rc::new(Datatype::Number(value))
+    }
+
+    #[test]
+    fn transforms_strings() {
+        assert_eq!(string("ell"), StringFunction::Substring.call(&[string("hello"), number(1), number(4)]).unwrap());
+        assert_eq!(string("hi"), StringFunction::Trim.call(&[string("  hi\n")]).unwrap());
+        assert_eq!(string("HI"), StringFunction::ToUpper.call(&[string("hi")]).unwrap());
+        assert_eq!(string("hi"), StringFunction::ToLower.call(&[string("HI")]).unwrap());
+        assert_eq!(string("b-b"), StringFunction::Replace.call(&[string("a-a"), string("a"), string("b")]).unwrap());
+    }
+
+    #[test]
+    fn splits_and_joins() {
+        assert_eq!(strings(&["a", "b", "c"]), StringFunction::Split.call(&[string("a,b,c"), string(",")]).unwrap());
+        assert_eq!(strings(&["a", "b"]), StringFunction::Split.call(&[string("ab"), string("")]).unwrap());
+        assert_eq!(string("a, b"), StringFunction::Join.call(&[strings(&["a", "b"]), string(", ")]).unwrap());
+        assert_eq!(strings(&["h", "é"]), StringFunction::Chars.call(&[string("hé")]).unwrap());
+    }
+
+    #[test]
+    fn searches_count_characters() {
+        assert_eq!(number(2), StringFunction::Len.call(&[string("hé")]).unwrap());
+        assert_eq!(number(2), StringFunction::Find.call(&[string("éél"), string("l")]).unwrap());
+        assert_eq!(number(-1), StringFunction::Find.call(&[string("abc"), string("z")]).unwrap());
+        assert_eq!(Datatype::Bool(true), *StringFunction::StartsWith.call(&[string("hello"), string("he")]).unwrap());
+        assert_eq!(Datatype::Bool(false), *StringFunction::EndsWith.call(&[string("hello"), string("he")]).unwrap());
+        assert_eq!(Datatype::Bool(true), *StringFunction::Contains.call(&[string("hello"), string("ll")]).unwrap());
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(number(-12), StringFunction::ParseNumber.call(&[string(" -12\n")]).unwrap());
+        assert_eq!(Datatype::Float(2.5), *StringFunction::ParseFloat.call(&[string("2.5")]).unwrap());
+        assert_eq!(
+            LangError::CouldNotParseNumber("2.5".to_string()),
+            StringFunction::ParseNumber.call(&[string("2.5")]).unwrap_err()
+        );
+        assert!(StringFunction::ParseFloat.call(&[string("two")]).is_err());
+    }
+
+    #[test]
+    fn to_string_accepts_any_value() {
+        assert_eq!(string("12"), StringFunction::ToString.call(&[number(12)]).unwrap());
+        assert_eq!(string("hi"), StringFunction::ToString.call(&[string("hi")]).unwrap());
+        assert_eq!(string(r#"["a", "b"]"#), StringFunction::ToString.call(&[strings(&["a", "b"])]).unwrap());
+        assert_eq!(Ok(TypeInfo::String), StringFunction::ToString.check_types(&[TypeInfo::Bool]));
+    }
+
+    #[test]
+    fn out_of_range_substrings_are_rejected() {
+        assert_eq!(LangError::OutOfBoundsArrayAccess, StringFunction::Substring.call(&[string("hi"), number(0), number(3)]).unwrap_err());
+        assert_eq!(LangError::OutOfBoundsArrayAccess, StringFunction::Substring.call(&[string("hi"), number(2), number(1)]).unwrap_err());
+    }
+
+    #[test]
+    fn type_checking() {
+        let string_array = TypeInfo::Array(Box::new(TypeInfo::String));
+        assert_eq!(Ok(string_array.clone()), StringFunction::Split.check_types(&[TypeInfo::String, TypeInfo::String]));
+        assert_eq!(Ok(TypeInfo::String), StringFunction::Join.check_types(&[string_array, TypeInfo::String]));
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::Array(Box::new(TypeInfo::Number)), TypeInfo::Array(Box::new(TypeInfo::String)))),
+            StringFunction::Join.check_types(&[TypeInfo::Array(Box::new(TypeInfo::Number)), TypeInfo::String])
+        );
+        assert_eq!(
+            Err(TypeError::TypeMismatch(TypeInfo::Float, TypeInfo::Number)),
+            StringFunction::Substring.check_types(&[TypeInfo::String, TypeInfo::Number, TypeInfo::Float])
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use ast::{Ast, SExpression, TypeInfo, LangError};
+use interpreter::{Interpreter, Snapshot, parse};
+use io_context::FileSystemAccess;
+
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Functions whose names start with this, and take no parameters, are run as tests.
+const TEST_FUNCTION_PREFIX: &str = "test_";
+
+/// The outcome of running one test function, or of loading a file that tests couldn't be run from.
+#[derive(Debug, PartialEq)]
+pub struct TestResult {
+    pub file: PathBuf,
+    /// The name of the test function, or None if the file couldn't be loaded.
+    pub name: Option<String>,
+    pub outcome: Result<(), LangError>,
+}
+
+impl TestResult {
+    fn description(&self) -> String {
+        match self.name {
+            Some(ref name) => format!("{}::{}", self.file.display(), name),
+            None => format!("{}", self.file.display()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.outcome.is_ok()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+}
+
+/// Lists each test, then the errors of the ones that failed, then a summary.
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in &self.results {
+            let status: &str = if result.outcome.is_ok() { "ok" } else { "FAILED" };
+            writeln!(f, "test {} ... {}", result.description(), status)?;
+        }
+        for result in &self.results {
+            if let Err(ref error) = result.outcome {
+                writeln!(f, "\n---- {} ----\n{}", result.description(), error)?;
+            }
+        }
+        let status: &str = if self.failed() == 0 { "ok" } else { "FAILED" };
+        writeln!(f, "\ntest result: {}. {} passed; {} failed", status, self.passed(), self.failed())
+    }
+}
+
+/// Runs the test functions in the `.hlw` file, or in every `.hlw` file in the directory and its subdirectories.
+///
+/// Only files that declare test functions are evaluated, once each, and the globals are put back after each test,
+/// so tests can't affect each other. A test passes if calling it doesn't produce an error.
+pub fn run_tests(path: &Path, file_system: &FileSystemAccess) -> Result<TestReport, LangError> {
+    let mut results: Vec<TestResult> = vec![];
+    for file in source_files(path)? {
+        run_file_tests(&file, file_system, &mut results);
+    }
+    Ok(TestReport { results })
+}
+
+/// The path if it is a file, or the `.hlw` files in the directory and its subdirectories, sorted by path.
+pub fn source_files(path: &Path) -> Result<Vec<PathBuf>, LangError> {
+    let mut files: Vec<PathBuf> = vec![];
+    if path.is_dir() {
+        find_source_files(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(files)
+}
+
+fn find_source_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<(), LangError> {
+    let could_not_read = |e: ::std::io::Error| {
+        LangError::CouldNotReadFile {
+            filename: directory.display().to_string(),
+            reason: e.to_string(),
+        }
+    };
+    let mut entries: Vec<PathBuf> = vec![];
+    for entry in fs::read_dir(directory).map_err(could_not_read)? {
+        entries.push(entry.map_err(could_not_read)?.path());
+    }
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            find_source_files(&entry, files)?;
+        } else if entry.extension() == Some(OsStr::new("hlw")) {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+fn run_file_tests(file: &Path, file_system: &FileSystemAccess, results: &mut Vec<TestResult>) {
+    let load_failed = |error: LangError| TestResult {
+        file: file.to_path_buf(),
+        name: None,
+        outcome: Err(error),
+    };
+    let source: String = match fs::read_to_string(file) {
+        Ok(source) => source,
+        Err(e) => {
+            results.push(load_failed(LangError::CouldNotReadFile { filename: file.display().to_string(), reason: e.to_string() }));
+            return;
+        }
+    };
+    // Other files are programs, which shouldn't be run just to find out they have no tests.
+    if !declares_tests(&source) {
+        return;
+    }
+    let mut interpreter: Interpreter = Interpreter::new();
+    interpreter.set_file_system_access(file_system.clone());
+    if let Err(error) = interpreter.load_file(&file.to_string_lossy()) {
+        results.push(load_failed(error));
+        return;
+    }
+    let loaded: Snapshot = interpreter.snapshot();
+    for name in test_names(&interpreter) {
+        let outcome: Result<(), LangError> = interpreter.call_function(&name, vec![]).map(|_| ());
+        interpreter.restore_snapshot(&loaded);
+        results.push(TestResult {
+            file: file.to_path_buf(),
+            name: Some(name),
+            outcome,
+        });
+    }
+}
+
+/// Checks if the source declares a function that could be a test.
+/// Source that can't be parsed counts if it looks like it does, so broken test files are reported rather than skipped.
+fn declares_tests(source: &str) -> bool {
+    let test_declaration: String = format!("fn {}", TEST_FUNCTION_PREFIX);
+    let statements: Vec<Ast> = match parse(source) {
+        Ok(Ast::ExpressionList(statements)) => statements,
+        Ok(_) => return false,
+        Err(_) => return source.contains(&test_declaration),
+    };
+    statements.iter().any(|statement| match *statement {
+        Ast::SExpr(SExpression::DeclareFunction { ref identifier, .. }) => {
+            matches!(**identifier, Ast::ValueIdentifier(ref name) if name.starts_with(TEST_FUNCTION_PREFIX))
+        }
+        _ => false,
+    })
+}
+
+fn test_names(interpreter: &Interpreter) -> Vec<String> {
+    interpreter.global_names()
+        .into_iter()
+        .filter(|name| name.starts_with(TEST_FUNCTION_PREFIX))
+        .filter(|name| {
+            match interpreter.get_global(name).map(|value| TypeInfo::from(value.as_ref().clone())) {
+                Some(TypeInfo::Function { ref parameters, .. }) => parameters.is_empty(),
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FIXTURES: &str = "src/testing/fixtures";
+
+    #[test]
+    fn runs_each_test_function() {
+        let report = run_tests(Path::new(FIXTURES), &FileSystemAccess::Denied).unwrap();
+        let names: Vec<String> = report.results.iter().map(TestResult::description).collect();
+        assert_eq!(
+            vec![
+                "src/testing/fixtures/math_test.hlw::test_addition".to_string(),
+                "src/testing/fixtures/math_test.hlw::test_division_by_zero".to_string(),
+                "src/testing/fixtures/math_test.hlw::test_wrong_sum".to_string(),
+            ],
+            names
+        );
+        assert_eq!(2, report.passed());
+        assert_eq!(1, report.failed());
+        assert_eq!(
+            Err(LangError::AssertionFailed("Assertion failed: left == right\n  left: 5\n right: 4".to_string())),
+            report.results[2].outcome
+        );
+    }
+
+    #[test]
+    fn tests_do_not_share_state() {
+        let report = run_tests(Path::new("src/testing/fixtures/math_test.hlw"), &FileSystemAccess::Denied).unwrap();
+        // test_addition increments a global that test_wrong_sum expects to be unchanged.
+        assert!(report.results[0].outcome.is_ok());
+        assert!(report.to_string().ends_with("test result: FAILED. 2 passed; 1 failed\n"));
+    }
+
+    #[test]
+    fn files_without_tests_are_not_run() {
+        let report = run_tests(Path::new("src/testing/fixtures/program.hlw"), &FileSystemAccess::Denied).unwrap();
+        assert_eq!(Vec::<TestResult>::new(), report.results);
+        let report = run_tests(Path::new("examples"), &FileSystemAccess::Denied).unwrap();
+        assert_eq!((2, 0), (report.passed(), report.failed()));
+    }
+
+    #[test]
+    fn broken_test_files_are_reported() {
+        assert!(declares_tests("fn test_broken() -> Bool {"));
+        assert!(!declares_tests("let a := ("));
+        assert!(declares_tests("fn test_a() -> Bool { true }"));
+        assert!(!declares_tests("fn helper() -> Bool { true }\nlet test_value := 1"));
+    }
+}
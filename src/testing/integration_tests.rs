@@ -326,7 +326,7 @@ mod test {
             _ => panic!(),
         };
 
-        assert_eq!(Datatype::Number(180), *ast.evaluate(&mut map).unwrap())
+        assert_eq!(Datatype::Number(108), *ast.evaluate(&mut map).unwrap())
     }
 
 
@@ -386,7 +386,11 @@ mod test {
         struct_map.insert("a".to_string(), Datatype::Number(8));
         struct_map.insert("b".to_string(), Datatype::Number(3));
         assert_eq!(
-            Datatype::Struct { map: struct_map },
+            Datatype::Struct {
+                identifier: Some("MyStruct".to_string()),
+                field_order: vec!["a".to_string(), "b".to_string()],
+                map: struct_map,
+            },
             *ast.evaluate(&mut map).unwrap()
         )
     }
@@ -417,7 +421,11 @@ mod test {
         struct_map.insert("a".to_string(), Datatype::Number(8));
         struct_map.insert("b".to_string(), Datatype::Number(3));
         assert_eq!(
-            Datatype::Struct { map: struct_map },
+            Datatype::Struct {
+                identifier: Some("MyStruct".to_string()),
+                field_order: vec!["a".to_string(), "b".to_string()],
+                map: struct_map,
+            },
             *ast.evaluate(&mut map).unwrap()
         )
     }
@@ -450,7 +458,11 @@ mod test {
         struct_map.insert("a".to_string(), Datatype::Number(8));
         struct_map.insert("b".to_string(), Datatype::Number(3));
         assert_eq!(
-            Datatype::Struct { map: struct_map },
+            Datatype::Struct {
+                identifier: Some("MyStruct".to_string()),
+                field_order: vec!["a".to_string(), "b".to_string()],
+                map: struct_map,
+            },
             *ast.evaluate(&mut map).unwrap()
         )
     }
@@ -483,7 +495,11 @@ mod test {
         struct_map.insert("a".to_string(), Datatype::Number(8));
         struct_map.insert("b".to_string(), Datatype::Number(3));
         assert_eq!(
-            Datatype::Struct { map: struct_map },
+            Datatype::Struct {
+                identifier: Some("MyStruct".to_string()),
+                field_order: vec!["a".to_string(), "b".to_string()],
+                map: struct_map,
+            },
             *ast.evaluate(&mut map).unwrap()
         )
     }
@@ -603,6 +619,488 @@ mod test {
        assert_eq!(Datatype::Number(6), *ast.evaluate(&mut map).unwrap());
     }
 
+    #[test]
+    fn program_numeric_conversion_builtins_test() {
+        use ast::type_checking::TypeStore;
+        use std_functions::{add_std_functions, add_std_function_types};
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        add_std_functions(&mut map);
+        add_std_function_types(&mut type_store);
+        let input_string = r#"
+        fn average( a: Number, b: Number ) -> Float {
+            (to_float(a) + b) / 2
+        }
+        round(average(2, 5)) + floor(2.9) + to_number(-1.5)
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        ast.check_types(&mut type_store).unwrap();
+        assert_eq!(Datatype::Number(5), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_bitwise_and_power_operators_test() {
+        use ast::type_checking::TypeStore;
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        let input_string = r#"
+        let flags := 5 | 2
+        let masked := flags & ~1
+        let shifted := (masked ^ 1) << 2 >> 1
+        shifted + 2 ** 3 ** 2
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(TypeInfo::Number, ast.check_types(&mut type_store).unwrap().get_type());
+        assert_eq!(Datatype::Number(14 + 512), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_compound_assignment_and_increment_test() {
+        let mut map: VariableStore = VariableStore::new();
+        let input_string = r#"
+        let total := 0
+        let i := 0
+        while i < 5 {
+            total += i
+            i++
+        }
+        total *= 3
+        total -= 2
+        total %= 7
+        total
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(Datatype::Number(28 % 7), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_array_of_expressions_test() {
+        use ast::type_checking::TypeStore;
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        let input_string = r#"
+        fn double( x: Number ) -> Number {
+            x * 2
+        }
+        let a := 1
+        let b := 3
+        let xs := [a, double(b), b * 2]
+        let grid := [[1, 2], [3, 4], xs]
+        grid[2][1] + grid[1][0]
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(TypeInfo::Number, ast.check_types(&mut type_store).unwrap().get_type());
+        assert_eq!(Datatype::Number(9), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_array_of_structs_test() {
+        use ast::type_checking::TypeStore;
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        let input_string = r#"
+        struct Point {
+            x: Number
+            y: Number
+        }
+        let points := [new Point { x: 1 y: 2 }, new Point { x: 3 y: 4 }]
+        points[1].y
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(TypeInfo::Number, ast.check_types(&mut type_store).unwrap().get_type());
+        assert_eq!(
+            TypeInfo::Array(Box::new(TypeInfo::StructType { identifier: "Point".to_string() })),
+            type_store.get("points").unwrap().clone().get_type()
+        );
+        assert_eq!(Datatype::Number(4), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_array_mismatched_elements_test() {
+        use ast::type_checking::{TypeStore, TypeError};
+        use lang_result::LangError;
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        let input_string = r#"
+        let a := "Hello"
+        [1, a]
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(
+            TypeError::MismatchedArrayElementTypes { expected: TypeInfo::Number, found: TypeInfo::String },
+            ast.check_types(&mut type_store).unwrap_err()
+        );
+        assert_eq!(
+            LangError::MismatchedArrayElementTypes { expected: TypeInfo::Number, found: TypeInfo::String },
+            ast.evaluate(&mut map).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn program_annotated_empty_array_test() {
+        use ast::type_checking::TypeStore;
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        let input_string = r#"
+        struct Point {
+            x: Number
+        }
+        let xs: [Number] := []
+        const origin: Point := new Point { x: 0 }
+        let points: [Point] := [origin]
+        let ys := []
+        ys := ["inferred"]
+        xs
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(TypeInfo::Array(Box::new(TypeInfo::Number)), ast.check_types(&mut type_store).unwrap().get_type());
+        assert_eq!(TypeInfo::Array(Box::new(TypeInfo::String)), type_store.get("ys").unwrap().clone().get_type());
+        assert_eq!(Datatype::Array { value: vec![], type_: TypeInfo::Number }, *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_annotated_declaration_mismatch_test() {
+        use ast::type_checking::{TypeStore, TypeError};
+        use lang_result::LangError;
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        let input_string = r#"
+        let a: String := 5
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(TypeError::TypeMismatch(TypeInfo::String, TypeInfo::Number), ast.check_types(&mut type_store).unwrap_err());
+        assert_eq!(LangError::TypeError { expected: TypeInfo::String, found: TypeInfo::Number }, ast.evaluate(&mut map).unwrap_err());
+    }
+
+    #[test]
+    fn program_array_std_functions_test() {
+        use ast::type_checking::TypeStore;
+        use std_functions::{add_std_functions, add_std_function_types};
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        add_std_functions(&mut map);
+        add_std_function_types(&mut type_store);
+        let input_string = r#"
+        let xs := []
+        for n in [0..5] {
+            xs := push(xs, 4 - n)
+        }
+        xs := sort(concat(xs, [10, 7]))
+        let middle := slice(remove(xs, 0), 1, 3)
+        let found := contains(middle, 3) && index_of(xs, 10) == len(xs) - 1
+        if found {
+            insert(pop(reverse(middle)), 0, 42)
+        } else {
+            []
+        }
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        ast.check_types(&mut type_store).unwrap();
+        assert_eq!(TypeInfo::Array(Box::new(TypeInfo::Number)), type_store.get("xs").unwrap().clone().get_type());
+        assert_eq!(
+            Datatype::Array {
+                value: vec![Rc::new(Datatype::Number(42)), Rc::new(Datatype::Number(3))],
+                type_: TypeInfo::Number
+            },
+            *ast.evaluate(&mut map).unwrap()
+        );
+    }
+
+    #[test]
+    fn program_higher_order_std_functions_test() {
+        use ast::type_checking::TypeStore;
+        use std_functions::{add_std_functions, add_std_function_types};
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        add_std_functions(&mut map);
+        add_std_function_types(&mut type_store);
+        let input_string = r#"
+        fn square(x: Number) -> Number { x * x }
+        fn is_even(x: Number) -> Bool { x % 2 == 0 }
+        fn add(total: Number, x: Number) -> Number { total + x }
+        let squares := map([1, 2, 3, 4], square)
+        let even_squares := filter(squares, is_even)
+        let total := reduce(even_squares, 0, add)
+        let weighted := 0
+        for pair in zip(even_squares, map(even_squares, to_float)) {
+            weighted := weighted + pair.first
+        }
+        for entry in enumerate(squares) {
+            weighted := weighted + entry.index
+        }
+        if any(squares, is_even) && !(all(squares, is_even)) {
+            total + weighted
+        } else {
+            0
+        }
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        ast.check_types(&mut type_store).unwrap();
+        assert_eq!(TypeInfo::Array(Box::new(TypeInfo::Number)), type_store.get("even_squares").unwrap().clone().get_type());
+        assert_eq!(TypeInfo::Number, type_store.get("total").unwrap().clone().get_type());
+        assert_eq!(Datatype::Number(46), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_string_std_functions_test() {
+        use ast::type_checking::TypeStore;
+        use std_functions::{add_std_functions, add_std_function_types};
+        let mut map: VariableStore = VariableStore::new();
+        let mut type_store: TypeStore = TypeStore::new();
+        add_std_functions(&mut map);
+        add_std_function_types(&mut type_store);
+        let input_string = r#"
+        let words := split(trim("  hello, big world  "), ", ")
+        let last := split(words[1], " ")[1]
+        let initials := ""
+        for word in words {
+            initials := initials + to_upper(word[0])
+        }
+        let found := contains(words[0], "ell") && starts_with(last, "wo") && ends_with(last, "ld") && contains(words, "hello")
+        if found && find(last, "r") == 2 && len(chars(last)) == len(last) {
+            join([initials, replace(substring(words[0], 0, 4), "l", "L"), to_lower("OK")], "-")
+        } else {
+            ""
+        }
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        ast.check_types(&mut type_store).unwrap();
+        assert_eq!(TypeInfo::Array(Box::new(TypeInfo::String)), type_store.get("words").unwrap().clone().get_type());
+        assert_eq!(TypeInfo::String, type_store.get("last").unwrap().clone().get_type());
+        assert_eq!(Datatype::String("HB-heLL-ok".to_string()), *ast.evaluate(&mut map).unwrap());
+    }
+
+    #[test]
+    fn program_output_is_captured_test() {
+        use interpreter::Interpreter;
+        use io_context::{IoContext, FileSystemAccess};
+        use std::cell::RefCell;
+        let stdout = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let stderr = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_io_context(IoContext {
+            stdin: Rc::new(RefCell::new(::std::io::empty())),
+            stdout: stdout.clone(),
+            stderr: stderr.clone(),
+            file_system: FileSystemAccess::Denied,
+        });
+        let input_string = r#"
+        struct Point {
+            x: Number
+        }
+        let p := new Point { x: 3 }
+        for n in [0..2] {
+            println("line " + n)
+        }
+        print("x = " + p.x)
+        eprint("warning: ")
+        eprintln("done")
+         "#;
+
+        assert_eq!(
+            Datatype::String("done\n".to_string()),
+            *interpreter.eval_str(input_string).unwrap()
+        );
+        assert_eq!("line 0\nline 1\nx = 3", String::from_utf8(stdout.borrow().clone()).unwrap());
+        assert_eq!("warning: done\n", String::from_utf8(stderr.borrow().clone()).unwrap());
+    }
+
+    #[test]
+    fn program_prints_any_value_test() {
+        use interpreter::Interpreter;
+        use io_context::{IoContext, FileSystemAccess};
+        use std::cell::RefCell;
+        let stdout = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let stderr = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_io_context(IoContext {
+            stdin: Rc::new(RefCell::new(::std::io::empty())),
+            stdout: stdout.clone(),
+            stderr: stderr.clone(),
+            file_system: FileSystemAccess::Denied,
+        });
+        let input_string = r#"
+        struct P {
+            x: Number
+        }
+        print([1, 2])
+        println(new P { x: 1 })
+        println(3)
+        eprintln([true])
+        eprint(new P { x: 2 })
+        to_string([1.5]) + "!"
+         "#;
+
+        assert_eq!(
+            Datatype::String("[1.5]!".to_string()),
+            *interpreter.eval_str(input_string).unwrap()
+        );
+        assert_eq!("[1, 2]P { x: 1 }\n3\n", String::from_utf8(stdout.borrow().clone()).unwrap());
+        assert_eq!("[true]\nP { x: 2 }", String::from_utf8(stderr.borrow().clone()).unwrap());
+    }
+
+    #[test]
+    fn program_reads_scripted_input_test() {
+        use interpreter::Interpreter;
+        use io_context::{IoContext, FileSystemAccess};
+        use std::cell::RefCell;
+        use std::io::Cursor;
+        let stdout = Rc::new(RefCell::new(Vec::<u8>::new()));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_io_context(IoContext {
+            stdin: Rc::new(RefCell::new(Cursor::new("3\n1.5\nsecond line\nthird line\n"))),
+            stdout: stdout.clone(),
+            stderr: Rc::new(RefCell::new(::std::io::sink())),
+            file_system: FileSystemAccess::Denied,
+        });
+        let input_string = r#"
+        let count := parse_number(read_line())
+        let scale := parse_float(read_line())
+        let rest := read_all()
+        println("lines left: " + len(split(trim(rest), "\n")))
+        to_float(count) * scale
+         "#;
+
+        assert_eq!(Datatype::Float(4.5), *interpreter.eval_str(input_string).unwrap());
+        assert_eq!("lines left: 2\n", String::from_utf8(stdout.borrow().clone()).unwrap());
+        assert_eq!(Datatype::String("".to_string()), *interpreter.eval_str("read_line()").unwrap());
+    }
+
+    #[test]
+    fn program_try_catch_test() {
+        use interpreter::Interpreter;
+        let mut interpreter = Interpreter::new();
+        let input_string = r#"
+        fn parse_or_default(text: String, default: Number) -> Number {
+            try {
+                parse_number(text)
+            } catch e {
+                default
+            }
+        }
+        let xs := [1, 2]
+        let message := ""
+        try {
+            xs[5]
+        } catch error {
+            message := error
+        }
+        let total := parse_or_default("40", 0) + parse_or_default("forty", 2)
+        try {
+            total := total / 0
+        } catch e {
+            message := message + ", " + e
+        }
+        message + ": " + total
+         "#;
+
+        assert_eq!(
+            Datatype::String("Index out of bounds, Division by zero: 42".to_string()),
+            *interpreter.eval_str(input_string).unwrap()
+        );
+    }
+
+    #[test]
+    fn program_file_system_test() {
+        use interpreter::Interpreter;
+        use io_context::FileSystemAccess;
+        use std::env;
+        use std::fs;
+        let directory = env::temp_dir().join(format!("hlwnpa_program_file_system_test_{}", ::std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("directory", Datatype::from(directory.to_string_lossy().into_owned()));
+        let input_string = r#"
+        let path := directory + "/log.txt"
+        let denied := read_file("Cargo.toml")
+        let written := write_file(path, "a")
+        let appended := append_file(path, "b")
+        let contents := read_file(path).value
+        let entries := list_dir(directory).value
+        !denied.ok && written.ok && appended.ok && file_exists(path).value && contents == "ab" && len(entries) == 1
+         "#;
+
+        let denied_program = "let result := write_file(directory + \"/log.txt\", \"a\")\n!result.ok && len(result.error) > 0";
+        assert_eq!(Datatype::Bool(true), *interpreter.eval_str(denied_program).unwrap());
+        interpreter.set_file_system_access(FileSystemAccess::Within(directory.clone()));
+        let result = interpreter.eval_str(input_string);
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(Datatype::Bool(true), *result.unwrap());
+    }
+
+    #[test]
+    fn program_mixed_numeric_comparison_test() {
+        let mut map: VariableStore = VariableStore::new();
+        let input_string = r#"
+        let a := 1 < 1.5
+        let b := 1.5 > 1
+        let c := 2 == 2.0
+        a && b && c
+         "#;
+        let (_, ast) = match program(input_string.as_bytes()) {
+            IResult::Done(rest, v) => (rest, v),
+            IResult::Error(e) => panic!("{}", e),
+            _ => panic!(),
+        };
+
+        assert_eq!(Datatype::Bool(true), *ast.evaluate(&mut map).unwrap());
+    }
+
 
     mod benches {
         use super::*;